@@ -1,4 +1,5 @@
 //! Complex numbers
+use std::fmt;
 use std::ops::{Add, Sub, Mul, Div, Neg};
 
 /// Complex number
@@ -18,6 +19,88 @@ impl Complex {
     pub fn conj(self) -> Complex {
         Complex { re: self.re, im: -self.im }
     }
+    /// Create a complex number from its magnitude and argument
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+    /// Create the complex number on the unit circle at angle `theta` (cos theta + i sin theta)
+    pub fn cis(theta: f64) -> Complex {
+        Complex::from_polar(1.0, theta)
+    }
+    /// Compute the magnitude (absolute value) of a number
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+    /// Compute the squared magnitude of a number (cheaper than `abs`)
+    pub fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    /// Compute the argument (phase angle) of a number, in radians in the range (-pi, pi]
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+    /// Compute the magnitude and argument of a number
+    pub fn to_polar(self) -> (f64, f64) {
+        (self.abs(), self.arg())
+    }
+    /// Compute the exponential function, e^z
+    pub fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+    /// Compute the natural logarithm (principal branch, with imaginary part in (-pi, pi])
+    pub fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+    /// Compute the principal square root (with non-negative real part)
+    pub fn sqrt(self) -> Complex {
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+    /// Compute the sine function
+    pub fn sin(self) -> Complex {
+        Complex::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+    /// Compute the cosine function
+    pub fn cos(self) -> Complex {
+        Complex::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+    /// Raise a number to an integer power (by repeated squaring, so that small powers are exact)
+    pub fn powi(self, n: i64) -> Complex {
+        let mut result = Complex::new(1.0, 0.0);
+        let mut base = self;
+        let mut exp = n.abs();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        if n < 0 { Complex::new(1.0, 0.0) / result } else { result }
+    }
+    /// Raise a number to a real power (principal value)
+    pub fn powf(self, x: f64) -> Complex {
+        if self.re == 0.0 && self.im == 0.0 {
+            return if x == 0.0 { Complex::new(1.0, 0.0) } else { self };
+        }
+        let (r, theta) = self.to_polar();
+        Complex::from_polar(r.powf(x), theta * x)
+    }
+    /// Raise a number to a complex power (principal value, e^(w ln z))
+    pub fn powc(self, w: Complex) -> Complex {
+        if self.re == 0.0 && self.im == 0.0 {
+            return if w.re == 0.0 && w.im == 0.0 { Complex::new(1.0, 0.0) } else { self };
+        }
+        (w * self.ln()).exp()
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}+{}i", self.re, self.im)
+    }
 }
 
 impl Add for Complex {
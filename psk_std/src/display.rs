@@ -0,0 +1,94 @@
+//! Printing values in transpiled source code, in the same format as the interpreter prints them
+//! (e.g. `[9, 2.5, 4]` for an array of floats, where Rust's debug format would print
+//! `[9.0, 2.5, 4.0]`).
+
+use std::fmt;
+
+use complex::Complex;
+
+/// Values which can be printed (with `print`) or converted to a string (with `str`).
+pub trait Show {
+    /// Write this value in the interpreter's format.
+    fn show(&self, f: &mut fmt::Formatter) -> fmt::Result;
+}
+
+/// Wrapper implementing `Display` for a value which can be shown.
+pub struct Display<'a, T: 'a + ?Sized>(&'a T);
+impl<'a, T: Show + ?Sized> fmt::Display for Display<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.show(f)
+    }
+}
+
+/// Display `value` in the interpreter's format.
+pub fn display<'a, T: Show + ?Sized>(value: &'a T) -> Display<'a, T> {
+    Display(value)
+}
+
+macro_rules! show_with_display {
+    ($($ty:ty),*) => {
+        $(
+            impl Show for $ty {
+                fn show(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "{}", self)
+                }
+            }
+        )*
+    }
+}
+show_with_display!(i64, f64, bool, String, Complex);
+
+/// Write `elems` separated by commas.
+fn show_list<'a, I>(elems: I, f: &mut fmt::Formatter) -> fmt::Result
+        where I: Iterator<Item=&'a dyn Show> {
+    for (i, elem) in elems.enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        elem.show(f)?;
+    }
+    Ok(())
+}
+
+impl<T: Show> Show for Vec<T> {
+    fn show(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[")?;
+        show_list(self.iter().map(|elem| elem as &dyn Show), f)?;
+        write!(f, "]")
+    }
+}
+
+macro_rules! show_tuple {
+    ($($name:ident: $index:tt),*) => {
+        impl<$($name: Show),*> Show for ($($name,)*) {
+            fn show(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "(")?;
+                show_list([$(&self.$index as &dyn Show),*].iter().cloned(), f)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+show_tuple!(A: 0, B: 1);
+show_tuple!(A: 0, B: 1, C: 2);
+show_tuple!(A: 0, B: 1, C: 2, D: 3);
+show_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+show_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+show_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+show_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+/// Write the fields of a struct named `name`, in the interpreter's format (e.g.
+/// `Camera { center: -0.5+0i, zoom: 2 }`). Used by the `Show` implementations generated for the
+/// structs of transpiled programs.
+pub fn show_struct(name: &str, fields: &[(&str, &dyn Show)], f: &mut fmt::Formatter)
+        -> fmt::Result {
+    write!(f, "{} {{ ", name)?;
+    for (i, &(field, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}: ", field)?;
+        value.show(f)?;
+    }
+    write!(f, " }}")
+}
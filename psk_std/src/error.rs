@@ -0,0 +1,32 @@
+//! Errors raised by standard library functions.
+
+use std::error::Error;
+use std::fmt;
+
+/// Error raised by a standard library function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StdlibError {
+    /// Name of the function which raised the error
+    pub function: String,
+    /// Description of the error
+    pub message: String,
+}
+impl StdlibError {
+    /// Create an error raised by the standard library function `function`.
+    pub fn new<F: Into<String>, M: Into<String>>(function: F, message: M) -> StdlibError {
+        StdlibError { function: function.into(), message: message.into() }
+    }
+}
+impl fmt::Display for StdlibError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.function, self.message)
+    }
+}
+impl Error for StdlibError {}
+// transpiled programs report errors as strings
+impl From<StdlibError> for String {
+    fn from(err: StdlibError) -> String { err.to_string() }
+}
+
+/// Result of a standard library function.
+pub type StdResult<T> = Result<T, StdlibError>;
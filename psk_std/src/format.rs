@@ -0,0 +1,249 @@
+//! String formatting used by the `format` built-in function, in both interpreted and transpiled
+//! code.
+//!
+//! Format strings contain `{}` placeholders which are replaced, in order, by the supplied
+//! arguments. A placeholder may contain a specification after a colon: `{:[align][0][width]
+//! [.precision]}`, where `align` is one of `<`, `^` or `>`, a `0` pads numbers with zeros
+//! instead of spaces, and `precision` is the number of digits after the decimal point for
+//! floating-point values (or the maximum length of a string). Literal braces are written as `{{`
+//! and `}}`.
+
+use std::iter::repeat;
+
+use complex::Complex;
+
+/// Alignment of a formatted value within its field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    /// Pad on the right
+    Left,
+    /// Pad evenly on both sides (extra padding on the right)
+    Center,
+    /// Pad on the left
+    Right,
+}
+
+/// Specification of how to format a single value, parsed from a placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FormatSpec {
+    /// Alignment within the field; `None` right-aligns numbers and left-aligns everything else
+    pub align: Option<Align>,
+    /// Whether numbers are padded with zeros (after any sign) instead of spaces
+    pub zero: bool,
+    /// Minimum width of the field
+    pub width: usize,
+    /// Digits after the decimal point for floating-point values, maximum length for strings
+    pub precision: Option<usize>,
+}
+
+/// Piece of a parsed format string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatPiece {
+    /// Literal text, copied to the output
+    Text(String),
+    /// Placeholder, replaced by the next argument
+    Placeholder(FormatSpec),
+}
+
+/// Values which can be formatted with a `FormatSpec`.
+pub trait Format {
+    /// Format this value, without any padding.
+    fn format_body(&self, precision: Option<usize>) -> String;
+    /// Whether this value is numeric; numbers are right-aligned by default and can be padded
+    /// with zeros.
+    fn is_numeric(&self) -> bool { true }
+}
+
+impl Format for i64 {
+    fn format_body(&self, _: Option<usize>) -> String {
+        self.to_string()
+    }
+}
+
+impl Format for f64 {
+    fn format_body(&self, precision: Option<usize>) -> String {
+        match precision {
+            Some(precision) => format!("{:.*}", precision, self),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl Format for Complex {
+    fn format_body(&self, precision: Option<usize>) -> String {
+        format!("{}+{}i", self.re.format_body(precision), self.im.format_body(precision))
+    }
+}
+
+impl Format for bool {
+    fn format_body(&self, _: Option<usize>) -> String {
+        self.to_string()
+    }
+    fn is_numeric(&self) -> bool { false }
+}
+
+impl Format for String {
+    fn format_body(&self, precision: Option<usize>) -> String {
+        match precision {
+            Some(precision) => self.chars().take(precision).collect(),
+            None => self.clone(),
+        }
+    }
+    fn is_numeric(&self) -> bool { false }
+}
+
+/// Format a single value according to a specification.
+pub fn format_value<T: Format + ?Sized>(value: &T, spec: &FormatSpec) -> String {
+    let body = value.format_body(spec.precision);
+    let len = body.chars().count();
+    if len >= spec.width {
+        return body;
+    }
+    let pad = spec.width - len;
+    if spec.zero && spec.align.is_none() && value.is_numeric() {
+        // zeros go between the sign and the digits
+        let (sign, digits) = if body.starts_with('-') || body.starts_with('+') {
+            body.split_at(1)
+        } else {
+            ("", &body[..])
+        };
+        return format!("{}{}{}", sign, zeros(pad), digits);
+    }
+    let align = spec.align.unwrap_or(if value.is_numeric() { Align::Right } else { Align::Left });
+    match align {
+        Align::Left => format!("{}{}", body, spaces(pad)),
+        Align::Center => format!("{}{}{}", spaces(pad / 2), body, spaces(pad - pad / 2)),
+        Align::Right => format!("{}{}", spaces(pad), body),
+    }
+}
+
+fn spaces(n: usize) -> String {
+    repeat(' ').take(n).collect()
+}
+fn zeros(n: usize) -> String {
+    repeat('0').take(n).collect()
+}
+
+/// Parse a format string into its literal text and placeholders.
+pub fn parse_format(fmt: &str) -> Result<Vec<FormatPiece>, String> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            },
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => { break; },
+                        Some(c) => { placeholder.push(c); },
+                        None => { return Err(format!("unterminated placeholder in format \
+                            string \"{}\"", fmt)); }
+                    }
+                }
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(text));
+                    text = String::new();
+                }
+                pieces.push(FormatPiece::Placeholder(parse_spec(&placeholder)?));
+            },
+            '}' => {
+                return Err(format!("unmatched '}}' in format string \"{}\"", fmt));
+            },
+            c => { text.push(c); }
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Parse the contents of a placeholder (the text between the braces).
+fn parse_spec(placeholder: &str) -> Result<FormatSpec, String> {
+    let mut spec = FormatSpec::default();
+    if placeholder.is_empty() {
+        return Ok(spec);
+    }
+    if !placeholder.starts_with(':') {
+        return Err(format!("invalid placeholder '{{{}}}': expected '{{}}' or '{{:spec}}'",
+            placeholder));
+    }
+    let mut rest = &placeholder[1..];
+    spec.align = match rest.chars().next() {
+        Some('<') => Some(Align::Left),
+        Some('^') => Some(Align::Center),
+        Some('>') => Some(Align::Right),
+        _ => None
+    };
+    if spec.align.is_some() {
+        rest = &rest[1..];
+    }
+    if rest.starts_with('0') {
+        spec.zero = true;
+        rest = &rest[1..];
+    }
+    let (width, after_width) = split_number(rest);
+    spec.width = width.unwrap_or(0);
+    rest = after_width;
+    if rest.starts_with('.') {
+        let (precision, after_precision) = split_number(&rest[1..]);
+        if precision.is_none() {
+            return Err(format!("missing precision in placeholder '{{{}}}'", placeholder));
+        }
+        spec.precision = precision;
+        rest = after_precision;
+    }
+    if !rest.is_empty() {
+        return Err(format!("invalid placeholder '{{{}}}'", placeholder));
+    }
+    Ok(spec)
+}
+
+/// Split a leading unsigned number (if any) off of a string.
+fn split_number(s: &str) -> (Option<usize>, &str) {
+    let end = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+    if end == 0 {
+        (None, s)
+    } else {
+        (s[..end].parse().ok(), &s[end..])
+    }
+}
+
+/// Number of placeholders (and therefore arguments) in a format string.
+pub fn placeholder_count(fmt: &str) -> Result<usize, String> {
+    Ok(parse_format(fmt)?.iter().filter(|piece| match **piece {
+        FormatPiece::Placeholder(_) => true,
+        FormatPiece::Text(_) => false,
+    }).count())
+}
+
+/// Format the arguments according to the format string `fmt`. Fails if the format string is
+/// invalid or the number of arguments does not match the number of placeholders.
+pub fn format(fmt: &str, args: &[&dyn Format]) -> Result<String, String> {
+    let pieces = parse_format(fmt)?;
+    let mut args = args.iter();
+    let mut output = String::new();
+    for piece in pieces {
+        match piece {
+            FormatPiece::Text(text) => { output.push_str(&text); },
+            FormatPiece::Placeholder(spec) => {
+                let arg = args.next().ok_or(format!("too few arguments for format string \
+                    \"{}\"", fmt))?;
+                output.push_str(&format_value(*arg, &spec));
+            }
+        }
+    }
+    if args.next().is_some() {
+        return Err(format!("too many arguments for format string \"{}\"", fmt));
+    }
+    Ok(output)
+}
@@ -4,11 +4,21 @@
 
 extern crate image as img;
 
+/// Version of this library, which transpiled programs depend on.
+pub const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
 mod image;
 mod extrema;
 pub mod stdlib;
+mod math;
 mod environment;
 pub use self::environment::*;
+mod error;
+pub use self::error::*;
 pub mod complex;
+pub mod ops;
+pub mod format;
+pub mod display;
 
+pub mod set;
 pub mod step_range;
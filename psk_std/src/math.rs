@@ -0,0 +1,92 @@
+//! Mathematical standard library functions.
+//!
+//! Functions which are overloaded for several argument types in piske are named after the piske
+//! function followed by the parameter types (e.g. `sqrt_float` and `sqrt_complex` for `sqrt`).
+
+use environment::Environment;
+use error::{StdlibError, StdResult};
+use complex::Complex;
+
+/// Absolute value of an integer (`abs` for int arguments).
+pub fn abs_int(_: &mut Environment, x: i64) -> StdResult<i64> { Ok(x.abs()) }
+/// Absolute value of a floating-point number (`abs` for float arguments).
+pub fn abs_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.abs()) }
+/// Magnitude of a complex number (`abs` for complex arguments).
+pub fn abs_complex(_: &mut Environment, c: Complex) -> StdResult<f64> { Ok(c.abs()) }
+
+/// Smaller of two integers.
+pub fn min_int(_: &mut Environment, a: i64, b: i64) -> StdResult<i64> { Ok(a.min(b)) }
+/// Smaller of two floating-point numbers.
+pub fn min_float(_: &mut Environment, a: f64, b: f64) -> StdResult<f64> { Ok(a.min(b)) }
+/// Larger of two integers.
+pub fn max_int(_: &mut Environment, a: i64, b: i64) -> StdResult<i64> { Ok(a.max(b)) }
+/// Larger of two floating-point numbers.
+pub fn max_float(_: &mut Environment, a: f64, b: f64) -> StdResult<f64> { Ok(a.max(b)) }
+/// Restrict an integer to the interval [lo, hi].
+pub fn clamp_int(_: &mut Environment, x: i64, lo: i64, hi: i64) -> StdResult<i64> {
+    if lo > hi {
+        return Err(StdlibError::new("clamp", format!("invalid interval: [{}, {}]", lo, hi)));
+    }
+    Ok(if x < lo { lo } else if x > hi { hi } else { x })
+}
+/// Restrict a floating-point number to the interval [lo, hi].
+pub fn clamp_float(_: &mut Environment, x: f64, lo: f64, hi: f64) -> StdResult<f64> {
+    if lo > hi {
+        return Err(StdlibError::new("clamp", format!("invalid interval: [{}, {}]", lo, hi)));
+    }
+    Ok(if x < lo { lo } else if x > hi { hi } else { x })
+}
+/// Linear interpolation between `a` (at `t` = 0) and `b` (at `t` = 1).
+pub fn lerp(_: &mut Environment, a: f64, b: f64, t: f64) -> StdResult<f64> {
+    Ok(a + (b - a) * t)
+}
+
+/// Largest integer value less than or equal to a number.
+pub fn floor(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.floor()) }
+/// Smallest integer value greater than or equal to a number.
+pub fn ceil(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.ceil()) }
+/// Nearest integer value to a number (rounding half-way cases away from zero).
+pub fn round(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.round()) }
+
+/// Square root of a floating-point number (NaN for negative numbers).
+pub fn sqrt_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.sqrt()) }
+/// Complex square root (principal branch).
+pub fn sqrt_complex(_: &mut Environment, c: Complex) -> StdResult<Complex> { Ok(c.sqrt()) }
+/// Exponential function.
+pub fn exp_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.exp()) }
+/// Complex exponential function.
+pub fn exp_complex(_: &mut Environment, c: Complex) -> StdResult<Complex> { Ok(c.exp()) }
+/// Natural logarithm.
+pub fn log_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.ln()) }
+/// Complex natural logarithm (principal branch).
+pub fn log_complex(_: &mut Environment, c: Complex) -> StdResult<Complex> { Ok(c.ln()) }
+/// Base-10 logarithm.
+pub fn log10(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.log10()) }
+
+/// Sine function (of an angle in radians).
+pub fn sin_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.sin()) }
+/// Complex sine function.
+pub fn sin_complex(_: &mut Environment, c: Complex) -> StdResult<Complex> { Ok(c.sin()) }
+/// Cosine function (of an angle in radians).
+pub fn cos_float(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.cos()) }
+/// Complex cosine function.
+pub fn cos_complex(_: &mut Environment, c: Complex) -> StdResult<Complex> { Ok(c.cos()) }
+/// Tangent function (of an angle in radians).
+pub fn tan(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.tan()) }
+/// Inverse sine, in radians in the range [-pi/2, pi/2].
+pub fn asin(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.asin()) }
+/// Inverse cosine, in radians in the range [0, pi].
+pub fn acos(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.acos()) }
+/// Inverse tangent, in radians in the range [-pi/2, pi/2].
+pub fn atan(_: &mut Environment, x: f64) -> StdResult<f64> { Ok(x.atan()) }
+/// Angle of the point (x, y) from the positive x axis, in radians in the range (-pi, pi].
+pub fn atan2(_: &mut Environment, y: f64, x: f64) -> StdResult<f64> { Ok(y.atan2(x)) }
+
+/// Squared magnitude of a complex number.
+pub fn norm(_: &mut Environment, c: Complex) -> StdResult<f64> { Ok(c.norm_sqr()) }
+/// Argument (phase angle) of a complex number, in radians in the range (-pi, pi].
+pub fn arg(_: &mut Environment, c: Complex) -> StdResult<f64> { Ok(c.arg()) }
+/// Magnitude and argument of a complex number.
+pub fn polar(_: &mut Environment, c: Complex) -> StdResult<(f64, f64)> { Ok(c.to_polar()) }
+/// Complex number on the unit circle at the specified angle (cos theta + i sin theta).
+pub fn cis(_: &mut Environment, theta: f64) -> StdResult<Complex> { Ok(Complex::cis(theta)) }
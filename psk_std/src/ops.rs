@@ -0,0 +1,77 @@
+//! Arithmetic operations used in transpiled source code whose semantics differ from Rust's
+//! built-in operators. The interpreter uses the same operations, so that both report the same
+//! errors (instead of the transpiled program panicking).
+
+/// Floor division and modulo. Division rounds toward negative infinity (instead of toward zero),
+/// and the modulo result always has the same sign as the divisor, so that
+/// `a == a.floor_div(b) * b + a.floor_mod(b)`.
+pub trait FloorDivMod: Sized {
+    /// Divide, rounding the quotient toward negative infinity. Fails for integer division by zero.
+    fn floor_div(self, rhs: Self) -> Result<Self, String>;
+    /// Remainder of floor division; has the same sign as `rhs`. Fails for integer division by
+    /// zero.
+    fn floor_mod(self, rhs: Self) -> Result<Self, String>;
+}
+
+impl FloorDivMod for i64 {
+    fn floor_div(self, rhs: i64) -> Result<i64, String> {
+        if rhs == 0 {
+            return Err("attempt to divide integer value by zero".to_string());
+        }
+        let quot = self / rhs;
+        if self % rhs != 0 && (self < 0) != (rhs < 0) {
+            Ok(quot - 1)
+        } else {
+            Ok(quot)
+        }
+    }
+    fn floor_mod(self, rhs: i64) -> Result<i64, String> {
+        if rhs == 0 {
+            return Err("attempt to divide integer value by zero".to_string());
+        }
+        let rem = self % rhs;
+        if rem != 0 && (rem < 0) != (rhs < 0) {
+            Ok(rem + rhs)
+        } else {
+            Ok(rem)
+        }
+    }
+}
+
+impl FloorDivMod for f64 {
+    fn floor_div(self, rhs: f64) -> Result<f64, String> {
+        Ok((self / rhs).floor())
+    }
+    fn floor_mod(self, rhs: f64) -> Result<f64, String> {
+        let rem = self % rhs;
+        if rem != 0.0 && (rem < 0.0) != (rhs < 0.0) {
+            Ok(rem + rhs)
+        } else {
+            Ok(rem)
+        }
+    }
+}
+
+/// Bit shifts which fail for shift amounts outside `[0, 64)` (where Rust's shift operators
+/// overflow).
+pub trait Shift: Sized {
+    /// Shift left by `rhs` bits.
+    fn shift_left(self, rhs: i64) -> Result<Self, String>;
+    /// Arithmetic shift right by `rhs` bits.
+    fn shift_right(self, rhs: i64) -> Result<Self, String>;
+}
+
+impl Shift for i64 {
+    fn shift_left(self, rhs: i64) -> Result<i64, String> {
+        if rhs < 0 || rhs >= 64 {
+            return Err(format!("invalid shift amount: {}", rhs));
+        }
+        Ok(self << rhs)
+    }
+    fn shift_right(self, rhs: i64) -> Result<i64, String> {
+        if rhs < 0 || rhs >= 64 {
+            return Err(format!("invalid shift amount: {}", rhs));
+        }
+        Ok(self >> rhs)
+    }
+}
@@ -0,0 +1,42 @@
+//! Set operations used in transpiled source code (and by the piske interpreter).
+//!
+//! Sets are represented by vectors of distinct elements. Elements are kept in the order they are
+//! first encountered, so that iterating over a set is deterministic.
+
+/// Remove repeated elements, keeping the first occurrence of each.
+pub fn unique<T: PartialEq>(elems: Vec<T>) -> Vec<T> {
+    let mut set = Vec::with_capacity(elems.len());
+    for elem in elems {
+        if !set.contains(&elem) {
+            set.push(elem);
+        }
+    }
+    set
+}
+
+/// Elements in either set: the elements of `left`, followed by the elements of `right` which are
+/// not in `left`.
+pub fn union<T, L, R>(left: L, right: R) -> Vec<T>
+        where T: PartialEq, L: IntoIterator<Item=T>, R: IntoIterator<Item=T> {
+    let mut set = unique(left.into_iter().collect());
+    for elem in right {
+        if !set.contains(&elem) {
+            set.push(elem);
+        }
+    }
+    set
+}
+
+/// Elements of `left` which are also in `right`.
+pub fn intersection<T, L, R>(left: L, right: R) -> Vec<T>
+        where T: PartialEq, L: IntoIterator<Item=T>, R: IntoIterator<Item=T> {
+    let right: Vec<T> = right.into_iter().collect();
+    unique(left.into_iter().filter(|elem| right.contains(elem)).collect())
+}
+
+/// Elements of `left` which are not in `right`.
+pub fn difference<T, L, R>(left: L, right: R) -> Vec<T>
+        where T: PartialEq, L: IntoIterator<Item=T>, R: IntoIterator<Item=T> {
+    let right: Vec<T> = right.into_iter().collect();
+    unique(left.into_iter().filter(|elem| !right.contains(elem)).collect())
+}
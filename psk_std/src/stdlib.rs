@@ -6,30 +6,33 @@ use environment::Environment;
 use image::Dims;
 use extrema::Extrema;
 use complex::Complex;
+use error::{StdlibError, StdResult};
+
+pub use math::*;
 
 /// Set the image dimensions. May invalidate the contents of the image data.
-pub fn set_image_dims(env: &mut Environment, height: i64, width: i64) -> Result<(), String> {
+pub fn set_image_dims(env: &mut Environment, height: i64, width: i64) -> StdResult<()> {
     env.image_data.set_dims(Dims { rows: height, cols: width });
     Ok(())
 }
 /// Get the currently set image height.
-pub fn get_image_height(env: &mut Environment) -> Result<i64, String> {
+pub fn get_image_height(env: &mut Environment) -> StdResult<i64> {
     let &Dims { rows: height, .. } = env.image_data.get_dims();
     Ok(height)
 }
 /// Get the currently set image width.
-pub fn get_image_width(env: &mut Environment) -> Result<i64, String> {
+pub fn get_image_width(env: &mut Environment) -> StdResult<i64> {
     let &Dims { cols: width, .. } = env.image_data.get_dims();
     Ok(width)
 }
 /// Set the current pixel data for the specified row and column
 pub fn set_pixel_data(env: &mut Environment, row: i64, col: i64, value: f64)
-        -> Result<(), String> {
+        -> StdResult<()> {
     env.image_data.set(Dims::new(row, col), value);
     Ok(())
 }
 /// Render the current image data and write it to a file.
-pub fn write(env: &mut Environment, filename: String) -> Result<(), String> {
+pub fn write(env: &mut Environment, filename: String) -> StdResult<()> {
     use std::fs::File;
 
     let &Dims { rows, cols } = env.image_data.get_dims();
@@ -47,20 +50,22 @@ pub fn write(env: &mut Environment, filename: String) -> Result<(), String> {
         *pixel = img::Luma([alpha]);
     }
 
-    let mut file = File::create(filename).map_err(|e| format!("{}", e))?;
-    img::ImageLuma8(img_buf).save(&mut file, img::PNG).map_err(|e| format!("{}", e))?;
+    let write_err = |e: String| StdlibError::new("write",
+        format!("unable to write image to '{}': {}", filename, e));
+    let mut file = File::create(&filename).map_err(|e| write_err(e.to_string()))?;
+    img::ImageLuma8(img_buf).save(&mut file, img::PNG).map_err(|e| write_err(e.to_string()))?;
 
     Ok(())
 }
 /// Project the given pixel onto the underlying axes, using the provided center and size.
 pub fn project(env: &mut Environment, row: i64, col: i64, center: Complex, size: Complex)
-        -> Result<Complex, String> {
+        -> StdResult<Complex> {
     let &Dims { rows, cols } = env.image_data.get_dims();
     let re = (row as f64 / rows as f64 - 0.5) * size.re + center.re;
     let im = (col as f64 / cols as f64 - 0.5) * size.im + center.im;
     Ok(Complex::new(re, im))
 }
 /// Extract the real component of a complex number.
-pub fn re(_: &mut Environment, c: Complex) -> Result<f64, String> { Ok(c.re) }
+pub fn re(_: &mut Environment, c: Complex) -> StdResult<f64> { Ok(c.re) }
 /// Extract the imaginary component of a complex number.
-pub fn im(_: &mut Environment, c: Complex) -> Result<f64, String> { Ok(c.im) }
+pub fn im(_: &mut Environment, c: Complex) -> StdResult<f64> { Ok(c.im) }
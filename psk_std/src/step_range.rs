@@ -1,33 +1,66 @@
 //! Definition of the StepRange iterator use in transpiled source code.
 
-use std::ops::Add;
+/// Numeric types which can be iterated over with a `StepRange`.
+pub trait StepValue: Copy + PartialOrd {
+    /// The zero value for this type.
+    fn zero() -> Self;
+    /// The value `n` steps of size `step` away from `start`.
+    fn nth_step(start: Self, step: Self, n: usize) -> Self;
+}
+impl StepValue for i64 {
+    fn zero() -> i64 { 0 }
+    fn nth_step(start: i64, step: i64, n: usize) -> i64 {
+        start + step * n as i64
+    }
+}
+impl StepValue for f64 {
+    fn zero() -> f64 { 0.0 }
+    fn nth_step(start: f64, step: f64, n: usize) -> f64 {
+        // computed directly (instead of by repeated addition) so that floating-point rounding
+        // errors do not accumulate over the course of the iteration
+        start + step * n as f64
+    }
+}
 
 /// Iterates from a starting point to an end point (exclusive or inclusive) by a certain step.
+/// A negative step iterates downward from the starting point.
 pub struct StepRange<T> {
     start: T,
     end: T,
     end_inclusive: bool,
-    step: T
+    step: T,
+    index: usize,
 }
-impl<T> StepRange<T> {
+impl<T: StepValue> StepRange<T> {
     /// Create new StepRange iterator
-    pub fn new(start: T, end: T, end_inclusive: bool, step: T) -> StepRange<T> {
-        StepRange {
+    ///
+    /// # Failures
+    /// Returns `Err` if `step` is zero.
+    pub fn new(start: T, end: T, end_inclusive: bool, step: T) -> Result<StepRange<T>, String> {
+        if step == T::zero() {
+            return Err("interval step cannot be zero".to_string());
+        }
+        Ok(StepRange {
             start: start,
             end: end,
             end_inclusive: end_inclusive,
             step: step,
-        }
+            index: 0,
+        })
     }
 }
-impl<T: Copy + Add<Output=T> + PartialOrd> Iterator for StepRange<T> {
+impl<T: StepValue> Iterator for StepRange<T> {
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
-        if (self.end_inclusive && self.start <= self.end) ||
-                (!self.end_inclusive && self.start < self.end) {
-            let value = self.start;
-            self.start = self.start + self.step;
+        let value = T::nth_step(self.start, self.step, self.index);
+        let before_end = if self.step < T::zero() {
+            value > self.end
+        } else {
+            value < self.end
+        };
+        if before_end || (self.end_inclusive && value == self.end) {
+            self.index += 1;
             Some(value)
         } else {
             None
@@ -7,14 +7,15 @@ use std::rc::Rc;
 use PType;
 use Symbol;
 use value::Value;
+use ast::{Module, Span};
 
 use sindra::{Type, Typed, Identifier};
 use sindra::scope::{Scope, Scoped, MemoryScope, SymbolStore, MemoryStore};
 use sindra::value::Coerce;
 
 /// Annotation type for piske abstract syntax tree. Contains a symbol scope, memory scope,
-/// and typing information.
-#[derive(Debug, Clone, PartialEq)]
+/// typing information and the node's position in the source code.
+#[derive(Debug, Clone)]
 pub struct Annotation {
     /// The scope for a particular AST node
     scope: Option<Rc<RefCell<MemoryScope<Symbol, Value>>>>,
@@ -22,6 +23,22 @@ pub struct Annotation {
     pub ty: Option<PType>,
     /// The promoted type for this AST node
     pub promote_ty: Option<PType>,
+    /// Variables captured from enclosing scopes (only used for closures)
+    captures: Vec<Identifier>,
+    /// The imported module (only used for import statements)
+    module: Option<Rc<Module>>,
+    /// Index of the called definition of an overloaded function (only used for function calls)
+    overload: Option<usize>,
+    /// The span of source code the node was parsed from
+    span: Option<Span>,
+}
+// spans are not compared, so that nodes parsed from different positions can be equal
+impl PartialEq for Annotation {
+    fn eq(&self, other: &Annotation) -> bool {
+        self.scope == other.scope && self.ty == other.ty && self.promote_ty == other.promote_ty
+            && self.captures == other.captures && self.module == other.module
+            && self.overload == other.overload
+    }
 }
 impl Default for Annotation {
     fn default() -> Annotation {
@@ -29,9 +46,31 @@ impl Default for Annotation {
             scope: None,
             ty: None,
             promote_ty: None,
+            captures: vec![],
+            module: None,
+            overload: None,
+            span: None,
         }
     }
 }
+impl Annotation {
+    /// The variables captured from enclosing scopes by the annotated closure.
+    pub fn captures(&self) -> &[Identifier] { &self.captures }
+    /// Set the variables captured from enclosing scopes by the annotated closure.
+    pub fn set_captures(&mut self, captures: Vec<Identifier>) { self.captures = captures; }
+    /// The module loaded by the annotated import statement.
+    pub fn module(&self) -> Option<Rc<Module>> { self.module.clone() }
+    /// Set the module loaded by the annotated import statement.
+    pub fn set_module(&mut self, module: Option<Rc<Module>>) { self.module = module; }
+    /// The index of the overloaded function definition called by the annotated function call.
+    pub fn overload(&self) -> Option<usize> { self.overload }
+    /// Set the index of the overloaded function definition called by the annotated function call.
+    pub fn set_overload(&mut self, overload: Option<usize>) { self.overload = overload; }
+    /// The span of source code the annotated node was parsed from.
+    pub fn span(&self) -> Option<Span> { self.span }
+    /// Set the span of source code the annotated node was parsed from.
+    pub fn set_span(&mut self, span: Option<Span>) { self.span = span; }
+}
 
 impl Typed<PType> for Annotation {
     fn ty(&self) -> Option<PType> { self.ty.clone() }
@@ -125,7 +164,7 @@ impl fmt::Display for Annotation {
             }
         };
         match self.ty {
-            Some(ty) => { write!(f, " type:{}", ty.name()) },
+            Some(ref ty) => { write!(f, " type:{}", ty.name()) },
             None => { write!(f, " type:none") },
         }
     }
@@ -4,8 +4,15 @@
 //! This abstract syntax tree is constructed by the rust-peg parser, and annotated by the AST
 //! visitors.
 
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
 use sindra::{Identifier, Node};
-use ast::Annotation;
+use sindra::scope::{Scoped, SymbolStore};
+use ast::{Annotation, Span, Spanned};
+use diagnostic::Source;
+use Symbol;
 
 /// Root-level program. Only contains a statement block.
 #[derive(Debug, Clone, PartialEq)]
@@ -22,44 +29,166 @@ annotate!(Block, Annotation);
 pub enum Statement {
     /// Statement only containing an expression.
     Expression(Node<Expression>),
-    /// Variable declaration statement.
-    Declare(Node<Identifier>, Node<Expression>),
+    /// Variable declaration statement, with optional explicit variable type.
+    Declare(Node<Identifier>, Option<TypeSpec>, Node<Expression>),
     /// Variable assignment statement.
     Assign(Node<Identifier>, Node<Expression>),
+    /// Global variable assignment statement, of form set <ident> = <expr>. At global scope, this
+    /// declares the global variable if it does not already exist.
+    SetGlobal(Node<Identifier>, Node<Expression>),
+    /// Tuple destructuring statement, of form let (<ident>, ...) = <expr> (declaring new
+    /// variables) or (<ident>, ...) = <expr> (assigning existing variables).
+    Destructure {
+        /// Whether this statement declares new variables
+        declare: bool,
+        /// Variables receiving each tuple element (`None` for an ignored element, written `_`)
+        names: Vec<Option<Node<Identifier>>>,
+        /// The tuple-valued expression
+        value: Node<Expression>,
+    },
+    /// Array element assignment statement, of form <array>[<index>] = <value>.
+    IndexAssign {
+        /// The array being modified (an identifier, or an access chain starting with one)
+        array: Node<Expression>,
+        /// Index of the element to assign
+        index: Node<Expression>,
+        /// Value to assign
+        value: Node<Expression>,
+    },
     /// Function definition statement.
     FnDefine(FunctionDef),
+    /// Struct definition statement.
+    StructDefine(StructDef),
     /// Return statement.
     Return(Node<Expression>),
-    /// Break statement.
-    Break(Node<Expression>),
+    /// Break statement, of form break ['<label>] <expr>; exits the innermost loop (or the loop with
+    /// the specified label) with the value of the expression.
+    Break(Option<Node<Identifier>>, Node<Expression>),
+    /// Continue statement, of form continue ['<label>]; skips to the next iteration of the innermost
+    /// loop (or the loop with the specified label).
+    Continue(Option<Node<Identifier>>),
     /// Print statement.
     Print(Vec<Node<Expression>>),
+    /// Import statement, of form import "<path>"; the path is relative to the importing file.
+    Import(String),
 }
 annotate!(Statement, Annotation);
 
+/// File loaded by an import statement. Imported files may only contain function definitions,
+/// struct definitions and further imports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    /// Name of the module (the file name without extension), used to qualify the names of its
+    /// functions (e.g. `palettes::fire`)
+    pub name: Identifier,
+    /// Location of the imported file
+    pub path: PathBuf,
+    /// Parsed contents of the imported file
+    pub program: Node<Program>,
+    /// Source code of the imported file, where errors in the module are reported
+    pub source: Arc<Source>,
+}
+impl Module {
+    /// Returns true if `body` is the body of one of the functions defined in the module.
+    pub fn defines(&self, body: &Node<Block>) -> bool {
+        (self.program.item.0).item.0.iter().any(|statement| match statement.item {
+            Statement::FnDefine(FunctionDef { body: ref fn_body, .. }) => {
+                Rc::ptr_eq(&fn_body.annotation, &body.annotation)
+            },
+            _ => false,
+        })
+    }
+    /// Symbols made available to an importing file: the functions and structs defined in the
+    /// module. Functions are exported under both their own and their qualified name.
+    pub fn exports(&self) -> Vec<(Identifier, Symbol)> {
+        let scope = match self.program.annotation.borrow().scope() {
+            Some(scope) => scope,
+            None => { return vec![]; }
+        };
+        let mut exports = vec![];
+        for statement in (self.program.item.0).item.0.iter() {
+            match statement.item {
+                Statement::FnDefine(FunctionDef { ref name, .. }) => {
+                    let symbol: Option<Symbol> = scope.borrow().resolve(&name.item);
+                    if let Some(symbol) = symbol {
+                        exports.push((Identifier(format!("{}::{}", self.name, name.item)),
+                            symbol.clone()));
+                        exports.push((name.item.clone(), symbol));
+                    }
+                },
+                Statement::StructDefine(StructDef { ref name, .. }) => {
+                    let symbol: Option<Symbol> = scope.borrow().resolve(&name.item);
+                    if let Some(symbol) = symbol {
+                        exports.push((name.item.clone(), symbol));
+                    }
+                },
+                _ => {}
+            }
+        }
+        exports
+    }
+}
+
 /// Definition of a function.
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDef {
     /// Function name.
     pub name: Node<Identifier>,
     /// Return type.
-    pub ret_type: Node<Identifier>,
+    pub ret_type: TypeSpec,
     /// List of function parameters.
     pub params: Vec<Node<Parameter>>,
     /// Body of the function.
     pub body: Node<Block>,
 }
 
+/// Definition of a struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDef {
+    /// Struct name.
+    pub name: Node<Identifier>,
+    /// List of struct fields.
+    pub fields: Vec<Node<Field>>,
+}
+
+/// Struct field (used in struct definitions).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// Field name.
+    pub name: Node<Identifier>,
+    /// Field type.
+    pub ty: TypeSpec,
+}
+annotate!(Field, Annotation);
+
 /// Function parameter (used in function definitions).
 #[derive(Debug, Clone, PartialEq)]
 pub struct Parameter {
     /// Paramter variable name.
     pub name: Node<Identifier>,
     /// Parameter variable type.
-    pub ty: Node<Identifier>,
+    pub ty: TypeSpec,
 }
 annotate!(Parameter, Annotation);
 
+/// Type specification, as written in parameter, return, and field types.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeSpec {
+    /// A named type (built-in type or struct)
+    Named(Node<Identifier>),
+    /// An array, of form [<type>]
+    Array(Box<TypeSpec>),
+    /// A tuple, of form (<type>, <type>, ...)
+    Tuple(Vec<TypeSpec>),
+    /// A function, of form fn(<type>, ...) -> <type>
+    Function {
+        /// Parameter types
+        params: Vec<TypeSpec>,
+        /// Return type
+        ret: Box<TypeSpec>,
+    },
+}
+
 /// Valid expressions in the piske programming language.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
@@ -90,8 +219,24 @@ pub enum Expression {
         /// The operand
         left: Box<Node<Expression>>,
     },
+    /// Explicit type cast, of form <expr> as <type>
+    Cast {
+        /// The expression being cast
+        expr: Box<Node<Expression>>,
+        /// The destination type
+        ty: TypeSpec,
+    },
     /// A block of statements is treated as an expression (and can have a return value)
     Block(Node<Block>),
+    /// Anonymous function (closure), of form |<param>: <type>, ...| -> <type> { <body> }
+    Closure {
+        /// List of closure parameters.
+        params: Vec<Node<Parameter>>,
+        /// Return type.
+        ret_type: TypeSpec,
+        /// Body of the closure.
+        body: Node<Block>,
+    },
     /// A function call
     FnCall {
         /// Function name.
@@ -108,17 +253,152 @@ pub enum Expression {
         /// else-block (executed if cond is false); optional
         else_block: Option<Node<Block>>
     },
+    /// Match expression, of form match <expr> { <pattern> => <expr>, ... }
+    Match {
+        /// The value being matched
+        value: Box<Node<Expression>>,
+        /// List of match arms, tested in order
+        arms: Vec<MatchArm>,
+    },
+    /// Struct literal, of form <name> { <field>: <expr>, ... }
+    StructLiteral {
+        /// Struct name.
+        name: Node<Identifier>,
+        /// List of field names and their initializing expressions.
+        fields: Vec<(Node<Identifier>, Node<Expression>)>,
+    },
+    /// Struct member access, of form <expr>.<field>
+    Member {
+        /// The struct-valued expression being accessed
+        object: Box<Node<Expression>>,
+        /// Name of the accessed field
+        field: Node<Identifier>,
+    },
+    /// Array literal, of form [<expr>, <expr>, ...]
+    Array(Vec<Node<Expression>>),
+    /// Tuple literal, of form (<expr>, <expr>, ...)
+    Tuple(Vec<Node<Expression>>),
+    /// Array literal with repeated element, of form [<expr>; <count>]
+    ArrayRepeat {
+        /// The element value
+        value: Box<Node<Expression>>,
+        /// The number of elements
+        count: Box<Node<Expression>>,
+    },
+    /// Array indexing, of form <expr>[<index>]
+    Index {
+        /// The array-valued expression being indexed
+        object: Box<Node<Expression>>,
+        /// The element index
+        index: Box<Node<Expression>>,
+    },
     /// Loop expression
     Loop {
+        /// loop label (used by break and continue statements in nested loops)
+        label: Option<Node<Identifier>>,
         /// name of loop-varying symbol
         variant: Option<Node<Identifier>>,
         /// set of elements to loop over
         set: Node<Set>,
         /// loop body
         body: Node<Block>,
+    },
+    /// Conditional loop expression, which iterates as long as its condition is true
+    WhileLoop {
+        /// loop label (used by break and continue statements in nested loops)
+        label: Option<Node<Identifier>>,
+        /// loop condition (checked before each iteration)
+        cond: Box<Node<Expression>>,
+        /// loop body
+        body: Node<Block>,
+    },
+    /// Loop over every pixel of the current image, of form
+    /// iterate (<row>, <col>) over pixels { <body> }, which sets each pixel to the value of the body
+    PixelLoop {
+        /// loop label (used by break and continue statements in nested loops)
+        label: Option<Node<Identifier>>,
+        /// name of the pixel row symbol
+        row: Node<Identifier>,
+        /// name of the pixel column symbol
+        col: Node<Identifier>,
+        /// loop body
+        body: Node<Block>,
     }
 }
 annotate!(Expression, Annotation);
+impl Expression {
+    /// Create a prefix expression node, spanning the source code from byte offset `start` (the
+    /// position of the operator) to the end of the operand.
+    pub fn prefix(op: PrefixOp, start: usize, right: Node<Expression>) -> Node<Expression> {
+        let end = right.span().map_or(start, |span| span.end);
+        Node::new(Expression::Prefix { op: op, right: Box::new(right) }).with_span(start, end)
+    }
+    /// Create an infix expression node, spanning the source code of both of its operands.
+    pub fn infix(op: InfixOp, left: Node<Expression>, right: Node<Expression>)
+            -> Node<Expression> {
+        let span = join_spans(&left, &right);
+        let node = Node::new(Expression::Infix {
+            op: op,
+            left: Box::new(left),
+            right: Box::new(right),
+        });
+        node.set_span(span);
+        node
+    }
+}
+
+/// Single arm of a match expression, of form <pattern> => <expr>
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    /// The pattern the matched value is tested against
+    pub pattern: Pattern,
+    /// The expression evaluated if the pattern matches
+    pub body: Node<Expression>,
+}
+
+/// Pattern in a match arm
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// Matches a single value
+    Value(Literal),
+    /// Matches all values in an interval
+    Interval {
+        /// Starting value for the interval (inclusive)
+        start: Literal,
+        /// Ending value for the interval
+        end: Literal,
+        /// Whether the ending value for the interval is inclusive or exclusive
+        end_inclusive: bool,
+    },
+    /// Matches any value (`_`)
+    Wildcard,
+}
+
+/// Accessor applied to the end of an expression (used when parsing access chains such as
+/// `a.b[1].c`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Accessor {
+    /// Struct member access
+    Field(Node<Identifier>),
+    /// Array indexing
+    Index(Node<Expression>),
+}
+impl Accessor {
+    /// Apply this accessor to an expression, producing the resulting member access or index
+    /// expression.
+    pub fn apply(self, object: Node<Expression>) -> Node<Expression> {
+        match self {
+            Accessor::Field(field) => Node::new(Expression::Member {
+                object: Box::new(object),
+                field: field,
+            }),
+            Accessor::Index(index) => Node::new(Expression::Index {
+                object: Box::new(object),
+                index: Box::new(index),
+            }),
+        }
+    }
+}
 
 /// Set (collection) structure
 #[derive(Debug, Clone, PartialEq)]
@@ -134,8 +414,62 @@ pub enum Set {
         /// Interval step
         step: Box<Node<Expression>>
     },
+    /// Enumerated set, of form {<expr>, <expr>, ...}
+    Elements(Vec<Node<Expression>>),
+    /// Set-builder notation, of form { <elem> | <var> in <set>, <cond> } (where <elem> and <var>
+    /// are the same symbol)
+    Builder {
+        /// Symbol for the elements of the set
+        elem: Node<Identifier>,
+        /// Symbol for the elements of the source set
+        var: Node<Identifier>,
+        /// Set whose elements are selected
+        source: Box<Node<Set>>,
+        /// Condition selecting elements of the source set (all elements if not specified)
+        cond: Option<Box<Node<Expression>>>,
+    },
+    /// Union, intersection or difference of two sets
+    Operation {
+        /// The set operation
+        op: SetOp,
+        /// Left-hand set
+        left: Box<Node<Set>>,
+        /// Right-hand set
+        right: Box<Node<Set>>,
+    },
 }
 annotate!(Set, Annotation);
+impl Set {
+    /// Create a set operation node, spanning the source code of both of its operands.
+    pub fn operation(op: SetOp, left: Node<Set>, right: Node<Set>) -> Node<Set> {
+        let span = join_spans(&left, &right);
+        let node = Node::new(Set::Operation {
+            op: op,
+            left: Box::new(left),
+            right: Box::new(right),
+        });
+        node.set_span(span);
+        node
+    }
+}
+
+fn join_spans<L: Spanned, R: Spanned>(left: &L, right: &R) -> Option<Span> {
+    match (left.span(), right.span()) {
+        (Some(left), Some(right)) => Some(left.join(right)),
+        _ => None
+    }
+}
+
+/// Valid operations on sets
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SetOp {
+    /// Union (elements in either set)
+    Union,
+    /// Intersection (elements in both sets)
+    Intersection,
+    /// Difference (elements in the left set but not the right set)
+    Difference,
+}
 
 /// Supported literals
 #[derive(Debug, Clone, PartialEq)]
@@ -158,6 +492,8 @@ pub enum PrefixOp {
     UnaryMinus,
     /// Unary plus (posation - basically a no-op)
     UnaryPlus,
+    /// Logical not
+    Not,
 }
 
 /// Valid infix operations
@@ -171,10 +507,28 @@ pub enum InfixOp {
     Multiply,
     /// Division
     Divide,
+    /// Floor division (quotient rounded toward negative infinity)
+    FloorDivide,
+    /// Modulo (remainder of floor division; same sign as the divisor)
+    Modulo,
     /// Exponentiation
     Power,
+    /// Bitwise and
+    BitAnd,
+    /// Bitwise or
+    BitOr,
+    /// Bitwise exclusive or
+    BitXor,
+    /// Bitwise left shift
+    ShiftLeft,
+    /// Bitwise right shift (arithmetic)
+    ShiftRight,
     /// Comparison
     Comparison(CompareOp),
+    /// Logical and (right operand only evaluated if left operand is true)
+    And,
+    /// Logical or (right operand only evaluated if left operand is false)
+    Or,
 }
 
 /// Valid postfix operations
@@ -25,10 +25,33 @@ impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         match *self {
             Statement::Expression(ref expr) => write!(f, "expr:{}", expr.item),
-            Statement::Declare(ref ident, ref expr) => write!(f, "decl({}->{})",
+            Statement::Declare(ref ident, None, ref expr) => write!(f, "decl({}->{})",
                 ident.item, expr.item),
+            Statement::Declare(ref ident, Some(ref ty), ref expr) => write!(f, "decl({}:{}->{})",
+                ident.item, ty, expr.item),
             Statement::Assign(ref ident, ref expr) => write!(f, "assign({}->{})",
                 ident.item, expr.item),
+            Statement::SetGlobal(ref ident, ref expr) => write!(f, "set({}->{})",
+                ident.item, expr.item),
+            Statement::Destructure { declare, ref names, ref value } => {
+                let mut nl = String::new();
+                let mut first = true;
+                for name in names {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut nl, ",")?;
+                    }
+                    match *name {
+                        Some(ref name) => write!(&mut nl, "{}", name.item)?,
+                        None => write!(&mut nl, "_")?,
+                    }
+                }
+                let kind = if declare { "decl" } else { "assign" };
+                write!(f, "{}(({})->{})", kind, nl, value.item)
+            },
+            Statement::IndexAssign { ref array, ref index, ref value } =>
+                write!(f, "assign({}[{}]->{})", array.item, index.item, value.item),
             Statement::FnDefine(FunctionDef { ref name, ref body, ref params, ref ret_type }) => {
                 let mut pl = String::new();
                 let mut first = true;
@@ -40,11 +63,31 @@ impl fmt::Display for Statement {
                     }
                     write!(&mut pl, "{}", expr.item)?;
                 }
-                write!(f, "def({}({}) -> {}) {}", name.item, pl, ret_type.item,
+                write!(f, "def({}({}) -> {}) {}", name.item, pl, ret_type,
                     body.item)
             },
+            Statement::StructDefine(StructDef { ref name, ref fields }) => {
+                let mut fl = String::new();
+                let mut first = true;
+                for field in fields {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut fl, ",")?;
+                    }
+                    write!(&mut fl, "{}", field.item)?;
+                }
+                write!(f, "struct({}{{{}}})", name.item, fl)
+            },
             Statement::Return(ref expr) => write!(f, "return({})", expr.item),
-            Statement::Break(ref expr) => write!(f, "break({})", expr.item),
+            Statement::Break(ref label, ref expr) => match *label {
+                Some(ref label) => write!(f, "break('{},{})", label.item, expr.item),
+                None => write!(f, "break({})", expr.item),
+            },
+            Statement::Continue(ref label) => match *label {
+                Some(ref label) => write!(f, "continue('{})", label.item),
+                None => write!(f, "continue()"),
+            },
             Statement::Print(ref exprs) => {
                 let mut out = "print(".to_string();
                 let mut first = true;
@@ -57,14 +100,56 @@ impl fmt::Display for Statement {
                     write!(&mut out, "{}", expr.item)?;
                 }
                 write!(f, "{})", out)
-            }
+            },
+            Statement::Import(ref path) => write!(f, "import(\"{}\")", path),
         }
     }
 }
 
 impl fmt::Display for Parameter {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
-        write!(f, "{}: {}", self.name.item, self.ty.item)
+        write!(f, "{}: {}", self.name.item, self.ty)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        write!(f, "{}: {}", self.name.item, self.ty)
+    }
+}
+
+impl fmt::Display for TypeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        match *self {
+            TypeSpec::Named(ref name) => write!(f, "{}", name.item),
+            TypeSpec::Array(ref elem) => write!(f, "[{}]", elem),
+            TypeSpec::Tuple(ref elems) => {
+                write!(f, "(")?;
+                let mut first = true;
+                for elem in elems {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, ")")
+            },
+            TypeSpec::Function { ref params, ref ret } => {
+                write!(f, "fn(")?;
+                let mut first = true;
+                for param in params {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", param)?;
+                }
+                write!(f, ") -> {}", ret)
+            },
+        }
     }
 }
 
@@ -79,8 +164,23 @@ impl fmt::Display for Expression {
                 write!(f, "prefix:{}{}", op, right.item),
             Expression::Postfix { ref op, ref left } =>
                 write!(f, "postfix:{}{}", left.item, op),
+            Expression::Cast { ref expr, ref ty } =>
+                write!(f, "cast:{}->{}", expr.item, ty),
             Expression::Block(ref block) =>
                 write!(f, "block:{}", block.item),
+            Expression::Closure { ref params, ref ret_type, ref body } => {
+                let mut pl = String::new();
+                let mut first = true;
+                for param in params {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut pl, ",")?;
+                    }
+                    write!(&mut pl, "{}", param.item)?;
+                }
+                write!(f, "closure(|{}| -> {}) {}", pl, ret_type, body.item)
+            },
             Expression::FnCall { name: ref ident, ref args } => {
                 let mut pl = String::new();
                 let mut first = true;
@@ -100,11 +200,81 @@ impl fmt::Display for Expression {
             Expression::IfElse { ref cond, ref if_block, else_block: None } => {
                 write!(f, "if({}){{{}}}", cond.item, if_block.item)
             },
-            Expression::Loop { ref variant, ref set, ref body } => {
+            Expression::Match { ref value, ref arms } => {
+                let mut al = String::new();
+                for arm in arms {
+                    write!(&mut al, "{}=>{};", arm.pattern, arm.body.item)?;
+                }
+                write!(f, "match({}){{{}}}", value.item, al)
+            },
+            Expression::StructLiteral { ref name, ref fields } => {
+                let mut fl = String::new();
+                let mut first = true;
+                for &(ref field, ref expr) in fields {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut fl, ",")?;
+                    }
+                    write!(&mut fl, "{}:{}", field.item, expr.item)?;
+                }
+                write!(f, "struct{{{}}}({})", name.item, fl)
+            },
+            Expression::Member { ref object, ref field } => {
+                write!(f, "member:{}.{}", object.item, field.item)
+            },
+            Expression::Array(ref elems) => {
+                let mut el = String::new();
+                let mut first = true;
+                for expr in elems {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut el, ",")?;
+                    }
+                    write!(&mut el, "{}", expr.item)?;
+                }
+                write!(f, "array[{}]", el)
+            },
+            Expression::Tuple(ref elems) => {
+                let mut el = String::new();
+                let mut first = true;
+                for expr in elems {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(&mut el, ",")?;
+                    }
+                    write!(&mut el, "{}", expr.item)?;
+                }
+                write!(f, "tuple({})", el)
+            },
+            Expression::ArrayRepeat { ref value, ref count } => {
+                write!(f, "array[{};{}]", value.item, count.item)
+            },
+            Expression::Index { ref object, ref index } => {
+                write!(f, "index:{}[{}]", object.item, index.item)
+            },
+            Expression::Loop { ref label, ref variant, ref set, ref body } => {
+                if let Some(ref label) = *label {
+                    write!(f, "'{}:", label.item)?;
+                }
                 match *variant {
                     Some(ref var) => write!(f, "for({}={}){{{}}}", var.item, set.item, body.item),
                     None => write!(f, "for({}){{{}}}", set.item, body.item),
                 }
+            },
+            Expression::WhileLoop { ref label, ref cond, ref body } => {
+                if let Some(ref label) = *label {
+                    write!(f, "'{}:", label.item)?;
+                }
+                write!(f, "while({}){{{}}}", cond.item, body.item)
+            },
+            Expression::PixelLoop { ref label, ref row, ref col, ref body } => {
+                if let Some(ref label) = *label {
+                    write!(f, "'{}:", label.item)?;
+                }
+                write!(f, "pixels({},{}){{{}}}", row.item, col.item, body.item)
             }
         }
     }
@@ -116,11 +286,49 @@ impl fmt::Display for Set {
             Set::Interval { ref start, ref end, end_inclusive, ref step } => {
                 let end_bracket = if end_inclusive { "]" } else { ")" };
                 write!(f, "[{}..{}..{}{}", start.item, step.item, end.item, end_bracket)
+            },
+            Set::Elements(ref elems) => {
+                let el = elems.iter().map(|elem| format!("{}", elem.item))
+                    .collect::<Vec<_>>().join(",");
+                write!(f, "{{{}}}", el)
+            },
+            Set::Builder { ref elem, ref var, ref source, ref cond } => {
+                write!(f, "{{{}|{} in {}", elem.item, var.item, source.item)?;
+                if let Some(ref cond) = *cond {
+                    write!(f, ",{}", cond.item)?;
+                }
+                write!(f, "}}")
+            },
+            Set::Operation { op, ref left, ref right } => {
+                write!(f, "({}{}{})", left.item, op, right.item)
             }
         }
     }
 }
 
+impl fmt::Display for SetOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        match *self {
+            SetOp::Union => write!(f, "|"),
+            SetOp::Intersection => write!(f, "&"),
+            SetOp::Difference => write!(f, "-"),
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        match *self {
+            Pattern::Value(ref lit) => write!(f, "{}", lit),
+            Pattern::Interval { ref start, ref end, end_inclusive } => {
+                let end_bracket = if end_inclusive { "]" } else { ")" };
+                write!(f, "[{},{}{}", start, end, end_bracket)
+            },
+            Pattern::Wildcard => write!(f, "_"),
+        }
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         match *self {
@@ -137,6 +345,7 @@ impl fmt::Display for PrefixOp {
         match *self {
             PrefixOp::UnaryMinus => write!(f, "-"),
             PrefixOp::UnaryPlus => write!(f, "+"),
+            PrefixOp::Not => write!(f, "not "),
         }
     }
 }
@@ -148,7 +357,16 @@ impl fmt::Display for InfixOp {
             InfixOp::Subtract => write!(f, "-"),
             InfixOp::Multiply => write!(f, "*"),
             InfixOp::Divide => write!(f, "/"),
+            InfixOp::FloorDivide => write!(f, " div "),
+            InfixOp::Modulo => write!(f, "%"),
             InfixOp::Power => write!(f, "^"),
+            InfixOp::BitAnd => write!(f, "&"),
+            InfixOp::BitOr => write!(f, "|"),
+            InfixOp::BitXor => write!(f, " xor "),
+            InfixOp::ShiftLeft => write!(f, "<<"),
+            InfixOp::ShiftRight => write!(f, ">>"),
+            InfixOp::And => write!(f, " and "),
+            InfixOp::Or => write!(f, " or "),
             InfixOp::Comparison(op) => {
                 match op {
                     CompareOp::LessThan => write!(f, "<"),
@@ -49,25 +49,127 @@ lazy_static! {
     ];
 }
 
+lazy_static! {
+    /// Result type definition for floor division and modulo infix operations. `Some(...)`
+    /// indicates that the operation is possible and has the given result type, `None` indicates
+    /// that the operation is invalid on the supplied types.
+    pub static ref FLOOR_RESULT_TABLE: [[Option<PType>; 7]; 7] = [
+        // String % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Float % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, Some(PType::Float), Some(PType::Float), None, None, None, None],
+        // Int % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, Some(PType::Float), Some(PType::Int), None, None, None, None],
+        // Boolean % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Complex % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Set % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Void % [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+    ];
+}
+
+lazy_static! {
+    /// Result type definition for bitwise infix operations (e.g. &, |, <<). `Some(...)` indicates
+    /// that the operation is possible and has the given result type, `None` indicates that the
+    /// operation is invalid on the supplied types.
+    pub static ref BITWISE_RESULT_TABLE: [[Option<PType>; 7]; 7] = [
+        // String & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Float & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Int & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, Some(PType::Int), None, None, None, None],
+        // Boolean & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Complex & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Set & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+        // Void & [String, Float, Int, Boolean, Complex, Set, Void]
+        [None, None, None, None, None, None, None],
+    ];
+}
+
+/// Position of a type within the type tables above. Returns `None` for types which do not have an
+/// entry in these tables (e.g. user-defined structs).
+fn table_index(ty: &PType) -> Option<usize> {
+    match *ty {
+        PType::String => Some(0),
+        PType::Float => Some(1),
+        PType::Int => Some(2),
+        PType::Boolean => Some(3),
+        PType::Complex => Some(4),
+        PType::Set => Some(5),
+        PType::Void => Some(6),
+        PType::Struct(_) | PType::Array(_) | PType::Function(_, _) | PType::Tuple(_) => None,
+    }
+}
+
+/// Look up the entry for the given operand types in one of the type tables above.
+fn table_lookup(table: &[[Option<PType>; 7]; 7], left: &PType, right: &PType) -> Option<PType> {
+    match (table_index(left), table_index(right)) {
+        (Some(l), Some(r)) => table[l][r].clone(),
+        _ => None
+    }
+}
+
 impl InferTypesBinary for InfixOp {
     type Operand = PType;
 
     fn infer_types(&self, left: PType, right: PType) -> Option<BinaryOpTypes<PType>> {
         match *self {
+            // strings can be concatenated, but support no other arithmetic
+            InfixOp::Add if left == PType::String && right == PType::String => {
+                Some(BinaryOpTypes {
+                    result: PType::String, left: PType::String, right: PType::String
+                })
+            },
             InfixOp::Subtract | InfixOp::Multiply | InfixOp::Divide | InfixOp::Add => {
-                ARITH_RESULT_TABLE[left as usize][right as usize]
-                    .map(|t| BinaryOpTypes { result: t, left: t, right: t })
+                table_lookup(&ARITH_RESULT_TABLE, &left, &right)
+                    .map(|t| BinaryOpTypes { result: t.clone(), left: t.clone(), right: t })
+            },
+            InfixOp::FloorDivide | InfixOp::Modulo => {
+                table_lookup(&FLOOR_RESULT_TABLE, &left, &right)
+                    .map(|t| BinaryOpTypes { result: t.clone(), left: t.clone(), right: t })
+            },
+            InfixOp::BitAnd | InfixOp::BitOr | InfixOp::BitXor | InfixOp::ShiftLeft
+                    | InfixOp::ShiftRight => {
+                table_lookup(&BITWISE_RESULT_TABLE, &left, &right)
+                    .map(|t| BinaryOpTypes { result: t.clone(), left: t.clone(), right: t })
             },
             InfixOp::Power => {
+                let is_numeric = |ty: &PType| {
+                    *ty == PType::Int || *ty == PType::Float || *ty == PType::Complex
+                };
                 if left == PType::String || right == PType::String {
                     None
+                } else if left == PType::Complex && is_numeric(&right) {
+                    // integer and real exponents of complex numbers keep their own type, so that
+                    // integer powers can be computed exactly
+                    Some(BinaryOpTypes { result: PType::Complex, left: PType::Complex,
+                        right: right })
+                } else if right == PType::Complex && is_numeric(&left) {
+                    Some(BinaryOpTypes { result: PType::Complex, left: PType::Complex,
+                        right: PType::Complex })
                 } else {
-                    Some(PType::Float).map(|t| BinaryOpTypes { result: t, left: t, right: t })
+                    Some(PType::Float).map(|t| BinaryOpTypes { result: t.clone(), left: t.clone(),
+                        right: t })
                 }
             },
             InfixOp::Comparison(_) => {
-                COMPARABLE[left as usize][right as usize]
-                    .map(|t| BinaryOpTypes { result: PType::Boolean, left: t, right: t })
+                table_lookup(&COMPARABLE, &left, &right)
+                    .map(|t| BinaryOpTypes { result: PType::Boolean, left: t.clone(), right: t })
+            },
+            InfixOp::And | InfixOp::Or => {
+                if left == PType::Boolean && right == PType::Boolean {
+                    Some(BinaryOpTypes { result: PType::Boolean, left: PType::Boolean,
+                        right: PType::Boolean })
+                } else {
+                    None
+                }
             }
         }
     }
@@ -77,10 +179,16 @@ impl InferTypesUnary for PrefixOp {
     type Operand = PType;
 
     fn infer_types(&self, operand: PType) -> Option<UnaryOpTypes<PType>> {
-        match operand {
-            PType::Float | PType::Int | PType::Complex => Some(operand),
-            _ => None,
-        }.map(|t| UnaryOpTypes { result: t, operand: t })
+        match (*self, operand) {
+            (PrefixOp::UnaryMinus, operand) | (PrefixOp::UnaryPlus, operand) => {
+                match operand {
+                    PType::Float | PType::Int | PType::Complex => Some(operand),
+                    _ => None,
+                }
+            },
+            (PrefixOp::Not, PType::Boolean) => Some(PType::Boolean),
+            (PrefixOp::Not, _) => None,
+        }.map(|t| UnaryOpTypes { result: t.clone(), operand: t })
     }
 }
 
@@ -94,7 +202,7 @@ impl InferTypesUnary for PostfixOp {
                     PType::Float | PType::Int => Some(PType::Float),
                     PType::Complex => Some(PType::Complex),
                     _ => None,
-                }.map(|t| UnaryOpTypes { result: t, operand: t })
+                }.map(|t| UnaryOpTypes { result: t.clone(), operand: t })
             },
             PostfixOp::Imaginary => {
                 match operand {
@@ -6,6 +6,9 @@ pub use self::annotation::Annotation;
 pub mod ast;
 pub use self::ast::*;
 
+pub mod span;
+pub use self::span::{Span, Spanned};
+
 pub mod display;
 pub mod inference;
 pub mod operator;
@@ -8,6 +8,7 @@ use ast::{InfixOp, PrefixOp, PostfixOp, CompareOp};
 use PType;
 
 use psk_std::complex::Complex;
+use psk_std::ops::{FloorDivMod, Shift};
 
 #[inline(always)]
 fn compare_primitives<T: PartialOrd + PartialEq>(op: CompareOp, left: T, right: T) -> bool {
@@ -36,6 +37,9 @@ fn compare(op: CompareOp, left: &Value, right: &Value) -> Result<Value, String>
         (&Value::Float(l), &Value::Int(r)) => {
             Ok(Value::Boolean(compare_primitives(op, l, r as f64)))
         }
+        (&Value::Boolean(l), &Value::Boolean(r)) => {
+            Ok(Value::Boolean(compare_primitives(op, l, r)))
+        }
         _ => {
             Err(format!("unable to compare values of type '{}' and '{}'", PType::from(left),
                 PType::from(right)))
@@ -55,8 +59,15 @@ impl BinaryOperator<PType, Value> for InfixOp {
                     InfixOp::Divide => Ok(Value::Float(left / right)),
                     InfixOp::Multiply => Ok(Value::Float(left * right)),
                     InfixOp::Power => Ok(Value::Float(left.powf(right))),
+                    InfixOp::FloorDivide => Ok(Value::Float(left.floor_div(right)?)),
+                    InfixOp::Modulo => Ok(Value::Float(left.floor_mod(right)?)),
+                    InfixOp::BitAnd | InfixOp::BitOr | InfixOp::BitXor | InfixOp::ShiftLeft
+                            | InfixOp::ShiftRight => Err(
+                        "bitwise operations cannot be interpreted as floating point".to_string()),
                     InfixOp::Comparison(_) => Err(
-                        "comparisons cannot be interpreted as integers".to_string())
+                        "comparisons cannot be interpreted as integers".to_string()),
+                    InfixOp::And | InfixOp::Or => Err(
+                        "logical operations cannot be interpreted as floating point".to_string())
                 }
             },
             PType::Int => {
@@ -74,8 +85,17 @@ impl BinaryOperator<PType, Value> for InfixOp {
                             Err("attempt to raise integer value to negative power".to_string())
                         }
                     },
+                    InfixOp::FloorDivide => Ok(Value::Int(left.floor_div(right)?)),
+                    InfixOp::Modulo => Ok(Value::Int(left.floor_mod(right)?)),
+                    InfixOp::BitAnd => Ok(Value::Int(left & right)),
+                    InfixOp::BitOr => Ok(Value::Int(left | right)),
+                    InfixOp::BitXor => Ok(Value::Int(left ^ right)),
+                    InfixOp::ShiftLeft => Ok(Value::Int(left.shift_left(right)?)),
+                    InfixOp::ShiftRight => Ok(Value::Int(left.shift_right(right)?)),
                     InfixOp::Comparison(_) => Err(
-                        "comparisons cannot be interpreted as floating point".to_string())
+                        "comparisons cannot be interpreted as floating point".to_string()),
+                    InfixOp::And | InfixOp::Or => Err(
+                        "logical operations cannot be interpreted as integers".to_string())
                 }
             },
             PType::Boolean => {
@@ -83,11 +103,39 @@ impl BinaryOperator<PType, Value> for InfixOp {
                     InfixOp::Comparison(op) => {
                         compare(op, left, right)
                     },
+                    // note: short-circuiting is handled by the evaluator, which only calls this
+                    // when both operands are needed
+                    InfixOp::And => {
+                        let (left, right): (bool, bool) = (left.extract()?, right.extract()?);
+                        Ok(Value::Boolean(left && right))
+                    },
+                    InfixOp::Or => {
+                        let (left, right): (bool, bool) = (left.extract()?, right.extract()?);
+                        Ok(Value::Boolean(left || right))
+                    },
                     _ => Err(format!("unable to interpret type '{}' as boolean", ty))
                 }
             }
+            PType::String => {
+                match *self {
+                    InfixOp::Add => {
+                        let (left, right): (String, String) = (left.extract()?, right.extract()?);
+                        Ok(Value::String(left + &right))
+                    },
+                    _ => Err(format!("unable to interpret operator '{}' on strings", self))
+                }
+            },
             PType::Complex => {
                 let Complex { re: a, im: b } = left.extract()?;
+                // integer and real exponents keep their own type
+                let power = match (*self, right) {
+                    (InfixOp::Power, &Value::Int(n)) => Some(Complex::new(a, b).powi(n)),
+                    (InfixOp::Power, &Value::Float(x)) => Some(Complex::new(a, b).powf(x)),
+                    _ => None
+                };
+                if let Some(Complex { re, im }) = power {
+                    return Ok(Value::Complex(re, im));
+                }
                 let Complex { re: c, im: d } = right.extract()?;
                 match *self {
                     InfixOp::Add => Ok(Value::Complex(a + c, b + d)),
@@ -104,10 +152,18 @@ impl BinaryOperator<PType, Value> for InfixOp {
                         ))
                     },
                     InfixOp::Power => {
-                        Err("exponentiation of complex numbers currently unimplemented".to_string())
+                        let Complex { re, im } = Complex::new(a, b).powc(Complex::new(c, d));
+                        Ok(Value::Complex(re, im))
                     },
+                    InfixOp::FloorDivide | InfixOp::Modulo => Err(
+                        "floor division cannot be interpreted as complex".to_string()),
+                    InfixOp::BitAnd | InfixOp::BitOr | InfixOp::BitXor | InfixOp::ShiftLeft
+                            | InfixOp::ShiftRight => Err(
+                        "bitwise operations cannot be interpreted as complex".to_string()),
                     InfixOp::Comparison(_) => Err(
-                        "comparisons cannot be interpreted as complex".to_string())
+                        "comparisons cannot be interpreted as complex".to_string()),
+                    InfixOp::And | InfixOp::Or => Err(
+                        "logical operations cannot be interpreted as complex".to_string())
                 }
             }
             _ => Err(format!("infix operators invalid for type {}", ty))
@@ -122,7 +178,8 @@ impl UnaryOperator<PType, Value> for PrefixOp {
                 let operand: f64 = operand.extract()?;
                 match *self {
                     PrefixOp::UnaryMinus => Ok(Value::Float(-operand)),
-                    PrefixOp::UnaryPlus => Ok(Value::Float(operand))
+                    PrefixOp::UnaryPlus => Ok(Value::Float(operand)),
+                    PrefixOp::Not => Err(format!("invalid type '{}' for logical not", ty)),
                 }
             },
             PType::Int => {
@@ -130,6 +187,15 @@ impl UnaryOperator<PType, Value> for PrefixOp {
                 match *self {
                     PrefixOp::UnaryMinus => Ok(Value::Int(-operand)),
                     PrefixOp::UnaryPlus => Ok(Value::Int(operand)),
+                    PrefixOp::Not => Err(format!("invalid type '{}' for logical not", ty)),
+                }
+            },
+            PType::Boolean => {
+                let operand: bool = operand.extract()?;
+                match *self {
+                    PrefixOp::Not => Ok(Value::Boolean(!operand)),
+                    PrefixOp::UnaryMinus | PrefixOp::UnaryPlus => Err(format!(
+                        "invalid type '{}' for unary {}", ty, self)),
                 }
             }
             _ => Err(format!("prefix operators invalid for type {}", ty))
@@ -0,0 +1,58 @@
+//! Source code positions of abstract syntax tree nodes.
+//!
+//! Programs, blocks, statements, parameters, struct fields, expressions and sets record their
+//! spans. Identifiers, literals and type specifications do not: sindra fixes the annotation of
+//! `Node<Identifier>`, literals carry no annotation, and type specifications are not nodes.
+//! Errors about them are reported at the innermost enclosing node with a span, e.g. an unknown
+//! parameter type at the parameter, or an undefined variable at the identifier expression.
+
+use sindra::Node;
+
+use ast::{Program, Block, Statement, Parameter, Field, Expression, Set};
+
+/// Byte range of the source code an abstract syntax tree node was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Byte offset of the start of the node
+    pub start: usize,
+    /// Byte offset just past the end of the node
+    pub end: usize,
+}
+impl Span {
+    /// Create a span covering the byte range `start..end`.
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start: start, end: end }
+    }
+    /// Smallest span covering both this span and `other`.
+    pub fn join(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+/// Abstract syntax tree nodes which record the span of source code they were parsed from.
+pub trait Spanned: Sized {
+    /// The span of this node, if known.
+    fn span(&self) -> Option<Span>;
+    /// Set the span of this node.
+    fn set_span(&self, span: Option<Span>);
+
+    /// Record that this node was parsed from the byte range `start..end`, returning the node.
+    fn with_span(self, start: usize, end: usize) -> Self {
+        self.set_span(Some(Span::new(start, end)));
+        self
+    }
+}
+
+macro_rules! impl_spanned {
+    ($($t:ty),*) => {
+        $(
+            impl Spanned for Node<$t> {
+                fn span(&self) -> Option<Span> { self.annotation.borrow().span() }
+                fn set_span(&self, span: Option<Span>) {
+                    self.annotation.borrow_mut().set_span(span);
+                }
+            }
+        )*
+    }
+}
+impl_spanned!(Program, Block, Statement, Parameter, Field, Expression, Set);
@@ -2,8 +2,7 @@ extern crate rustyline;
 extern crate piske;
 extern crate sindra;
 
-use std::io::{Read, Write};
-use std::fs::File;
+use std::io::Write;
 
 use rustyline::{CompletionType, Context, Editor, Helper};
 use rustyline::highlight::Highlighter;
@@ -16,6 +15,7 @@ use sindra::scope::Scoped;
 use piske::parse;
 use piske::glue;
 use piske::visitor::State;
+use piske::visitor::lint::Lints;
 
 mod result { pub type Result<T> = ::std::result::Result<T, String>; }
 type Result = result::Result<()>;
@@ -54,9 +54,10 @@ const STDOUT_ERRSTR: &str = "unable to write to stdout";
 const STDERR_ERRSTR: &str = "unable to write to stderr";
 
 impl<O: Write, E: Write> Repl<O, E> {
-    fn new(mut cout: O, cerr: E) -> Repl<O, E> {
+    fn new(mut cout: O, cerr: E, lints: Lints) -> Repl<O, E> {
         let ast = parse::program("").unwrap();
         let mut state = State::default();
+        state.lints = lints;
         glue::interpret_pipeline(&ast, &mut state).unwrap();
         state.scope = ast.item.0.annotation.borrow().scope().unwrap();
 
@@ -113,7 +114,10 @@ impl<O: Write, E: Write> Repl<O, E> {
                 writeln!(self.cout, "{}", val).unwrap();
             },
             Err(e) => {
-                writeln!(self.cerr, "interpreting statement failed: {}", e).unwrap();
+                // logged errors have already been reported
+                if !e.is_logged() {
+                    writeln!(self.cerr, "{}", e).unwrap();
+                }
             }
         }
 
@@ -122,38 +126,59 @@ impl<O: Write, E: Write> Repl<O, E> {
 
 }
 
-fn interpret_file(file_name: &str) {
-    match File::open(file_name) {
-        Ok(mut file) => {
-            let mut source = String::new();
-            match file.read_to_string(&mut source) {
-                Ok(_) => {
-                    match piske::glue::interpret(&source) {
-                        Ok(_) => {},
-                        Err(e) => {
-                            writeln!(::std::io::stderr(), "interpreting failed: {}", e).unwrap();
-                            ::std::process::exit(1);
-                        }
-                    }
-                },
-                Err(e) => {
-                    writeln!(::std::io::stderr(), "file error: {}", e).unwrap();
-                    ::std::process::exit(1);
-                }
-            }
-        },
-        Err(e) => {
-            writeln!(::std::io::stderr(), "file error: {}", e).unwrap();
-            ::std::process::exit(1);
+fn interpret_file(file_name: &str, lints: Lints) {
+    // imports are resolved relative to the file
+    if let Err(e) = glue::interpret_file_with_lints(file_name, lints) {
+        // logged errors have already been reported
+        if !e.is_logged() {
+            writeln!(::std::io::stderr(), "{}", e).unwrap();
         }
+        ::std::process::exit(1);
     }
 }
 
+fn usage(program: &str) -> ! {
+    writeln!(::std::io::stderr(), "Usage: {} [-W <lint>] [-A <lint>] [<file>]", program).unwrap();
+    writeln!(::std::io::stderr(),
+        "  -W, --warn <lint>   enable the warnings of lint <lint> (or 'all' lints)").unwrap();
+    writeln!(::std::io::stderr(),
+        "  -A, --allow <lint>  disable the warnings of lint <lint> (or 'all' lints)").unwrap();
+    writeln!(::std::io::stderr(), "Lints: {}", Lints::names().join(", ")).unwrap();
+    ::std::process::exit(1);
+}
+
+/// Parse the command-line arguments into the lints to check and the file to interpret, if any.
+fn parse_args(args: &[String]) -> (Lints, Option<String>) {
+    let mut lints = Lints::default();
+    let mut file_name = None;
+    let mut args_iter = args.iter().skip(1);
+    while let Some(arg) = args_iter.next() {
+        let enabled = match arg.as_str() {
+            "-W" | "--warn" => true,
+            "-A" | "--allow" => false,
+            _ if file_name.is_none() && !arg.starts_with('-') => {
+                file_name = Some(arg.clone());
+                continue;
+            },
+            _ => usage(&args[0])
+        };
+        let name = args_iter.next().unwrap_or_else(|| usage(&args[0]));
+        if let Err(e) = lints.set(name, enabled) {
+            writeln!(::std::io::stderr(), "{}", e).unwrap();
+            usage(&args[0]);
+        }
+    }
+    (lints, file_name)
+}
+
 fn main() {
     let args: Vec<String> = ::std::env::args().collect();
-    if args.len() == 1 {
+    let (lints, file_name) = parse_args(&args);
+    if let Some(file_name) = file_name {
+        interpret_file(&file_name, lints);
+    } else {
         // no file passed in, open REPL
-        let result = Repl::new(::std::io::stdout(), ::std::io::stderr()).start();
+        let result = Repl::new(::std::io::stdout(), ::std::io::stderr(), lints).start();
         match result {
             Ok(_) => { ::std::process::exit(0); },
             Err(e) => {
@@ -161,10 +186,5 @@ fn main() {
                 ::std::process::exit(1);
             }
         }
-    } else if args.len() == 2 {
-        interpret_file(&args[1]);
-    } else {
-        writeln!(::std::io::stderr(), "Usage: {} [<file>]", args[0]).unwrap();
-        ::std::process::exit(1);
     }
 }
@@ -0,0 +1,79 @@
+//! Rendering of error and warning messages at positions in the source code.
+
+use ast::Span;
+
+/// Source code being processed, used to locate and display the spans of abstract syntax tree
+/// nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    /// Name of the source (usually a file name)
+    pub name: String,
+    /// The source code
+    pub text: String,
+}
+impl Source {
+    /// Create a source with the specified name and code.
+    pub fn new<N: Into<String>, T: Into<String>>(name: N, text: T) -> Source {
+        Source { name: name.into(), text: text.into() }
+    }
+
+    /// Line and column (both starting at 1) of byte offset `offset`. Columns are counted in
+    /// characters.
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// Render a message of the specified level (e.g. `error`) in the style of rustc diagnostics,
+    /// with the location of `span`, the line of source code containing it, and an underline
+    /// marking the span.
+    ///
+    /// ```text
+    /// error: incompatible types for +: int, string
+    ///  --> example.psk:2:9
+    ///   |
+    /// 2 | let b = a + "1";
+    ///   |         ^^^^^^^
+    /// ```
+    pub fn render(&self, level: &str, message: &str, span: Span) -> String {
+        let span = self.trim(span);
+        let (line, col) = self.location(span.start);
+
+        let line_start = self.text[..span.start].rfind('\n').map_or(0, |pos| pos + 1);
+        let line_end = self.text[span.start..].find('\n').map_or(self.text.len(),
+            |pos| span.start + pos);
+        let source_line = self.text[line_start..line_end].trim_end_matches('\r');
+
+        // underline up to the end of the span, or the end of the line for multi-line spans
+        let underline_end = span.end.min(line_start + source_line.len()).max(span.start);
+        let indent: String = self.text[line_start..span.start].chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+        let carets = self.text[span.start..underline_end].chars().count().max(1);
+
+        let gutter = " ".repeat(line.to_string().len());
+        format!("{}: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}", level, message, gutter,
+            self.name, line, col, gutter, line, source_line, gutter, indent, "^".repeat(carets))
+    }
+
+    /// Span with leading and trailing whitespace removed.
+    fn trim(&self, span: Span) -> Span {
+        let start = self.clamp(span.start);
+        let end = self.clamp(span.end).max(start);
+        let text = &self.text[start..end];
+        let trimmed = text.trim_start();
+        let start = start + (text.len() - trimmed.len());
+        Span::new(start, start + trimmed.trim_end().len())
+    }
+
+    /// Largest character boundary in the source which is not past `offset`.
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
@@ -0,0 +1,177 @@
+//! Errors reported by the stages of processing a piske program.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use psk_std::StdlibError;
+
+use ast::Span;
+use diagnostic::Source;
+
+/// Description of an error: what went wrong, and where.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorDetail {
+    /// Error message
+    pub message: String,
+    /// Span of the source code where the error occurred, if known
+    pub span: Option<Span>,
+    /// Source code in which the error occurred, if known
+    pub source: Option<Arc<Source>>,
+    /// Additional information about the error
+    pub notes: Vec<String>,
+    /// Whether the error has already been reported through the logger of the visitor state (errors
+    /// found while defining symbols and computing types are logged as they are found, so that all
+    /// of them are reported)
+    pub logged: bool,
+}
+impl ErrorDetail {
+    /// Create an error description with the specified message, and no known location.
+    pub fn new<M: Into<String>>(message: M) -> ErrorDetail {
+        ErrorDetail {
+            message: message.into(),
+            span: None,
+            source: None,
+            notes: vec![],
+            logged: false,
+        }
+    }
+}
+
+/// Error raised while processing a piske program, distinguished by the kind of failure.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PiskeError {
+    /// Failure to read a source file
+    Io(ErrorDetail),
+    /// Syntax error
+    Parse(ErrorDetail),
+    /// Error found while defining and resolving symbols (including failed imports)
+    Symbol(ErrorDetail),
+    /// Error found while computing and checking types
+    Type(ErrorDetail),
+    /// Error while evaluating a program
+    Runtime(ErrorDetail),
+    /// Error raised by a standard library function
+    Stdlib(ErrorDetail),
+    /// Failure to transpile a program
+    Transpile(ErrorDetail),
+}
+
+macro_rules! error_constructors {
+    ($($name:ident => $variant:ident),*) => {
+        $(
+            /// Create an error of this kind with the specified message.
+            pub fn $name<M: Into<String>>(message: M) -> PiskeError {
+                PiskeError::$variant(ErrorDetail::new(message))
+            }
+        )*
+    }
+}
+
+impl PiskeError {
+    error_constructors!(io => Io, parse => Parse, symbol => Symbol, type_error => Type,
+        runtime => Runtime, stdlib => Stdlib, transpile => Transpile);
+
+    /// Name of the kind of error (`io`, `parse`, `symbol`, `type`, `runtime`, `stdlib` or
+    /// `transpile`), for reporting errors in a machine-readable way.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            PiskeError::Io(_) => "io",
+            PiskeError::Parse(_) => "parse",
+            PiskeError::Symbol(_) => "symbol",
+            PiskeError::Type(_) => "type",
+            PiskeError::Runtime(_) => "runtime",
+            PiskeError::Stdlib(_) => "stdlib",
+            PiskeError::Transpile(_) => "transpile",
+        }
+    }
+    /// Description of the error.
+    pub fn detail(&self) -> &ErrorDetail {
+        match *self {
+            PiskeError::Io(ref detail) | PiskeError::Parse(ref detail)
+                | PiskeError::Symbol(ref detail) | PiskeError::Type(ref detail)
+                | PiskeError::Runtime(ref detail) | PiskeError::Stdlib(ref detail)
+                | PiskeError::Transpile(ref detail) => detail
+        }
+    }
+    /// Mutable description of the error.
+    pub fn detail_mut(&mut self) -> &mut ErrorDetail {
+        match *self {
+            PiskeError::Io(ref mut detail) | PiskeError::Parse(ref mut detail)
+                | PiskeError::Symbol(ref mut detail) | PiskeError::Type(ref mut detail)
+                | PiskeError::Runtime(ref mut detail) | PiskeError::Stdlib(ref mut detail)
+                | PiskeError::Transpile(ref mut detail) => detail
+        }
+    }
+    /// Error message.
+    pub fn message(&self) -> &str { &self.detail().message }
+    /// Span of the source code where the error occurred, if known.
+    pub fn span(&self) -> Option<Span> { self.detail().span }
+    /// Additional information about the error.
+    pub fn notes(&self) -> &[String] { &self.detail().notes }
+    /// Whether the error has already been reported through the logger of the visitor state.
+    pub fn is_logged(&self) -> bool { self.detail().logged }
+    /// Name of the source the error occurred in, with the line and column (both starting at 1)
+    /// of the start of the error, if known.
+    pub fn location(&self) -> Option<(&str, usize, usize)> {
+        let detail = self.detail();
+        match (detail.source.as_ref(), detail.span) {
+            (Some(source), Some(span)) => {
+                let (line, col) = source.location(span.start);
+                Some((&source.name, line, col))
+            },
+            _ => None,
+        }
+    }
+
+    /// Locate the error at `span`, unless it already has a (more specific) span.
+    pub fn located(mut self, span: Option<Span>) -> PiskeError {
+        if self.detail().span.is_none() {
+            self.detail_mut().span = span;
+        }
+        self
+    }
+    /// Record that the error occurred in `source`, unless it already has a source.
+    pub fn in_source(mut self, source: Option<&Arc<Source>>) -> PiskeError {
+        if self.detail().source.is_none() {
+            self.detail_mut().source = source.cloned();
+        }
+        self
+    }
+    /// Add a note with additional information about the error.
+    pub fn with_note<N: Into<String>>(mut self, note: N) -> PiskeError {
+        self.detail_mut().notes.push(note.into());
+        self
+    }
+}
+
+impl fmt::Display for PiskeError {
+    /// Displays the error in the style of rustc diagnostics, including the line of source code
+    /// where it occurred if known.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let detail = self.detail();
+        match (detail.source.as_ref(), detail.span) {
+            (Some(source), Some(span)) => {
+                write!(f, "{}", source.render("error", &detail.message, span))?;
+            },
+            (Some(source), None) => {
+                write!(f, "error: {}\n --> {}", detail.message, source.name)?;
+            },
+            _ => { write!(f, "error: {}", detail.message)?; }
+        }
+        for note in detail.notes.iter() {
+            write!(f, "\n  = note: {}", note)?;
+        }
+        Ok(())
+    }
+}
+impl Error for PiskeError {}
+
+/// Errors raised by the operations on values used while evaluating a program are strings; these
+/// are runtime errors.
+impl From<String> for PiskeError {
+    fn from(message: String) -> PiskeError { PiskeError::runtime(message) }
+}
+impl From<StdlibError> for PiskeError {
+    fn from(err: StdlibError) -> PiskeError { PiskeError::stdlib(err.to_string()) }
+}
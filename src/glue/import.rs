@@ -0,0 +1,130 @@
+//! Loading of files referenced by import statements.
+
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use sindra::Node;
+use sindra::Identifier;
+use sindra::scope::Stack;
+
+use ast::{Statement, Module, Spanned};
+use glue::{pipeline, read_source, parse_error};
+use visitor::State;
+use diagnostic::Source;
+use error::PiskeError;
+use parse;
+
+/// Load the files imported by `statements` (and, recursively, the files they import), resolving
+/// import paths relative to `dir`. Each import statement is annotated with its module.
+///
+/// Each file is only loaded once. When first loaded, its symbols are defined and its types
+/// computed in a scope of its own, and it is added to the list of modules in `state`.
+///
+/// Errors which are not located in an imported file are located at the failing import statement,
+/// in the source of `state`.
+pub fn load_imports(statements: &[Node<Statement>], dir: &Path, state: &mut State)
+        -> Result<(), PiskeError> {
+    load_imports_from(statements, dir, &mut vec![], state)
+}
+
+fn load_imports_from(statements: &[Node<Statement>], dir: &Path, loading: &mut Vec<PathBuf>,
+        state: &mut State) -> Result<(), PiskeError> {
+    for statement in statements {
+        if let Statement::Import(ref path) = statement.item {
+            let module = load_module(&dir.join(path), path, loading, state).map_err(|e| {
+                if e.detail().source.is_some() {
+                    e
+                } else {
+                    e.in_source(state.source.as_ref()).located(statement.span())
+                }
+            })?;
+            statement.annotation.borrow_mut().set_module(Some(module));
+        }
+    }
+    Ok(())
+}
+
+fn load_module(file: &Path, import_path: &str, loading: &mut Vec<PathBuf>, state: &mut State)
+        -> Result<Rc<Module>, PiskeError> {
+    let file = file.canonicalize().map_err(|e| PiskeError::io(format!(
+        "unable to import '{}': {}", import_path, e)))?;
+    if let Some(module) = state.modules.iter().find(|module| module.path == file) {
+        return Ok(Rc::clone(module));
+    }
+    if loading.contains(&file) {
+        return Err(PiskeError::symbol(format!("circular import of '{}'", file.display())));
+    }
+
+    // the module name is used as an identifier (and as a module name in transpiled code)
+    let name = match file.file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) if is_identifier(stem) => Identifier(stem.to_string()),
+        _ => {
+            return Err(PiskeError::symbol(format!(
+                "unable to import '{}': file name is not a valid module name", import_path)));
+        }
+    };
+    if let Some(module) = state.modules.iter().find(|module| module.name == name) {
+        return Err(PiskeError::symbol(format!(
+            "unable to import '{}': module name '{}' already used by '{}'",
+            import_path, name, module.path.display())));
+    }
+
+    let text = read_source(&file)?;
+    let source = Arc::new(Source::new(file.display().to_string(), text));
+    let program = parse::program(&source.text).map_err(|e| parse_error(e, &source))?;
+    let statements = &(program.item.0).item.0;
+    for statement in statements.iter() {
+        match statement.item {
+            Statement::FnDefine(_) | Statement::StructDefine(_) | Statement::Import(_) => {},
+            _ => {
+                return Err(PiskeError::symbol(format!("imported file '{}' may only contain \
+                    function definitions, struct definitions and imports", file.display()))
+                    .in_source(Some(&source)).located(statement.span()));
+            }
+        }
+    }
+
+    // the module's own imports are reported in the module's source
+    loading.push(file.clone());
+    let dir = file.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+    let prev_source = mem::replace(&mut state.source, Some(Arc::clone(&source)));
+    let result = load_imports_from(statements, &dir, loading, state);
+    state.source = prev_source;
+    result?;
+    loading.pop();
+
+    let module = Rc::new(Module { name: name, path: file, program: program, source: source });
+    define_module(&module, state)?;
+    state.modules.push(Rc::clone(&module));
+    Ok(module)
+}
+
+/// Run the symbol definition and type computation visitors on an imported module, in a new scope
+/// directly under the global scope. While visiting, the module's scope takes the place of the
+/// global scope, so that its functions and structs are defined there, and errors are reported in
+/// the module's source. The functions of a module are used by the files importing it, so they are
+/// not reported as unused.
+fn define_module(module: &Module, state: &mut State) -> Result<(), PiskeError> {
+    let module_scope = state.global.push();
+    let prev_global = mem::replace(&mut state.global, Rc::clone(&module_scope));
+    let prev_scope = mem::replace(&mut state.scope, module_scope);
+    let prev_source = mem::replace(&mut state.source, Some(Arc::clone(&module.source)));
+    let prev_lints = state.lints;
+    state.lints.unused_functions = false;
+    let result = pipeline(&module.program, state);
+    state.global = prev_global;
+    state.scope = prev_scope;
+    state.source = prev_source;
+    state.lints = prev_lints;
+    result
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false
+    }
+}
@@ -1,30 +1,62 @@
 //! Collection of functions for interpreting programs.
 
-use sindra::log::LogPriority;
+use std::path::Path;
+use std::sync::Arc;
+use std::slice;
+use std::thread;
 
 use visitor::{self, State};
+use visitor::state::EVAL_STACK_SIZE;
+use visitor::lint::Lints;
 use value::Value;
-use glue::pipeline;
+use diagnostic::Source;
+use error::PiskeError;
+use glue::{pipeline, load_imports, read_source, source_dir, parse_error, parse_program};
+use glue::pipeline::check_logged_errors;
 use parse;
 
+/// Run `f` on a new thread with a stack of `EVAL_STACK_SIZE` bytes, waiting for it to finish, so
+/// that evaluation can reach the default maximum call depth without overflowing the stack of the
+/// calling thread. Panics in `f` are resumed on the calling thread.
+pub fn with_eval_stack<F, T>(f: F) -> T where F: FnOnce() -> T {
+    // `f` and its result can hold values which are not `Send` (the nodes, scopes and values of a
+    // program are reference-counted). They are only accessed by the new thread while the calling
+    // thread is blocked waiting for it, so they are never accessed from both threads at once.
+    struct Handoff<T>(T);
+    unsafe impl<T> Send for Handoff<T> {}
+
+    let f = Handoff(f);
+    thread::scope(|scope| {
+        let handle = thread::Builder::new().stack_size(EVAL_STACK_SIZE)
+            .spawn_scoped(scope, move || {
+                let f = f;
+                Handoff((f.0)())
+            })
+            .expect("unable to spawn evaluation thread");
+        match handle.join() {
+            Ok(result) => result.0,
+            Err(panic) => ::std::panic::resume_unwind(panic),
+        }
+    })
+}
+
 /// Full interpreter pipeline
-pub fn interpret_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Value, String>
+pub fn interpret_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Value, PiskeError>
         where T: visitor::symbol::SymbolDefineVisitor +
                  visitor::type_visitor::TypeComputationVisitor +
+                 visitor::lint::LintVisitor +
                  visitor::eval::EvaluateVisitor {
     pipeline(ast, &mut state)?;
 
     // evaluate
     let final_val = {
-        match visitor::eval::EvaluateVisitor::visit(ast, &mut state) {
+        match with_eval_stack(|| visitor::eval::EvaluateVisitor::visit(ast, &mut state)) {
             Ok(value) => {
-                if state.logger.flush() == Some(LogPriority::Error) {
-                    return Err(format!("stopping due to previous error(s)"));
-                }
+                check_logged_errors(state, PiskeError::Runtime)?;
                 value
             },
             Err(e) => {
-                return Err(format!("fatal error during evaluation: {}", e));
+                return Err(e.in_source(state.source.as_ref()));
             }
         }
     };
@@ -32,32 +64,61 @@ pub fn interpret_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Value, St
     Ok(final_val)
 }
 
-/// Interpret a single statement
+/// Interpret a single statement. Imports are resolved relative to the current directory.
 pub fn interpret_statement(line: &str, mut state: &mut State)
-        -> Result<Value, String> {
+        -> Result<Value, PiskeError> {
+    let source = Arc::new(Source::new("<input>", line));
+
     // lex the statement
     let statement_ast = match parse::statement(line) {
         Ok(ast) => ast,
         Err(e) => {
-            return Err(format!("failed to parse statement: {}", e));
+            return Err(parse_error(e, &source));
         }
     };
 
+    state.source = Some(source);
+    load_imports(slice::from_ref(&statement_ast), Path::new(""), &mut state)?;
     interpret_pipeline(&statement_ast, &mut state)
 }
 
-/// Interpret a program, given as a string.
-pub fn interpret(program: &str) -> Result<Value, String> {
-    // lex the program
-    let ast = match parse::program(program) {
-        Ok(ast) => ast,
-        Err(e) => {
-            return Err(format!("failed to lex program: {}", e));
-        }
-    };
+/// Interpret a program, given as a string. Imports are resolved relative to the current
+/// directory.
+pub fn interpret(program: &str) -> Result<Value, PiskeError> {
+    interpret_in(program, "<input>", Path::new(""), Lints::default())
+}
+
+/// Interpret a program, given as a string, checking the lints `lints`. Imports are resolved
+/// relative to the current directory.
+pub fn interpret_with_lints(program: &str, lints: Lints) -> Result<Value, PiskeError> {
+    interpret_in(program, "<input>", Path::new(""), lints)
+}
+
+/// Interpret a program from a source file. Imports are resolved relative to the directory
+/// containing the file.
+pub fn interpret_file<P: AsRef<Path>>(path: P) -> Result<Value, PiskeError> {
+    interpret_file_with_lints(path, Lints::default())
+}
+
+/// Interpret a program from a source file, checking the lints `lints`. Imports are resolved
+/// relative to the directory containing the file.
+pub fn interpret_file_with_lints<P: AsRef<Path>>(path: P, lints: Lints)
+        -> Result<Value, PiskeError> {
+    let path = path.as_ref();
+    interpret_in(&read_source(path)?, &path.display().to_string(), source_dir(path), lints)
+}
+
+fn interpret_in(program: &str, name: &str, dir: &Path, lints: Lints)
+        -> Result<Value, PiskeError> {
+    let source = Arc::new(Source::new(name, program));
 
     // set up a default state
     let mut state = State::default();
+    state.source = Some(Arc::clone(&source));
+    state.lints = lints;
+
+    let ast = parse_program(&source, dir, &mut state)?;
 
+    load_imports(&(ast.item.0).item.0, dir, &mut state)?;
     interpret_pipeline(&ast, &mut state)
 }
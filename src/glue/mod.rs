@@ -1,10 +1,92 @@
 //! Code for putting together compilation steps.
 
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use sindra::Node;
+
+use ast::{Program, Span};
+use error::PiskeError;
+use diagnostic::Source;
+use visitor::State;
+use visitor::lint::Lints;
+use parse::{self, ParseError};
+
 mod pipeline;
 pub use self::pipeline::pipeline;
 
+mod import;
+pub use self::import::load_imports;
+
 mod interpret;
-pub use self::interpret::{interpret_pipeline, interpret_statement, interpret};
+pub use self::interpret::{interpret_pipeline, interpret_statement, interpret, interpret_file,
+    interpret_with_lints, interpret_file_with_lints, with_eval_stack};
 
 mod transpile;
-pub use self::transpile::transpile;
+pub use self::transpile::{transpile, transpile_file, transpile_with_lints,
+    transpile_file_with_lints, cargo_manifest};
+
+/// Read the contents of a source file.
+fn read_source(path: &Path) -> Result<String, PiskeError> {
+    let mut source = String::new();
+    File::open(path).and_then(|mut file| file.read_to_string(&mut source))
+        .map_err(|e| PiskeError::io(format!("unable to read file '{}': {}", path.display(), e)))?;
+    Ok(source)
+}
+
+/// Syntax error in `source`, located at the position where parsing failed.
+fn parse_error(err: ParseError, source: &Arc<Source>) -> PiskeError {
+    let mut expected: Vec<&str> = err.expected.iter().cloned().collect();
+    expected.sort();
+    let message = match expected.len() {
+        0 => "expected end of input".to_string(),
+        1 => format!("expected {}", expected[0]),
+        _ => format!("expected one of {}", expected.join(", ")),
+    };
+    let position = Span::new(err.offset, err.offset);
+    PiskeError::parse(message).in_source(Some(source)).located(Some(position))
+}
+
+/// Parse a program in recovering mode, logging its syntax errors (up to the recovery limit of
+/// `parse::program_recovering`). If there are syntax errors, the imports, symbols and types of the
+/// rest of the program are still checked (to report as many errors as possible in one run), and the
+/// first syntax error is returned.
+fn parse_program(source: &Arc<Source>, dir: &Path, state: &mut State)
+        -> Result<Node<Program>, PiskeError> {
+    let (ast, errors) = parse::program_recovering(&source.text);
+    if errors.is_empty() {
+        return Ok(ast);
+    }
+    let errors: Vec<PiskeError> = errors.into_iter().map(|e| parse_error(e, source)).collect();
+    for error in errors.iter() {
+        state.logger.error(error.to_string());
+    }
+
+    // check the partially parsed program; lints are not checked, since the skipped code could
+    // cause spurious warnings (e.g. about variables only used in the skipped code)
+    let lints = state.lints;
+    state.lints = Lints::none();
+    let checked = load_imports(&(ast.item.0).item.0, dir, state)
+        .and_then(|_| pipeline(&ast, state));
+    state.lints = lints;
+    match checked {
+        Err(ref e) if !e.is_logged() => { state.logger.error(e.to_string()); },
+        _ => {}
+    }
+    state.logger.flush();
+
+    let count = errors.len();
+    let mut first = errors.into_iter().next().unwrap();
+    first.detail_mut().logged = true;
+    if count > 1 {
+        first = first.with_note(format!("{} further syntax error(s) reported", count - 1));
+    }
+    Err(first)
+}
+
+/// Directory against which the imports of a source file are resolved.
+fn source_dir(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
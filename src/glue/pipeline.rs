@@ -1,36 +1,68 @@
-use sindra::log::LogPriority;
-
+use error::{PiskeError, ErrorDetail};
 use visitor::state::State;
 use visitor;
 
 /// Abstract syntax tree visitor pipeline
-pub fn pipeline<T>(ast: &T, mut state: &mut State) -> Result<(), String>
+pub fn pipeline<T>(ast: &T, mut state: &mut State) -> Result<(), PiskeError>
         where T: visitor::symbol::SymbolDefineVisitor +
-                 visitor::type_visitor::TypeComputationVisitor {
+                 visitor::type_visitor::TypeComputationVisitor +
+                 visitor::lint::LintVisitor {
 
     // define symbols
     match visitor::symbol::SymbolDefineVisitor::visit(ast, &mut state) {
         Ok(_) => {
-            if state.logger.flush() == Some(LogPriority::Error) {
-                return Err(format!("stopping due to previous error(s)"));
-            }
+            check_logged_errors(state, PiskeError::Symbol)?;
         },
         Err(e) => {
-            return Err(format!("fatal error during symbol definition: {}", e));
+            let fatal = format!("fatal error during symbol definition: {}", e);
+            // report errors found before the failure, which are likely to have caused it
+            check_logged_errors(state, PiskeError::Symbol)
+                .map_err(|err| err.with_note(fatal.clone()))?;
+            return Err(PiskeError::symbol(fatal).in_source(state.source.as_ref()));
         }
     }
 
     // compute, check types
     match visitor::type_visitor::TypeComputationVisitor::visit(ast, &mut state) {
         Ok(_) => {
-            if state.logger.flush() == Some(LogPriority::Error) {
-                return Err(format!("stopping due to previous error(s)"));
-            }
+            check_logged_errors(state, PiskeError::Type)?;
         },
         Err(e) => {
-            return Err(format!("fatal error during type checking: {}", e));
+            let fatal = format!("fatal error during type checking: {}", e);
+            // report errors found before the failure, which are likely to have caused it
+            check_logged_errors(state, PiskeError::Type)
+                .map_err(|err| err.with_note(fatal.clone()))?;
+            return Err(PiskeError::type_error(fatal).in_source(state.source.as_ref()));
         }
     }
 
+    // check lints, which are only reported as warnings
+    visitor::lint::LintVisitor::visit(ast, &mut state);
+    state.logger.flush();
+
     Ok(())
 }
+
+/// Flush the logger, failing with an error of kind `kind` if any errors were logged. The error
+/// describes the first logged error.
+pub fn check_logged_errors(state: &mut State, kind: fn(ErrorDetail) -> PiskeError)
+        -> Result<(), PiskeError> {
+    // the logger only reports the priority of the last message flushed, which misses errors
+    // followed by warnings, so the collected errors decide the outcome
+    state.logger.flush();
+    let errors = state.take_errors();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let count = errors.len();
+    let mut detail = errors.into_iter().next().unwrap_or_else(|| {
+        let mut detail = ErrorDetail::new("stopping due to previous error(s)");
+        detail.logged = true;
+        detail
+    });
+    if count > 1 {
+        detail.notes.push(format!("{} further error(s) reported", count - 1));
+    }
+    Err(kind(detail))
+}
+
@@ -1,14 +1,19 @@
-use quote::Tokens;
+use std::path::Path;
+use std::sync::Arc;
 
-use sindra::log::LogPriority;
+use quote::Tokens;
 
-use parse;
-use glue::pipeline;
+use glue::{pipeline, load_imports, read_source, source_dir, parse_program};
+use glue::pipeline::check_logged_errors;
 use visitor::{self, State};
+use visitor::lint::Lints;
+use diagnostic::Source;
+use error::PiskeError;
 
-fn transpile_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Tokens, String>
+fn transpile_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Tokens, PiskeError>
         where T: visitor::symbol::SymbolDefineVisitor +
                  visitor::type_visitor::TypeComputationVisitor +
+                 visitor::lint::LintVisitor +
                  visitor::transpile::TranspileVisitor {
     pipeline(ast, &mut state)?;
 
@@ -16,13 +21,12 @@ fn transpile_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Tokens, Strin
     let transpiled = {
         match visitor::transpile::TranspileVisitor::visit(ast, &mut state) {
             Ok(value) => {
-                if state.logger.flush() == Some(LogPriority::Error) {
-                    return Err(format!("stopping due to previous error(s)"));
-                }
+                check_logged_errors(state, PiskeError::Transpile)?;
                 value
             },
             Err(e) => {
-                return Err(format!("fatal error during transpilation: {}", e));
+                return Err(PiskeError::transpile(format!("fatal error during transpilation: {}",
+                    e)).in_source(state.source.as_ref()));
             }
         }
     };
@@ -30,18 +34,65 @@ fn transpile_pipeline<T>(ast: &T, mut state: &mut State) -> Result<Tokens, Strin
     Ok(transpiled)
 }
 
-/// Transpile a program, given as a string.
-pub fn transpile(program: &str) -> Result<Tokens, String> {
-    // lex the program
-    let ast = match parse::program(program) {
-        Ok(ast) => ast,
-        Err(e) => {
-            return Err(format!("failed to lex program: {}", e));
-        }
-    };
+/// Transpile a program, given as a string. Imports are resolved relative to the current directory.
+pub fn transpile(program: &str) -> Result<Tokens, PiskeError> {
+    transpile_in(program, "<input>", Path::new(""), Lints::default())
+}
+
+/// Transpile a program, given as a string, checking the lints `lints`. Imports are resolved
+/// relative to the current directory.
+pub fn transpile_with_lints(program: &str, lints: Lints) -> Result<Tokens, PiskeError> {
+    transpile_in(program, "<input>", Path::new(""), lints)
+}
+
+/// Transpile a program from a source file. Imports are resolved relative to the directory
+/// containing the file.
+pub fn transpile_file<P: AsRef<Path>>(path: P) -> Result<Tokens, PiskeError> {
+    transpile_file_with_lints(path, Lints::default())
+}
+
+/// Transpile a program from a source file, checking the lints `lints`. Imports are resolved
+/// relative to the directory containing the file.
+pub fn transpile_file_with_lints<P: AsRef<Path>>(path: P, lints: Lints)
+        -> Result<Tokens, PiskeError> {
+    let path = path.as_ref();
+    transpile_in(&read_source(path)?, &path.display().to_string(), source_dir(path), lints)
+}
+
+fn transpile_in(program: &str, name: &str, dir: &Path, lints: Lints)
+        -> Result<Tokens, PiskeError> {
+    let source = Arc::new(Source::new(name, program));
 
     // set up a default state
     let mut state = State::default();
+    state.source = Some(Arc::clone(&source));
+    state.lints = lints;
+
+    let ast = parse_program(&source, dir, &mut state)?;
 
+    load_imports(&(ast.item.0).item.0, dir, &mut state)?;
     transpile_pipeline(&ast, &mut state)
 }
+
+/// Contents of the `Cargo.toml` of project `name`, for building a transpiled program. Transpiled
+/// programs depend on the version of the piske standard library (`psk_std`) that piske was built
+/// with. When piske is built from its repository, the library is taken from the repository
+/// instead of crates.io, so that changes which have not been published yet are available.
+pub fn cargo_manifest(name: &str) -> String {
+    let local = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/psk_std"));
+    let psk_std = if local.join("Cargo.toml").is_file() {
+        format!("{{ path = '{}', version = \"{}\" }}", local.display(), psk_std::VERSION)
+    } else {
+        format!("\"{}\"", psk_std::VERSION)
+    };
+    format!(
+r#"[package]
+name = "{}"
+version = "0.1.0"
+authors = ["Jamie Blondin <jblondin@gmail.com>"]
+
+[dependencies]
+psk_std = {}
+image = "0.18"
+"#, name, psk_std)
+}
@@ -21,6 +21,9 @@ pub mod parse {
     use sindra::Identifier;
 
     include!(concat!(env!("OUT_DIR"), "/piske.rs"));
+
+    mod recovery;
+    pub use self::recovery::{program_recovering, MAX_PARSES};
 }
 
 pub mod ptype;
@@ -31,5 +34,8 @@ pub use symbol::Symbol;
 
 pub mod value;
 pub mod visitor;
+pub mod diagnostic;
+pub mod error;
+pub use error::PiskeError;
 
 pub mod glue;
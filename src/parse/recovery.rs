@@ -0,0 +1,139 @@
+//! Recovering parser mode, which continues past syntax errors to report all of them.
+//!
+//! After a syntax error, the parser resynchronizes at the next `;` or `}`: the statement containing
+//! the error is blanked out (replaced by whitespace, so that the positions of the remaining code
+//! are unchanged) and the program is parsed again, until it parses successfully.
+//!
+//! The generated parser stops at the first error, so each skipped statement costs a parse of the
+//! whole program: recovering from `n` errors in a program of length `m` takes `O(n * m)` time. To
+//! bound this, recovery gives up after `MAX_PARSES` parses. The resynchronization points are found
+//! by scanning the text, which only distinguishes code from string literals and comments; an
+//! error inside an unclosed block skips the rest of the program.
+
+use sindra::Node;
+
+use ast::Program;
+use super::{program, ParseError};
+
+/// Maximum number of times a program is parsed while recovering from its syntax errors.
+pub const MAX_PARSES: usize = 20;
+
+/// Parse a program, recovering from syntax errors. Returns the program parsed from the code
+/// remaining after skipping the statements containing syntax errors, along with the errors (in
+/// the order they were found). If the program can't be parsed within `MAX_PARSES` attempts, the
+/// returned program is empty, and errors in the code not yet skipped are not reported.
+pub fn program_recovering(input: &str) -> (Node<Program>, Vec<ParseError>) {
+    let code = code_mask(input);
+    let mut text = input.to_string();
+    let mut errors: Vec<ParseError> = vec![];
+    for _ in 0..MAX_PARSES {
+        let err = match program(&text) {
+            Ok(ast) => { return (ast, errors); },
+            Err(err) => err,
+        };
+        let skipped = skipped_range(&text, &code, err.offset);
+        if !errors.iter().any(|prev| prev.offset == err.offset) {
+            errors.push(err);
+        }
+        match skipped {
+            Some((start, end)) => blank(&mut text, start, end),
+            None => { break; }
+        }
+    }
+
+    // nothing left to skip, or too many errors; the program is empty (parsed from an empty string,
+    // since a program consisting only of whitespace is not valid)
+    let empty = program("").expect("empty program is valid");
+    (empty, errors)
+}
+
+/// Whether each byte of `input` is part of the code (and not part of a string literal or
+/// comment), used to ignore `;`, `{` and `}` characters in strings and comments when
+/// resynchronizing.
+fn code_mask(input: &str) -> Vec<bool> {
+    let bytes = input.as_bytes();
+    let mut code = vec![true; bytes.len()];
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = if bytes[i] == b'"' {
+            // string literal, with escapes
+            let mut j = i + 1;
+            while j < bytes.len() && bytes[j] != b'"' {
+                j += if bytes[j] == b'\\' { 2 } else { 1 };
+            }
+            j + 1
+        } else if bytes[i..].starts_with(b"//") {
+            input[i..].find('\n').map_or(bytes.len(), |pos| i + pos)
+        } else if bytes[i..].starts_with(b"/*") {
+            input[i + 2..].find("*/").map_or(bytes.len(), |pos| i + 2 + pos + 2)
+        } else {
+            i += 1;
+            continue;
+        };
+        let end = end.min(bytes.len());
+        for masked in code[i..end].iter_mut() {
+            *masked = false;
+        }
+        i = end;
+    }
+    code
+}
+
+/// Range of code to skip after a syntax error at `offset`, from the start of the statement
+/// containing the error up to the next `;` (inclusive) or the `}` closing the enclosing block
+/// (exclusive). Returns `None` if there is no code left to skip.
+fn skipped_range(text: &str, code: &[bool], offset: usize) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let offset = offset.min(bytes.len());
+    let is_code = |i: usize, c: u8| bytes[i] == c && code[i];
+
+    let boundary_before = |pos: usize| (0..pos).rev()
+        .find(|&i| is_code(i, b';') || is_code(i, b'{') || is_code(i, b'}'));
+
+    // the statement starts after the previous statement or block boundary
+    let mut start = boundary_before(offset).map_or(0, |i| i + 1);
+
+    // skip blocks opened within the statement
+    let mut depth = 0;
+    let mut end = bytes.len();
+    for i in offset..bytes.len() {
+        if is_code(i, b'{') {
+            depth += 1;
+        } else if is_code(i, b'}') {
+            if depth == 0 {
+                // a stray '}' is skipped if nothing precedes it
+                end = if is_blank(&text[start..i]) { i + 1 } else { i };
+                break;
+            }
+            depth -= 1;
+        } else if is_code(i, b';') && depth == 0 {
+            end = i + 1;
+            break;
+        }
+    }
+
+    // errors at the end of a statement (e.g. at the end of the input, for an unclosed block) are
+    // caused by earlier code; skip back to the previous boundary until there is code to skip
+    while is_blank(&text[start..end]) && start > 0 {
+        start = boundary_before(start - 1).map_or(0, |i| i + 1);
+    }
+
+    if is_blank(&text[start..end]) { None } else { Some((start, end)) }
+}
+
+fn is_blank(text: &str) -> bool {
+    text.chars().all(char::is_whitespace)
+}
+
+/// Replace the code in `text[start..end]` by whitespace, leaving line breaks (and the byte
+/// offsets of the following code) unchanged.
+fn blank(text: &mut String, start: usize, end: usize) {
+    let replacement = blanked(&text[start..end]);
+    text.replace_range(start..end, &replacement);
+}
+
+fn blanked(text: &str) -> String {
+    text.chars().map(|c| {
+        if c == '\n' || c == '\r' { c.to_string() } else { " ".repeat(c.len_utf8()) }
+    }).collect()
+}
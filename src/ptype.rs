@@ -1,10 +1,12 @@
 //! The valid types of the piske programming language.
 
-use sindra::Type;
+use sindra::{Type, Identifier};
 use std::fmt;
 
+use ast::Literal;
+
 /// The types available in the piske programming language. Implements the sindra `Type` trait.
-#[derive(Copy, Debug, Clone, Hash, PartialEq)]
+#[derive(Debug, Clone, Hash, PartialEq)]
 pub enum PType {
     /// Built-in string type
     String,
@@ -19,7 +21,15 @@ pub enum PType {
     /// Set (collection)
     Set,
     /// Empty type
-    Void
+    Void,
+    /// User-defined struct, identified by its name
+    Struct(Identifier),
+    /// Array (with elements of the specified type)
+    Array(Box<PType>),
+    /// Function (with the specified parameter types and return type)
+    Function(Vec<PType>, Box<PType>),
+    /// Tuple (with elements of the specified types)
+    Tuple(Vec<PType>),
 }
 impl Type for PType {
     fn name(&self) -> &str {
@@ -31,6 +41,10 @@ impl Type for PType {
             PType::Complex => "complex",
             PType::Set => "set",
             PType::Void => "void",
+            PType::Struct(ref name) => &name.0,
+            PType::Array(_) => "array",
+            PType::Function(_, _) => "function",
+            PType::Tuple(_) => "tuple",
         }
     }
 }
@@ -48,8 +62,42 @@ impl<'a> From<&'a str> for PType {
     }
 }
 
+impl<'a> From<&'a Literal> for PType {
+    fn from(lit: &'a Literal) -> PType {
+        match *lit {
+            Literal::String(_) => PType::String,
+            Literal::Float(_) => PType::Float,
+            Literal::Int(_) => PType::Int,
+            Literal::Boolean(_) => PType::Boolean,
+        }
+    }
+}
+
 impl fmt::Display for PType {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
-        write!(f, "{}", self.name())
+        match *self {
+            PType::Array(ref elem) => write!(f, "[{}]", elem),
+            PType::Function(ref params, ref ret) => {
+                write!(f, "fn(")?;
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", param)?;
+                }
+                write!(f, ") -> {}", ret)
+            },
+            PType::Tuple(ref elems) => {
+                write!(f, "(")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, ")")
+            },
+            _ => write!(f, "{}", self.name())
+        }
     }
 }
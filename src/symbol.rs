@@ -2,10 +2,11 @@
 
 use std::fmt;
 
-use sindra::Identifier;
+use sindra::{Type, Identifier};
 use sindra::node::Node;
+use sindra::scope::SymbolStore;
 
-use ast::{Block, Parameter};
+use ast::{Block, Parameter, Field, Literal};
 use PType;
 
 use visitor::interp::ExtFuncIdent;
@@ -27,6 +28,16 @@ pub enum Symbol {
         /// Type of this variable (an Option, since this type is not known at all times of the
         /// computation)
         ty: Option<PType>,
+        /// Whether this is a global variable (declared with a `set` statement, and accessible
+        /// from all functions)
+        global: bool,
+    },
+    /// Named constants (e.g. `pi`), which are replaced by their value wherever they are used
+    Constant {
+        /// Name of the constant
+        name: Identifier,
+        /// Value of the constant
+        value: Literal,
     },
     /// Functions
     Function {
@@ -40,6 +51,20 @@ pub enum Symbol {
         /// Function parameters,
         params: Vec<Node<Parameter>>,
     },
+    /// Functions defined more than once with different parameter types
+    Overloaded {
+        /// Name of the functions
+        name: Identifier,
+        /// The function definitions (each a `Symbol::Function`), in order of definition
+        functions: Vec<Symbol>,
+    },
+    /// User-defined struct types
+    Struct {
+        /// Name of the struct
+        name: Identifier,
+        /// Struct fields (field types are stored in the field annotations once computed)
+        fields: Vec<Node<Field>>,
+    },
 }
 
 /// Function body types
@@ -49,6 +74,34 @@ pub enum FunctionBody {
     External(ExtFuncIdent),
     /// Ast-defined block
     Ast(Node<Block>),
+    /// Language intrinsic (function handled directly by the visitors)
+    Intrinsic(Intrinsic),
+}
+
+/// Intrinsic functions, which operate on values of more than one type and are therefore handled
+/// directly by the visitors instead of through parameter type checking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intrinsic {
+    /// Length of an array: `len(array)`
+    Len,
+    /// Append an element to the end of an array: `push(array, value)`
+    Push,
+    /// Convert a value to a string: `str(value)`
+    Str,
+    /// Format values into a string: `format("iter_{:04}.png", i)`
+    Format,
+}
+impl Intrinsic {
+    /// Number of arguments expected by this intrinsic, or `None` if it takes a format string
+    /// followed by any number of arguments
+    pub fn arity(&self) -> Option<usize> {
+        match *self {
+            Intrinsic::Len => Some(1),
+            Intrinsic::Push => Some(2),
+            Intrinsic::Str => Some(1),
+            Intrinsic::Format => None,
+        }
+    }
 }
 
 impl Symbol {
@@ -79,11 +132,50 @@ impl Symbol {
             params: params,
         }
     }
+    /// Create an intrinsic function Symbol
+    pub fn intrinsic(name: Identifier, intrinsic: Intrinsic) -> Symbol {
+        Symbol::Function {
+            name: name,
+            ret_ty: None,
+            body: FunctionBody::Intrinsic(intrinsic),
+            params: vec![],
+        }
+    }
+    /// Create a struct Symbol, with specified fields
+    pub fn structure(name: Identifier, fields: Vec<Node<Field>>) -> Symbol {
+        Symbol::Struct {
+            name: name,
+            fields: fields,
+        }
+    }
+    /// Create a named constant Symbol, with specified value
+    pub fn constant(name: Identifier, value: Literal) -> Symbol {
+        Symbol::Constant {
+            name: name,
+            value: value
+        }
+    }
     /// Create a variable Symbol
     pub fn variable(name: Identifier, ty: Option<PType>) -> Symbol {
         Symbol::Variable {
             name: name,
-            ty: ty
+            ty: ty,
+            global: false,
+        }
+    }
+    /// Create a global variable Symbol
+    pub fn global_variable(name: Identifier, ty: Option<PType>) -> Symbol {
+        Symbol::Variable {
+            name: name,
+            ty: ty,
+            global: true,
+        }
+    }
+    /// Return true if symbol is a global variable
+    pub fn is_global(&self) -> bool {
+        match *self {
+            Symbol::Variable { global, .. } => global,
+            _ => false
         }
     }
     /// Return true if symbol is a standard library function
@@ -93,15 +185,112 @@ impl Symbol {
             _ => false,
         }
     }
+    /// Return true if this symbol is a function with the same parameter types as `params`
+    pub fn has_params(&self, params: &[Node<Parameter>]) -> bool {
+        match *self {
+            Symbol::Function { params: ref own, .. } => own.len() == params.len()
+                && own.iter().zip(params.iter()).all(|(a, b)| a.item.ty == b.item.ty),
+            _ => false,
+        }
+    }
+    /// The definitions of this (possibly overloaded) function; empty if this is not a user-defined
+    /// or library function
+    pub fn functions(&self) -> Vec<Symbol> {
+        match *self {
+            Symbol::Function { body: FunctionBody::Ast(_), .. }
+                | Symbol::Function { body: FunctionBody::External(_), .. } => vec![self.clone()],
+            Symbol::Overloaded { ref functions, .. } => functions.clone(),
+            _ => vec![],
+        }
+    }
+    /// Find the definition of this (possibly overloaded) function with parameter types `params`
+    pub fn overload(&self, params: &[Node<Parameter>]) -> Option<&Symbol> {
+        match *self {
+            Symbol::Overloaded { ref functions, .. } => {
+                functions.iter().find(|function| function.has_params(params))
+            },
+            ref function => if function.has_params(params) { Some(function) } else { None }
+        }
+    }
+    /// The definition called by a function call to this symbol, given the overload index the call
+    /// was annotated with (symbols which are not overloaded are returned unchanged)
+    pub fn select_overload(self, overload: Option<usize>) -> Result<Symbol, String> {
+        match self {
+            Symbol::Overloaded { name, mut functions } => match overload {
+                Some(index) if index < functions.len() => Ok(functions.swap_remove(index)),
+                _ => Err(format!("no definition of overloaded function '{}' selected", name))
+            },
+            symbol => Ok(symbol)
+        }
+    }
+}
+
+/// Name of the Rust function for one definition of an overloaded function: the piske name followed
+/// by the parameter types (e.g. `mag_float` and `mag_complex` for `mag(x: float)` and
+/// `mag(z: complex)`). Overloaded library functions are named the same way in `psk_std`.
+pub fn overload_name(name: &str, param_tys: &[PType]) -> String {
+    let mut overload_name = name.to_string();
+    for ty in param_tys {
+        overload_name.push('_');
+        overload_name.push_str(&type_name_part(ty));
+    }
+    overload_name
+}
+fn type_name_part(ty: &PType) -> String {
+    match *ty {
+        PType::Array(ref elem_ty) => format!("array_{}", type_name_part(elem_ty)),
+        PType::Tuple(ref elem_tys) => {
+            let parts: Vec<String> = elem_tys.iter().map(type_name_part).collect();
+            format!("tuple_{}", parts.join("_"))
+        },
+        PType::Function(_, _) => "fn".to_string(),
+        ref ty => ty.name().to_string(),
+    }
+}
+
+/// Define a function symbol in `scope`. If a user-defined or library function of the same name is
+/// already visible from `scope`, the new function overloads it (or replaces it, if the parameter
+/// types are the same). Overloads keep their position when replaced, since function calls refer
+/// to them by index.
+pub fn define_function<Sc: SymbolStore<Symbol>>(scope: &mut Sc, name: Identifier,
+        function: Symbol) {
+    let mut functions = match scope.resolve(&name) {
+        Some(existing @ Symbol::Function { body: FunctionBody::Ast(_), .. })
+            | Some(existing @ Symbol::Function { body: FunctionBody::External(_), .. }) => {
+            vec![existing]
+        },
+        Some(Symbol::Overloaded { functions, .. }) => functions,
+        _ => vec![],
+    };
+    let same_params = match function {
+        Symbol::Function { ref params, .. } => {
+            functions.iter().position(|existing| existing.has_params(params))
+        },
+        _ => None
+    };
+    match same_params {
+        Some(index) => { functions[index] = function; },
+        None => { functions.push(function); }
+    }
+    let symbol = if functions.len() == 1 {
+        functions.pop().unwrap()
+    } else {
+        Symbol::Overloaded { name: name.clone(), functions: functions }
+    };
+    scope.define(name, symbol);
 }
 
 
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         let (kind, name, ty) = match *self {
-            Symbol::Variable { ref name,  ref ty } => ("var", name, ty.clone()),
+            Symbol::Variable { ref name, ref ty, global: false } => ("var", name, ty.clone()),
+            Symbol::Variable { ref name, ref ty, global: true } => ("global", name, ty.clone()),
+            Symbol::Constant { ref name, ref value } => ("const", name, Some(PType::from(value))),
             Symbol::Function { ref name, ref ret_ty, .. } => ("fn", name, ret_ty.clone()),
+            Symbol::Overloaded { ref name, .. } => ("overloaded fn", name, None),
             Symbol::BuiltinType { ref name, ref ty } => ("bi", name, Some(ty.clone())),
+            Symbol::Struct { ref name, .. } => ("struct", name, None),
         };
         match ty {
             Some(ty) => write!(f, "{} {}: {}", kind, name, ty),
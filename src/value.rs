@@ -3,11 +3,12 @@
 use std::fmt;
 use std::ops::Add;
 use std::cmp::Ordering;
+use std::rc::Rc;
 
-use sindra;
+use sindra::{self, Identifier, Node};
 use sindra::value::{Coerce, Cast, Extract};
 
-use ast::Literal;
+use ast::{Literal, Block};
 use PType;
 
 use psk_std::complex::Complex;
@@ -27,10 +28,21 @@ pub enum Value {
     Complex(f64, f64),
     /// Storage for a set
     Set(Box<ValueSet>),
+    /// Storage for a struct instance
+    Struct(Box<ValueStruct>),
+    /// Storage for an array
+    Array(Vec<Value>),
+    /// Storage for a tuple
+    Tuple(Vec<Value>),
+    /// Storage for a function (named function or closure)
+    Function(Rc<ValueFunction>),
     /// Indication of a value returned from a function
     Return(Box<Value>),
-    /// Indication of a value resulting from a break in a loop
-    Break(Box<Value>),
+    /// Indication of a value resulting from a break in a loop (with the label of the loop to exit,
+    /// if specified)
+    Break(Option<Identifier>, Box<Value>),
+    /// Indication of a continue in a loop (with the label of the loop to continue, if specified)
+    Continue(Option<Identifier>),
     /// Indication of empty / null value
     Empty
 }
@@ -44,8 +56,31 @@ impl fmt::Display for Value {
             Value::Complex(ref re, ref im)
                                   => write!(f, "{}+{}i", re, im),
             Value::Set(ref s)     => write!(f, "{}", s),
+            Value::Struct(ref s)  => write!(f, "{}", s),
+            Value::Array(ref a)   => {
+                write!(f, "[")?;
+                for (i, elem) in a.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            },
+            Value::Tuple(ref t)   => {
+                write!(f, "(")?;
+                for (i, elem) in t.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, ")")
+            },
+            Value::Function(ref func) => write!(f, "{}", func),
             Value::Return(ref v)  => write!(f, "{}", *v),
-            Value::Break(ref v)   => write!(f, "{}", *v),
+            Value::Break(_, ref v) => write!(f, "{}", *v),
+            Value::Continue(_)    => write!(f, "<continue>"),
             Value::Empty          => write!(f, "<null>")
         }
     }
@@ -72,8 +107,16 @@ impl<'a> From<&'a Value> for PType {
             Value::Boolean(_)     => PType::Boolean,
             Value::Complex(_, _)  => PType::Complex,
             Value::Set(_)         => PType::Set,
+            Value::Struct(ref s)  => PType::Struct(s.name.clone()),
+            Value::Array(ref a)   => {
+                // element type of an empty array is unknown at run time
+                PType::Array(Box::new(a.first().map(PType::from).unwrap_or(PType::Void)))
+            },
+            Value::Tuple(ref t)   => PType::Tuple(t.iter().map(PType::from).collect()),
+            Value::Function(ref func) => func.ty.clone(),
             Value::Return(ref v)  => PType::from(v.as_ref()),
-            Value::Break(ref v)   => PType::from(v.as_ref()),
+            Value::Break(_, ref v) => PType::from(v.as_ref()),
+            Value::Continue(_)    => PType::Void,
             Value::Empty          => PType::Void,
         }
     }
@@ -82,6 +125,12 @@ impl<'a> From<&'a Value> for PType {
 impl Cast<PType> for Value {
     fn cast(self, dest_ty: PType) -> Value {
         match dest_ty {
+            PType::Int => {
+                match self {
+                    Value::Float(f) => Value::Int(f as i64),
+                    _ => self
+                }
+            },
             PType::Float => {
                 match self {
                     Value::Int(i) => Value::Float(i as f64),
@@ -102,6 +151,12 @@ impl Cast<PType> for Value {
 impl Cast<PType> for PType {
     fn cast(self, dest_ty: PType) -> PType {
         match dest_ty {
+            PType::Int => {
+                match self {
+                    PType::Float => PType::Int,
+                    _ => self
+                }
+            },
             PType::Float => {
                 match self {
                     PType::Int => PType::Float,
@@ -220,6 +275,14 @@ impl Extract<String> for Value {
         }
     }
 }
+impl Extract<bool> for Value {
+    fn extract(&self) -> Result<bool, String> {
+        match *self {
+            Value::Boolean(b) => Ok(b),
+            _ => Err(format!("unable to extract boolean from type {}", PType::from(self)))
+        }
+    }
+}
 impl Extract<f64> for Value {
     fn extract(&self) -> Result<f64, String> {
         match *self {
@@ -242,11 +305,70 @@ impl Extract<Complex> for Value {
 }
 
 
+/// Value type for struct instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueStruct {
+    /// Name of the struct type
+    pub name: Identifier,
+    /// Field names and values, in struct definition order
+    pub fields: Vec<(Identifier, Value)>,
+}
+
+impl ValueStruct {
+    /// Retrieve the value of the specified field, if it exists.
+    pub fn get(&self, field: &Identifier) -> Option<&Value> {
+        self.fields.iter().find(|&&(ref name, _)| name == field).map(|&(_, ref value)| value)
+    }
+}
+
+impl fmt::Display for ValueStruct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        write!(f, "{} {{ ", self.name)?;
+        let mut first = true;
+        for &(ref name, ref value) in &self.fields {
+            if first {
+                first = false;
+            } else {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", name, value)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// Value type for functions (named functions and closures)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueFunction {
+    /// Name of the function (closures are anonymous)
+    pub name: Option<Identifier>,
+    /// Type of the function
+    pub ty: PType,
+    /// Parameter names
+    pub params: Vec<Identifier>,
+    /// Function body
+    pub body: Node<Block>,
+    /// Variables captured from the scope the closure was created in, with their values at the time
+    /// of creation
+    pub captured: Vec<(Identifier, Value)>,
+}
+
+impl fmt::Display for ValueFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
+        match self.name {
+            Some(ref name) => write!(f, "<fn {}: {}>", name, self.ty),
+            None => write!(f, "<closure: {}>", self.ty),
+        }
+    }
+}
+
 /// Value type for sets
 #[derive(Debug, Clone, PartialEq)]
 pub enum ValueSet {
     /// A set represented by an interval specification
-    Interval(SetInterval)
+    Interval(SetInterval),
+    /// A set represented by its (distinct) elements
+    Elements(Vec<Value>),
 }
 
 impl ValueSet {
@@ -264,20 +386,46 @@ impl ValueSet {
                     return Err("interval must have same value type for start, end, and step \
                         to be iterated".to_string())
                 }
+                match interval.step {
+                    Value::Int(0) => {
+                        return Err("interval step cannot be zero".to_string());
+                    },
+                    Value::Float(step) if step == 0.0 => {
+                        return Err("interval step cannot be zero".to_string());
+                    },
+                    Value::Int(_) | Value::Float(_) => {},
+                    _ => {
+                        return Err("interval must have numeric start, end, and step values to \
+                            be iterated".to_string());
+                    }
+                }
                 SetIter::Interval {
                     set: interval,
-                    prev: None
+                    index: 0
                 }
-            }
+            },
+            ValueSet::Elements(ref elems) => SetIter::Elements(elems.iter()),
         };
         Ok(iter)
     }
+
+    /// Return the elements of the set.
+    ///
+    /// #Failures
+    /// Returns an `Err` if the set cannot be iterated (see `iter`)
+    pub fn elements(&self) -> Result<Vec<Value>, String> {
+        self.iter()?.collect()
+    }
 }
 
 impl fmt::Display for ValueSet {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         match *self {
             ValueSet::Interval(ref set) => write!(f, "{}", set),
+            ValueSet::Elements(ref elems) => {
+                let elems = elems.iter().map(|elem| format!("{}", elem)).collect::<Vec<_>>();
+                write!(f, "{{{}}}", elems.join(", "))
+            },
         }
     }
 }
@@ -295,51 +443,83 @@ pub struct SetInterval {
     pub step: Value,
 }
 
+impl SetInterval {
+    /// Returns the value `n` steps away from the start of the interval. This is computed directly
+    /// (instead of by repeated addition) so that floating-point rounding errors do not accumulate.
+    ///
+    /// #Failures
+    /// Returns an `Err` if the start and step are not both integers or both floats
+    fn nth_value(&self, n: usize) -> Result<Value, String> {
+        match (&self.start, &self.step) {
+            (&Value::Int(start), &Value::Int(step)) => Ok(Value::Int(start + step * n as i64)),
+            (&Value::Float(start), &Value::Float(step)) => {
+                Ok(Value::Float(start + step * n as f64))
+            },
+            _ => Err(format!("unable to step interval with start of type '{}' and step of type \
+                '{}'", PType::from(&self.start), PType::from(&self.step)))
+        }
+    }
+
+    /// Returns true if the interval is iterated downward (i.e. has a negative step).
+    fn is_descending(&self) -> bool {
+        match self.step {
+            Value::Int(step) => step < 0,
+            Value::Float(step) => step < 0.0,
+            _ => false
+        }
+    }
+}
+
 impl fmt::Display for SetInterval {
     fn fmt(&self, f: &mut fmt::Formatter) -> ::std::result::Result<(), fmt::Error> {
         write!(f, "{}..{}..{}", self.start, self.step, self.end)
     }
 }
 
-/// Iterator over a set.
+/// Iterator over a set. Iteration stops after the first `Err`, returned if the next value of an
+/// interval cannot be computed.
 pub enum SetIter<'a> {
     /// Iterator over a set represented by an interval.
     Interval {
         /// Reference to the underlying set interval specification.
         set: &'a SetInterval,
-        /// Previous value returned by iterator
-        prev: Option<Value>,
-    }
+        /// Number of values returned by the iterator so far
+        index: usize,
+    },
+    /// Iterator over a set represented by its elements.
+    Elements(::std::slice::Iter<'a, Value>),
 }
 impl<'a> Iterator for SetIter<'a> {
-    type Item = Value;
+    type Item = Result<Value, String>;
 
-    fn next(&mut self) -> Option<Value> {
+    fn next(&mut self) -> Option<Result<Value, String>> {
         match *self {
-            SetIter::Interval { ref set, ref mut prev } => {
-                let next = match *prev {
-                    Some(ref prev) => {
-                        prev + &set.step
-                    },
-                    None => {
-                        set.start.clone()
+            SetIter::Interval { ref set, ref mut index } => {
+                let next = match set.nth_value(*index) {
+                    Ok(next) => next,
+                    Err(e) => {
+                        *self = SetIter::Elements([].iter());
+                        return Some(Err(e));
                     }
                 };
                 // terminate only when completely past the end of the set when the set is inclusive,
-                // terminate when at the end or beyond when the set is exclusive
-                let terminate = if set.end_inclusive {
-                    next > set.end
-                } else {
-                    next >= set.end
+                // terminate when at the end or beyond when the set is exclusive ('past' is below the
+                // end for descending intervals)
+                let terminate = match (set.is_descending(), set.end_inclusive) {
+                    (false, true) => next > set.end,
+                    (false, false) => next >= set.end,
+                    (true, true) => next < set.end,
+                    (true, false) => next <= set.end,
                 };
                 if terminate {
                     None
                 } else {
-                    *prev = Some(next.clone());
-                    Some(next)
+                    *index += 1;
+                    Some(Ok(next))
                 }
 
-            }
+            },
+            SetIter::Elements(ref mut elems) => elems.next().cloned().map(Ok),
         }
     }
 }
@@ -4,21 +4,341 @@
 //! and evaluating it. This implementation expects that the symbol table and type computation
 //! annotations already exist on the tree.
 
+use std::cell::RefCell;
 use std::rc::Rc;
 
-use sindra::Node;
+use sindra::{Node, Identifier};
 use sindra::Typed;
-use sindra::scope::{SymbolStore, MemoryStore, Scoped};
+use sindra::scope::{SymbolStore, MemoryStore, MemoryTable, MemoryScope, Scoped};
 use sindra::operator::{UnaryOperator, BinaryOperator};
-use sindra::value::Coerce;
+use sindra::value::{Coerce, Cast, Extract};
+
+use psk_std::complex::Complex;
+use psk_std::format::{format, Format};
+use psk_std::stdlib;
+use psk_std::set;
 
 use ast::*;
+use PType;
 use Symbol;
-use symbol::FunctionBody;
-use value::{Value, ValueSet, SetInterval};
+use symbol::{FunctionBody, Intrinsic};
+use value::{Value, ValueSet, SetInterval, ValueStruct, ValueFunction};
 use visitor::State;
+use visitor::state::Frame;
+use error::PiskeError;
+
+type Result = ::std::result::Result<Value, PiskeError>;
+
+/// Step along the path from a variable to a location within it.
+enum PathStep {
+    /// Struct field
+    Field(Identifier),
+    /// Array element
+    Index(usize),
+}
+
+/// Returns true if `value` is matched by a match arm pattern.
+fn pattern_matches(pattern: &Pattern, value: &Value) -> bool {
+    // pattern literals are promoted to the type of the matched value
+    let ty = Some(PType::from(value));
+    match *pattern {
+        Pattern::Value(ref lit) => *value == Value::from(lit.clone()).coerce(ty),
+        Pattern::Interval { ref start, ref end, end_inclusive } => {
+            let start = Value::from(start.clone()).coerce(ty.clone());
+            let end = Value::from(end.clone()).coerce(ty);
+            *value >= start && if end_inclusive { *value <= end } else { *value < end }
+        },
+        Pattern::Wildcard => true,
+    }
+}
+
+/// Handle the value of one iteration of a loop with label `loop_label`, updating the loop value
+/// `val`. Returns the result of the loop expression if the loop should stop: either the value of a
+/// break exiting this loop, or a return, break or continue to be handled further out.
+fn loop_iteration(result: Value, loop_label: &Option<Node<Identifier>>, val: &mut Value)
+        -> Option<Value> {
+    let targets_loop = |target: &Option<Identifier>| match *target {
+        Some(ref target) => loop_label.as_ref().map_or(false, |label| label.item == *target),
+        None => true,
+    };
+    match result {
+        Value::Break(ref target, ref returned_val) if targets_loop(target) => {
+            Some((**returned_val).clone())
+        },
+        Value::Continue(ref target) if targets_loop(target) => None,
+        Value::Break(..) | Value::Continue(_) | Value::Return(_) => Some(result),
+        result => {
+            *val = result;
+            None
+        }
+    }
+}
+
+/// Evaluate a set, returning its (promoted) elements.
+fn set_elements(set: &Node<Set>, state: &mut State)
+        -> ::std::result::Result<Vec<Value>, PiskeError> {
+    let promote_ty = set.annotation.borrow().promote_type();
+    match set.visit(state)? {
+        Value::Set(value_set) => {
+            Ok(value_set.elements()?.into_iter().map(|elem| elem.coerce(promote_ty.clone()))
+                .collect())
+        },
+        _ => Err(PiskeError::runtime("set specification did not evaluate as a set"))
+    }
+}
+
+/// Evaluate an array index expression.
+fn eval_index(index: &Node<Expression>, state: &mut State)
+        -> ::std::result::Result<usize, PiskeError> {
+    match index.visit(state)? {
+        Value::Int(i) if i >= 0 => Ok(i as usize),
+        Value::Int(i) => Err(PiskeError::runtime(format!("negative array index: {}", i))),
+        _ => Err(PiskeError::runtime("array index did not evaluate as an integer"))
+    }
+}
+
+/// Find the variable at the root of a place expression (e.g. `a` in `a.b[2]`), while recording
+/// the steps from that variable to the location.
+fn place_path<'a>(place: &'a Node<Expression>, state: &mut State, steps: &mut Vec<PathStep>)
+        -> ::std::result::Result<&'a Node<Expression>, PiskeError> {
+    match place.item {
+        Expression::Identifier(_) => Ok(place),
+        Expression::Member { ref object, ref field } => {
+            let root = place_path(object, state, steps)?;
+            steps.push(PathStep::Field(field.item.clone()));
+            Ok(root)
+        },
+        Expression::Index { ref object, ref index } => {
+            let root = place_path(object, state, steps)?;
+            steps.push(PathStep::Index(eval_index(index, state)?));
+            Ok(root)
+        },
+        _ => Err(PiskeError::runtime(format!("invalid assignment target: {}", place.item)))
+    }
+}
+
+/// Returns true if `expr` is a variable, or a field or element of a variable.
+fn is_place(expr: &Node<Expression>) -> bool {
+    match expr.item {
+        Expression::Identifier(_) => true,
+        Expression::Member { ref object, .. } | Expression::Index { ref object, .. } => {
+            is_place(object)
+        },
+        _ => false,
+    }
+}
+
+/// Apply `read` to the value of variable `ident` (as seen from `scope`), without copying it.
+/// Variables set in the current call frame take precedence over those stored in `scope` (or one of
+/// its parents). Returns `None` if the variable does not have a value.
+fn with_variable<F, T>(scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: &Identifier,
+        state: &State, read: F) -> Option<T> where F: FnOnce(&Value) -> T {
+    if let Some(value) = state.frames.last().and_then(|frame| frame.get(scope, ident)) {
+        return Some(read(value));
+    }
+    with_stored_variable(scope, ident, read)
+}
+
+/// Apply `read` to the value of variable `ident` stored in `scope` (or one of its parents).
+fn with_stored_variable<F, T>(scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: &Identifier,
+        read: F) -> Option<T> where F: FnOnce(&Value) -> T {
+    let scope = scope.borrow();
+    match scope.item.memory(ident) {
+        Some(memory) => memory.as_ref().map(read),
+        None => match scope.parent {
+            Some(ref parent) => with_stored_variable(parent, ident, read),
+            None => None,
+        }
+    }
+}
+
+/// Get (a copy of) the value of variable `ident`, as seen from `scope`.
+fn get_variable(scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: &Identifier,
+        state: &State) -> Option<Value> {
+    with_variable(scope, ident, state, |value| value.clone())
+}
+
+/// Read the location specified by a root variable and path, copying only the value at that
+/// location.
+fn read_place(root: &Node<Expression>, steps: &[PathStep], state: &State) -> Result {
+    let ident = match root.item {
+        Expression::Identifier(ref ident) => &ident.item,
+        _ => {
+            return Err(PiskeError::runtime(format!("invalid place expression: {}", root.item)));
+        }
+    };
+    let scope = root.annotation.borrow().scope().ok_or("invalid scope".to_string())?;
+    let read = with_variable(&scope, ident, state, |value| {
+        let mut target = value;
+        for step in steps {
+            target = match (step, target) {
+                (PathStep::Field(field), Value::Struct(value_struct)) => {
+                    value_struct.get(field).ok_or(PiskeError::runtime(format!(
+                        "struct '{}' has no field named '{}'", value_struct.name, field)))?
+                },
+                (PathStep::Field(field), _) => {
+                    return Err(PiskeError::runtime(format!(
+                        "attempt to access field '{}' of non-struct value", field)));
+                },
+                (&PathStep::Index(i), Value::Array(elems)) => {
+                    elems.get(i).ok_or(PiskeError::runtime(format!(
+                        "index {} out of bounds for array of length {}", i, elems.len())))?
+                },
+                (PathStep::Index(_), _) => {
+                    return Err(PiskeError::runtime("attempt to index a non-array value"));
+                },
+            };
+        }
+        Ok(target.clone())
+    });
+    read.unwrap_or_else(|| Err(PiskeError::runtime(format!("uninitialized variable: {}", ident))))
+}
+
+/// Store the value of a variable. Within a function call, the value is stored in the current call
+/// frame, unless the variable is global.
+fn set_variable(scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: Identifier, value: Value,
+        state: &mut State) -> ::std::result::Result<(), PiskeError> {
+    // global variables keep their values when a function call returns
+    let sym: Option<Symbol> = scope.borrow().resolve(&ident);
+    let global = sym.map_or(false, |sym| sym.is_global());
+    match state.frames.last_mut() {
+        Some(frame) if !global => { frame.set(scope, ident, value)?; },
+        _ => { scope.borrow_mut().set(ident, value)?; },
+    }
+    Ok(())
+}
 
-type Result = ::std::result::Result<Value, String>;
+/// Modify the location specified by a root variable and path using the provided function.
+fn modify_place<F>(root: &Node<Expression>, steps: Vec<PathStep>, state: &mut State, modify: F)
+        -> Result
+        where F: FnOnce(&mut Value) -> ::std::result::Result<(), PiskeError> {
+    let ident = match root.item {
+        Expression::Identifier(ref ident) => ident.item.clone(),
+        _ => {
+            return Err(PiskeError::runtime(format!("invalid assignment target: {}", root.item)));
+        }
+    };
+    let scope = root.annotation.borrow().scope().ok_or("invalid scope".to_string())?;
+    let mut value = get_variable(&scope, &ident, state).ok_or(
+        format!("uninitialized variable: {}", ident))?;
+    {
+        let mut target = &mut value;
+        for step in steps {
+            target = match (step, target) {
+                (PathStep::Field(ref field), &mut Value::Struct(ref mut value_struct)) => {
+                    value_struct.fields.iter_mut().find(|&&mut (ref name, _)| name == field)
+                        .map(|&mut (_, ref mut value)| value)
+                        .ok_or(format!("no field named '{}'", field))?
+                },
+                (PathStep::Index(i), &mut Value::Array(ref mut elems)) => {
+                    let len = elems.len();
+                    elems.get_mut(i).ok_or(format!("index {} out of bounds for array of \
+                        length {}", i, len))?
+                },
+                _ => {
+                    return Err(PiskeError::runtime(format!("invalid assignment target in '{}'",
+                        ident)));
+                }
+            };
+        }
+        modify(target)?;
+    }
+    set_variable(&scope, ident, value, state)?;
+    Ok(Value::Empty)
+}
+
+/// Evaluate a call to an intrinsic function.
+fn eval_intrinsic(intrinsic: Intrinsic, args: &[Node<Expression>], state: &mut State) -> Result {
+    match intrinsic {
+        Intrinsic::Len => {
+            match args[0].visit(state)? {
+                Value::Array(ref elems) => Ok(Value::Int(elems.len() as i64)),
+                _ => Err(PiskeError::runtime("len argument did not evaluate as an array"))
+            }
+        },
+        Intrinsic::Push => {
+            let value = args[1].visit(state)?.coerce(args[1].annotation.borrow().promote_type());
+            let mut steps = vec![];
+            let root = place_path(&args[0], state, &mut steps)?;
+            modify_place(root, steps, state, |target| {
+                match *target {
+                    Value::Array(ref mut elems) => {
+                        elems.push(value);
+                        Ok(())
+                    },
+                    _ => Err(PiskeError::runtime("push argument did not evaluate as an array"))
+                }
+            })
+        },
+        Intrinsic::Str => Ok(Value::String(args[0].visit(state)?.to_string())),
+        Intrinsic::Format => {
+            let fmt: String = args[0].visit(state)?.extract()?;
+            let mut values: Vec<Box<dyn Format>> = vec![];
+            for arg in args[1..].iter() {
+                values.push(match arg.visit(state)? {
+                    Value::String(s) => Box::new(s),
+                    Value::Float(f) => Box::new(f),
+                    Value::Int(i) => Box::new(i),
+                    Value::Boolean(b) => Box::new(b),
+                    Value::Complex(re, im) => Box::new(Complex::new(re, im)),
+                    value => {
+                        return Err(PiskeError::runtime(format!(
+                            "unable to format value of type '{}'", PType::from(&value))));
+                    }
+                });
+            }
+            let values: Vec<&dyn Format> = values.iter().map(|value| &**value).collect();
+            Ok(Value::String(format(&fmt, &values)?))
+        }
+    }
+}
+
+/// Call a function (or closure) with body `body`, using the provided (already evaluated) argument
+/// values. Captured variables and parameters are set in a new call frame.
+fn call_function(name: &Identifier, params: &[Identifier], body: &Node<Block>,
+        captured: &[(Identifier, Value)], args: Vec<Value>, state: &mut State) -> Result {
+    let fn_scope = body.annotation.borrow().scope().ok_or(
+        format!("missing function scope for function '{}'", name))?;
+
+    if state.frames.len() >= state.max_call_depth {
+        return Err(PiskeError::runtime(format!(
+            "maximum call depth ({}) exceeded when calling function '{}'",
+            state.max_call_depth, name)));
+    }
+    // start a new call frame
+    state.frames.push(Frame::default());
+
+    // establish captured variables, then arguments as parameters, in new scope
+    for &(ref ident, ref value) in captured.iter() {
+        set_variable(&fn_scope, ident.clone(), value.clone(), state)?;
+    }
+    for (pname, arg) in params.iter().zip(args.into_iter()) {
+        set_variable(&fn_scope, pname.clone(), arg, state)?;
+    }
+
+    let prev_scope = Rc::clone(&state.scope);
+    state.scope = Rc::clone(&fn_scope);
+    let result = body.visit(state).map_err(|e| {
+        // errors in the functions of imported files are located in the imported file
+        match state.modules.iter().find(|module| module.defines(body)) {
+            Some(module) => e.in_source(Some(&module.source)),
+            None => e,
+        }
+    });
+
+    // reset scope, and discard the call frame
+    state.scope = Rc::clone(&prev_scope);
+    state.frames.pop();
+
+    // handle possible return values (by unwrapping them)
+    let val = match result? {
+        Value::Return(returned_val) => {
+            *returned_val
+        },
+        val => val,
+    };
+    Ok(val.coerce(body.annotation.borrow().promote_type()))
+}
 
 /// Trait for evaluation visitor; implemented for all abstract syntax tree nodes.
 pub trait EvaluateVisitor {
@@ -26,6 +346,12 @@ pub trait EvaluateVisitor {
     fn visit(&self, &mut State) -> Result;
 }
 
+/// Evaluation of nodes with a position in the source code, which is where runtime errors raised
+/// while evaluating them are located.
+trait Evaluate {
+    fn evaluate(&self, state: &mut State) -> Result;
+}
+
 impl EvaluateVisitor for Node<Program> {
     fn visit(&self, state: &mut State) -> Result {
         self.item.0.visit(state)
@@ -37,9 +363,11 @@ impl EvaluateVisitor for Node<Block> {
         let mut last_result: Value = Value::Empty;
         for statement in self.item.0.iter() {
             last_result = statement.visit(state)?;
-            // if we have a return / break value, short-circuit this block and return it
+            // if we have a return / break / continue value, short-circuit this block and return it
             match last_result {
-                Value::Return(_) | Value::Break(_) => { return Ok(last_result); }
+                Value::Return(_) | Value::Break(..) | Value::Continue(_) => {
+                    return Ok(last_result);
+                }
                 _ => {}
             }
         }
@@ -49,39 +377,99 @@ impl EvaluateVisitor for Node<Block> {
 
 impl EvaluateVisitor for Node<Statement> {
     fn visit(&self, state: &mut State) -> Result {
+        self.evaluate(state).map_err(|e| e.located(self.span()))
+    }
+}
+
+impl Evaluate for Node<Statement> {
+    fn evaluate(&self, state: &mut State) -> Result {
         match (&self.item, &self.annotation) {
-            (&Statement::Declare(ref ident, ref expr), &ref annotation) => {
+            (&Statement::Declare(ref ident, _, ref expr), &ref annotation) => {
                 match annotation.borrow().scope() {
                     Some(ref scope) => {
-                        let value = expr.visit(state)?;
-                        scope.borrow_mut().set(ident.item.clone(), value.clone())?;
+                        let value = expr.visit(state)?.coerce(
+                            expr.annotation.borrow().promote_type());
+                        set_variable(scope, ident.item.clone(), value.clone(), state)?;
                         Ok(value)
                     },
-                   None => Err("no associated scope in declaration statement".to_string())
+                   None => Err(PiskeError::runtime("no associated scope in declaration statement"))
                 }
             },
             (&Statement::Assign(ref ident, ref expr), &ref annotation) => {
                 match annotation.borrow().scope() {
                     Some(ref scope) => {
-                        let value = expr.visit(state)?;
-                        scope.borrow_mut().set(ident.item.clone(), value.clone())?;
+                        let value = expr.visit(state)?.coerce(
+                            expr.annotation.borrow().promote_type());
+                        set_variable(scope, ident.item.clone(), value.clone(), state)?;
                         Ok(value)
                     },
-                    None => Err("no associated scope in assignment statement".to_string())
+                    None => Err(PiskeError::runtime("no associated scope in assignment statement"))
                 }
             },
+            (&Statement::SetGlobal(ref ident, ref expr), _) => {
+                let value = expr.visit(state)?.coerce(expr.annotation.borrow().promote_type());
+                let global = Rc::clone(&state.global);
+                set_variable(&global, ident.item.clone(), value.clone(), state)?;
+                Ok(value)
+            },
+            (&Statement::Destructure { declare, ref names, ref value }, &ref annotation) => {
+                let scope = annotation.borrow().scope().ok_or(
+                    "no associated scope in destructuring statement".to_string())?;
+                let value = value.visit(state)?;
+                let elems = match value {
+                    Value::Tuple(ref elems) if elems.len() == names.len() => elems.clone(),
+                    _ => {
+                        return Err(PiskeError::runtime(format!("unable to destructure value: {}",
+                            value)));
+                    }
+                };
+                for (name, elem) in names.iter().zip(elems) {
+                    let name = match *name {
+                        Some(ref name) => name.item.clone(),
+                        None => { continue; }
+                    };
+                    let elem = if declare {
+                        elem
+                    } else {
+                        // promote the element to the type of the existing variable
+                        let var_ty = match scope.borrow().resolve(&name) {
+                            Some(Symbol::Variable { ty, .. }) => ty,
+                            _ => None
+                        };
+                        elem.coerce(var_ty)
+                    };
+                    set_variable(&scope, name, elem, state)?;
+                }
+                Ok(value)
+            },
+            (&Statement::IndexAssign { ref array, ref index, ref value }, _) => {
+                let value = value.visit(state)?.coerce(value.annotation.borrow().promote_type());
+                let mut steps = vec![];
+                let root = place_path(array, state, &mut steps)?;
+                steps.push(PathStep::Index(eval_index(index, state)?));
+                modify_place(root, steps, state, |target| {
+                    *target = value;
+                    Ok(())
+                })
+            },
             (&Statement::Expression(ref expr), _) => {
                 expr.visit(state)
             },
-            (&Statement::FnDefine { .. }, _) => {
-                // nothing to evaluate for function definitions
+            (&Statement::FnDefine { .. }, _) | (&Statement::StructDefine { .. }, _)
+                    | (&Statement::Import(_), _) => {
+                // nothing to evaluate for function or struct definitions, or for imports (imported
+                // files only contain definitions)
                 Ok(Value::Empty)
             },
             (&Statement::Return(ref expr), _) => {
                 Ok(Value::Return(Box::new(expr.visit(state)?)))
             },
-            (&Statement::Break(ref expr), _) => {
-                Ok(Value::Break(Box::new(expr.visit(state)?)))
+            (&Statement::Break(ref label, ref expr), _) => {
+                Ok(Value::Break(label.as_ref().map(|label| label.item.clone()),
+                    Box::new(expr.visit(state)?)))
+            },
+            (&Statement::Continue(ref label), _) => {
+                Ok(Value::Continue(label.as_ref().map(|label| label.item.clone())))
             },
             (&Statement::Print(ref exprs), _) => {
                 for expr in exprs {
@@ -97,6 +485,12 @@ impl EvaluateVisitor for Node<Statement> {
 
 impl EvaluateVisitor for Node<Expression> {
     fn visit(&self, state: &mut State) -> Result {
+        self.evaluate(state).map_err(|e| e.located(self.span()))
+    }
+}
+
+impl Evaluate for Node<Expression> {
+    fn evaluate(&self, state: &mut State) -> Result {
         match (&self.item, &self.annotation) {
             (&Expression::Literal(ref literal), _) => {
                 Ok(Value::from(literal.item.clone()))
@@ -105,93 +499,221 @@ impl EvaluateVisitor for Node<Expression> {
                 let ident = &ident.item;
                 match annotation.borrow().scope() {
                     Some(ref scope) => {
-                        match scope.borrow().get(&ident) {
-                            Some(ref value) => Ok(value.clone()),
-                            None => Err(format!("uninitialized variable: {}", ident))
+                        if let Some(value) = get_variable(scope, ident, state) {
+                            return Ok(value);
+                        }
+                        // named functions can be used as values
+                        match scope.borrow().resolve(&ident) {
+                            Some(Symbol::Function { body: FunctionBody::Ast(body), params,
+                                    .. }) => {
+                                Ok(Value::Function(Rc::new(ValueFunction {
+                                    name: Some(ident.clone()),
+                                    ty: annotation.borrow().ty().ok_or(format!(
+                                        "missing type for function '{}'", ident))?,
+                                    params: params.iter().map(|p| p.item.name.item.clone())
+                                        .collect(),
+                                    body: body,
+                                    captured: vec![],
+                                })))
+                            },
+                            Some(Symbol::Constant { value, .. }) => Ok(Value::from(value)),
+                            _ => Err(PiskeError::runtime(format!("uninitialized variable: {}",
+                                ident)))
                         }
                     },
-                    None => Err("invalid scope".to_string())
+                    None => Err(PiskeError::runtime("invalid scope"))
                 }
             },
             (&Expression::Infix { ref op, ref left, ref right },
                     &ref annotation) => {
                 let lval = left.visit(state)?;
+                // short-circuit logical operations; right operand is only evaluated if needed
+                match (*op, &lval) {
+                    (InfixOp::And, &Value::Boolean(false))
+                            | (InfixOp::Or, &Value::Boolean(true)) => {
+                        return Ok(lval.clone());
+                    },
+                    _ => {}
+                }
                 let rval = right.visit(state)?;
 
-                op.op(
+                Ok(op.op(
                     annotation.borrow().ty().unwrap(),
                     &lval.coerce(left.annotation.borrow().promote_type()),
                     &rval.coerce(right.annotation.borrow().promote_type())
-                )
+                )?)
             },
             (&Expression::Prefix { ref op, ref right }, &ref annotation) => {
                 let rval = right.visit(state)?;
 
-                op.op(
+                Ok(op.op(
                     annotation.borrow().ty().unwrap(),
                     &rval.coerce(right.annotation.borrow().promote_type())
-                )
+                )?)
             },
             (&Expression::Postfix { ref op, ref left }, &ref annotation) => {
                 let lval = left.visit(state)?;
 
-                op.op(
+                Ok(op.op(
                     annotation.borrow().ty().unwrap(),
                     &lval.coerce(left.annotation.borrow().promote_type())
-                )
+                )?)
+            },
+            (&Expression::Cast { ref expr, .. }, &ref annotation) => {
+                let value = expr.visit(state)?.coerce(expr.annotation.borrow().promote_type());
+                Ok(value.cast(annotation.borrow().ty().unwrap()))
             },
             (&Expression::Block(ref block), _) => {
                 block.visit(state)
             }
-            (&Expression::FnCall { ref name, ref args, .. } , &ref annotation) => {
-                let mut evaluated_args = vec![];
-                for arg in args {
-                    evaluated_args.push(arg.visit(state)?);
-                }
+            (&Expression::StructLiteral { ref name, ref fields }, &ref annotation) => {
+                let scope = annotation.borrow().scope().ok_or(format!(
+                    "invalid scope when constructing struct '{}'", name.item))?;
+                let sym: Option<Symbol> = scope.borrow().resolve(&name.item);
+                let sym = sym.ok_or(format!("symbol not found: '{}'", name.item))?
+                    .select_overload(annotation.borrow().overload())?;
+                let struct_fields = match sym {
+                    Symbol::Struct { fields, .. } => fields,
+                    _ => {
+                        return Err(PiskeError::runtime(format!("symbol '{}' is not a struct",
+                            name.item)));
+                    }
+                };
 
+                // evaluate field expressions in the order they were written
+                let mut evaluated_fields = vec![];
+                for &(ref field_name, ref expr) in fields.iter() {
+                    let value = expr.visit(state)?.coerce(expr.annotation.borrow().promote_type());
+                    evaluated_fields.push((field_name.item.clone(), value));
+                }
+                // store fields in the order of the struct definition
+                let mut values = vec![];
+                for field in struct_fields.iter() {
+                    let field_name = &field.item.name.item;
+                    let index = evaluated_fields.iter().position(|&(ref f, _)| f == field_name)
+                        .ok_or(format!("missing field '{}' for struct '{}'", field_name,
+                            name.item))?;
+                    values.push(evaluated_fields.swap_remove(index));
+                }
+                Ok(Value::Struct(Box::new(ValueStruct {
+                    name: name.item.clone(),
+                    fields: values,
+                })))
+            },
+            (&Expression::Member { ref object, ref field }, _) => {
+                match object.visit(state)? {
+                    Value::Struct(ref value_struct) => {
+                        value_struct.get(&field.item).cloned().ok_or(PiskeError::runtime(format!(
+                            "struct '{}' has no field named '{}'", value_struct.name, field.item)))
+                    },
+                    _ => Err(PiskeError::runtime(format!(
+                        "attempt to access field '{}' of non-struct value", field.item)))
+                }
+            },
+            (&Expression::FnCall { ref name, ref args, .. } , &ref annotation) => {
                 let scope = annotation.borrow().scope().ok_or(format!(
                     "invalid scope when calling function \
                     '{}'", name.item))?;
 
-                let sym: Symbol = scope.borrow().resolve(&name.item).ok_or(format!(
-                    "symbol not found: '{}'", name.item))?;
+                let sym: Option<Symbol> = scope.borrow().resolve(&name.item);
+                let sym = sym.ok_or(format!("symbol not found: '{}'", name.item))?
+                    .select_overload(annotation.borrow().overload())?;
+
+                if let Symbol::Function { body: FunctionBody::Intrinsic(intrinsic), .. } = sym {
+                    return eval_intrinsic(intrinsic, args, state);
+                }
+
+                let mut evaluated_args = vec![];
+                for arg in args {
+                    evaluated_args.push(arg.visit(state)?
+                        .coerce(arg.annotation.borrow().promote_type()));
+                }
 
                 match sym {
                     Symbol::Function { ref name, body: FunctionBody::Ast(ref body),
                             ref params, .. } => {
-                        let fn_scope = body.annotation.borrow().scope().ok_or(
-                            format!("missing function scope for function \
-                                '{}'", name)
-                        )?;
-
-                        // establish arguments as parameters in new scope
-                        for (i, arg) in evaluated_args.iter().enumerate() {
-                            let param = &params[i];
-                            let pname = param.item.name.item.clone();
-                            fn_scope.borrow_mut().set(pname.clone(), arg.clone())?;
-                        }
-
-                        let prev_scope = Rc::clone(&state.scope);
-                        state.scope = fn_scope;
-                        // evaluate body, and handle possible return values (by unwrapping them)
-                        let val = match body.visit(state)? {
-                            Value::Return(returned_val) => {
-                                *returned_val
+                        let params: Vec<Identifier> = params.iter()
+                            .map(|p| p.item.name.item.clone()).collect();
+                        call_function(name, &params, body, &[], evaluated_args, state)
+                    },
+                    Symbol::Variable { .. } => {
+                        let value = get_variable(&scope, &name.item, state);
+                        match value {
+                            Some(Value::Function(ref func)) => {
+                                call_function(&name.item, &func.params, &func.body,
+                                    &func.captured, evaluated_args, state)
                             },
-                            val => val,
-                        };
-
-                        // reset scope
-                        state.scope = Rc::clone(&prev_scope);
-                        // return result
-                        Ok(val)
+                            _ => Err(PiskeError::runtime(format!(
+                                "variable '{}' does not hold a function", name.item)))
+                        }
                     },
                     Symbol::Function { body: FunctionBody::External(ext_func_id), .. } => {
                         state.std_funcs.call(&mut state.std_env, ext_func_id, evaluated_args)
                     },
-                    _ => Err(format!("unable to call symbol '{}' as function", name.item))
+                    _ => Err(PiskeError::runtime(format!(
+                        "unable to call symbol '{}' as function", name.item)))
                 }
             }
+            (&Expression::Closure { ref params, ref body, .. }, &ref annotation) => {
+                let scope = annotation.borrow().scope().ok_or(
+                    "invalid scope when creating closure".to_string())?;
+                // captured variables are copied when the closure is created
+                let mut captured = vec![];
+                for ident in annotation.borrow().captures() {
+                    let value = get_variable(&scope, ident, state).ok_or(format!(
+                        "uninitialized variable: {}", ident))?;
+                    captured.push((ident.clone(), value));
+                }
+                Ok(Value::Function(Rc::new(ValueFunction {
+                    name: None,
+                    ty: annotation.borrow().ty().ok_or(
+                        "missing type for closure".to_string())?,
+                    params: params.iter().map(|p| p.item.name.item.clone()).collect(),
+                    body: body.clone(),
+                    captured: captured,
+                })))
+            },
+            (&Expression::Array(ref elems), _) => {
+                let mut values = vec![];
+                for elem in elems {
+                    values.push(elem.visit(state)?.coerce(elem.annotation.borrow().promote_type()));
+                }
+                Ok(Value::Array(values))
+            },
+            (&Expression::Tuple(ref elems), _) => {
+                let mut values = vec![];
+                for elem in elems {
+                    values.push(elem.visit(state)?.coerce(elem.annotation.borrow().promote_type()));
+                }
+                Ok(Value::Tuple(values))
+            },
+            (&Expression::ArrayRepeat { ref value, ref count }, _) => {
+                let value = value.visit(state)?;
+                match count.visit(state)? {
+                    Value::Int(n) if n >= 0 => Ok(Value::Array(vec![value; n as usize])),
+                    Value::Int(n) => {
+                        Err(PiskeError::runtime(format!("negative array length: {}", n)))
+                    },
+                    _ => Err(PiskeError::runtime("array length did not evaluate as an integer"))
+                }
+            },
+            (&Expression::Index { ref object, ref index }, _) => {
+                if is_place(object) {
+                    // elements of variables are read in place, without copying the whole array
+                    let mut steps = vec![];
+                    let root = place_path(object, state, &mut steps)?;
+                    steps.push(PathStep::Index(eval_index(index, state)?));
+                    return read_place(root, &steps, state);
+                }
+                let elems = match object.visit(state)? {
+                    Value::Array(elems) => elems,
+                    _ => { return Err(PiskeError::runtime("attempt to index a non-array value")); }
+                };
+                let i = eval_index(index, state)?;
+                let len = elems.len();
+                elems.into_iter().nth(i).ok_or(PiskeError::runtime(format!(
+                    "index {} out of bounds for array of length {}", i, len)))
+            },
             (&Expression::IfElse { ref cond, ref if_block, ref else_block }, _) => {
                 match cond.visit(state)? {
                     Value::Boolean(b) => {
@@ -206,38 +728,98 @@ impl EvaluateVisitor for Node<Expression> {
                             }
                         }
                     },
-                    _ => Err(format!("conditional expression expected to be boolean"))
+                    _ => Err(PiskeError::runtime("conditional expression expected to be boolean"))
                 }
             }
-            (&Expression::Loop { ref variant, ref set, ref body }, _) => {
+            (&Expression::Match { ref value, ref arms }, _) => {
+                let value = value.visit(state)?;
+                for arm in arms.iter() {
+                    if pattern_matches(&arm.pattern, &value) {
+                        return Ok(arm.body.visit(state)?
+                            .coerce(arm.body.annotation.borrow().promote_type()));
+                    }
+                }
+                Err(PiskeError::runtime(format!("no match arm found for value '{}'", value)))
+            },
+            (&Expression::Loop { ref label, ref variant, ref set, ref body }, _) => {
                 let value_set = match set.visit(state)? {
                     Value::Set(value_set) => value_set,
-                    _ => { return Err("loop specification did not evaluate as a set".to_string()); }
+                    _ => {
+                        return Err(PiskeError::runtime(
+                            "loop specification did not evaluate as a set"));
+                    }
                 };
                 let mut val = Value::Empty;
                 for elem in value_set.iter()? {
+                    let elem = elem?;
                     if let Some(scope) = body.annotation.borrow().scope() {
                         match *variant {
                             Some(ref var) => {
-                                scope.borrow_mut().set(var.item.clone(), elem.clone())?;
+                                set_variable(&scope, var.item.clone(), elem.clone(), state)?;
                             },
                             None => {}
                         }
                     } else {
-                        return Err(
-                            "missing scope when trying to evaluate loop".to_string());
+                        return Err(PiskeError::runtime(
+                            "missing scope when trying to evaluate loop"));
                     }
-                    match body.visit(state)? {
-                        Value::Break(returned_val) => {
-                            val = *returned_val;
-                            break;
-                        },
-                        v => {
-                            val = v;
+                    let result = body.visit(state)?;
+                    if let Some(result) = loop_iteration(result, label, &mut val) {
+                        return Ok(result);
+                    }
+                }
+                Ok(val)
+            },
+            (&Expression::WhileLoop { ref label, ref cond, ref body }, _) => {
+                let mut val = Value::Empty;
+                loop {
+                    match cond.visit(state)? {
+                        Value::Boolean(true) => {},
+                        Value::Boolean(false) => { break; },
+                        _ => {
+                            return Err(PiskeError::runtime(
+                                "loop condition expected to be boolean"));
                         }
                     }
+                    let result = body.visit(state)?;
+                    if let Some(result) = loop_iteration(result, label, &mut val) {
+                        return Ok(result);
+                    }
                 }
                 Ok(val)
+            },
+            (&Expression::PixelLoop { ref label, ref row, ref col, ref body }, _) => {
+                let scope = body.annotation.borrow().scope().ok_or(
+                    "missing scope when trying to evaluate loop".to_string())?;
+                let rows = stdlib::get_image_height(&mut state.std_env)?;
+                let cols = stdlib::get_image_width(&mut state.std_env)?;
+                for r in 0..rows {
+                    for c in 0..cols {
+                        set_variable(&scope, row.item.clone(), Value::Int(r), state)?;
+                        set_variable(&scope, col.item.clone(), Value::Int(c), state)?;
+                        let mut val = Value::Empty;
+                        let result = body.visit(state)?;
+                        if let Some(result) = loop_iteration(result, label, &mut val) {
+                            return Ok(match result {
+                                Value::Break(..) | Value::Continue(_) | Value::Return(_) => result,
+                                // breaking out of this loop stops setting pixels
+                                _ => Value::Empty
+                            });
+                        }
+                        // a continue statement leaves the pixel unchanged
+                        match val.coerce(body.annotation.borrow().promote_type()) {
+                            Value::Float(value) => {
+                                stdlib::set_pixel_data(&mut state.std_env, r, c, value)?;
+                            },
+                            Value::Empty => {},
+                            _ => {
+                                return Err(PiskeError::runtime(
+                                    "pixel value did not evaluate as a float"));
+                            }
+                        }
+                    }
+                }
+                Ok(Value::Empty)
             }
         }
     }
@@ -245,14 +827,61 @@ impl EvaluateVisitor for Node<Expression> {
 
 impl EvaluateVisitor for Node<Set> {
     fn visit(&self, state: &mut State) -> Result {
+        self.evaluate(state).map_err(|e| e.located(self.span()))
+    }
+}
+
+impl Evaluate for Node<Set> {
+    fn evaluate(&self, state: &mut State) -> Result {
         match self.item {
             Set::Interval { ref start, ref end, end_inclusive, ref step } => {
                 Ok(Value::Set(Box::new(ValueSet::Interval(SetInterval {
-                    start: start.visit(state)?,
-                    end: end.visit(state)?,
+                    start: start.visit(state)?.coerce(start.annotation.borrow().promote_type()),
+                    end: end.visit(state)?.coerce(end.annotation.borrow().promote_type()),
                     end_inclusive: end_inclusive,
-                    step: step.visit(state)?,
+                    step: step.visit(state)?.coerce(step.annotation.borrow().promote_type()),
                 }))))
+            },
+            Set::Elements(ref elems) => {
+                let mut values = vec![];
+                for elem in elems.iter() {
+                    values.push(elem.visit(state)?.coerce(elem.annotation.borrow().promote_type()));
+                }
+                Ok(Value::Set(Box::new(ValueSet::Elements(set::unique(values)))))
+            },
+            Set::Builder { ref var, ref source, ref cond, .. } => {
+                let mut selected = vec![];
+                for elem in set_elements(source, state)? {
+                    let keep = match *cond {
+                        Some(ref cond) => {
+                            let scope = cond.annotation.borrow().scope().ok_or(
+                                "missing scope when trying to evaluate set-builder".to_string())?;
+                            set_variable(&scope, var.item.clone(), elem.clone(), state)?;
+                            match cond.visit(state)? {
+                                Value::Boolean(b) => b,
+                                _ => {
+                                    return Err(PiskeError::runtime(
+                                        "set-builder condition expected to be boolean"));
+                                }
+                            }
+                        },
+                        None => true
+                    };
+                    if keep {
+                        selected.push(elem);
+                    }
+                }
+                Ok(Value::Set(Box::new(ValueSet::Elements(selected))))
+            },
+            Set::Operation { op, ref left, ref right } => {
+                let left = set_elements(left, state)?;
+                let right = set_elements(right, state)?;
+                let elems = match op {
+                    SetOp::Union => set::union(left, right),
+                    SetOp::Intersection => set::intersection(left, right),
+                    SetOp::Difference => set::difference(left, right),
+                };
+                Ok(Value::Set(Box::new(ValueSet::Elements(elems))))
             }
         }
     }
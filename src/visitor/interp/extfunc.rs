@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use sindra::scope::SymbolStore;
 
 use value::Value;
+use error::PiskeError;
 use Symbol;
 
 use psk_std::Environment;
@@ -29,20 +30,89 @@ pub enum ExtFuncIdent {
     /// re std function
     Re,
     /// im std function
-    Im
+    Im,
+    /// abs std function, for int arguments
+    AbsInt,
+    /// abs std function, for float arguments
+    AbsFloat,
+    /// abs std function, for complex arguments
+    AbsComplex,
+    /// min std function, for int arguments
+    MinInt,
+    /// min std function, for float arguments
+    MinFloat,
+    /// max std function, for int arguments
+    MaxInt,
+    /// max std function, for float arguments
+    MaxFloat,
+    /// clamp std function, for int arguments
+    ClampInt,
+    /// clamp std function, for float arguments
+    ClampFloat,
+    /// lerp std function
+    Lerp,
+    /// floor std function
+    Floor,
+    /// ceil std function
+    Ceil,
+    /// round std function
+    Round,
+    /// sqrt std function, for float arguments
+    SqrtFloat,
+    /// sqrt std function, for complex arguments
+    SqrtComplex,
+    /// exp std function, for float arguments
+    ExpFloat,
+    /// exp std function, for complex arguments
+    ExpComplex,
+    /// log std function, for float arguments
+    LogFloat,
+    /// log std function, for complex arguments
+    LogComplex,
+    /// log10 std function
+    Log10,
+    /// sin std function, for float arguments
+    SinFloat,
+    /// sin std function, for complex arguments
+    SinComplex,
+    /// cos std function, for float arguments
+    CosFloat,
+    /// cos std function, for complex arguments
+    CosComplex,
+    /// tan std function
+    Tan,
+    /// asin std function
+    Asin,
+    /// acos std function
+    Acos,
+    /// atan std function
+    Atan,
+    /// atan2 std function
+    Atan2,
+    /// norm std function
+    Norm,
+    /// arg std function
+    Arg,
+    /// polar std function
+    Polar,
+    /// cis std function
+    Cis,
 }
 
-type FuncResult = Result<Value, String>;
+type FuncResult = Result<Value, PiskeError>;
 type RustFuncInterface = fn(&mut Environment, Vec<Value>) -> FuncResult;
 
 /// Standard function lookup for Piske interpreter.
 pub struct StdFuncTable {
     func_table: HashMap<ExtFuncIdent, Box<RustFuncInterface>>,
+    /// Name of the interpreter function implementing each standard function
+    fn_names: HashMap<ExtFuncIdent, &'static str>,
 }
 impl Default for StdFuncTable {
     fn default() -> StdFuncTable {
         StdFuncTable {
             func_table: HashMap::new(),
+            fn_names: HashMap::new(),
         }
     }
 }
@@ -51,26 +121,97 @@ impl StdFuncTable {
     pub fn call(&self, env: &mut Environment, func: ExtFuncIdent, args: Vec<Value>) -> FuncResult {
         self.func_table[&func](env, args)
     }
+    /// Name of a standard library function in `psk_std::stdlib`. Each interpreter function
+    /// `psk_<name>` wraps the library function `<name>` (e.g. `psk_abs_float` wraps `abs_float`).
+    pub fn lib_name(&self, func: ExtFuncIdent) -> Option<String> {
+        self.fn_names.get(&func).map(|name| name.trim_start_matches("psk_").to_string())
+    }
     /// Create a new standard function table, and register the standard functions in the scope
     pub fn new<Sc: SymbolStore<Symbol>>(scope: &mut Sc) -> StdFuncTable {
         let mut tbl = StdFuncTable::default();
-        add_func!(scope, tbl.func_table, "set_image_dims", ExtFuncIdent::SetImageDims,
+        add_func!(scope, tbl, "set_image_dims", ExtFuncIdent::SetImageDims,
             psk_set_image_dims, [("height", "int"), ("width", "int")], PType::Void);
-        add_func!(scope, tbl.func_table, "get_image_height", ExtFuncIdent::GetImageHeight,
+        add_func!(scope, tbl, "get_image_height", ExtFuncIdent::GetImageHeight,
             psk_get_image_height, [], PType::Int);
-        add_func!(scope, tbl.func_table, "get_image_width", ExtFuncIdent::GetImageWidth,
+        add_func!(scope, tbl, "get_image_width", ExtFuncIdent::GetImageWidth,
             psk_get_image_width, [], PType::Int);
-        add_func!(scope, tbl.func_table, "write", ExtFuncIdent::Write, psk_write,
+        add_func!(scope, tbl, "write", ExtFuncIdent::Write, psk_write,
             [("file", "string")], PType::Void);
-        add_func!(scope, tbl.func_table, "set_pixel_data", ExtFuncIdent::SetPixelData,
+        add_func!(scope, tbl, "set_pixel_data", ExtFuncIdent::SetPixelData,
             psk_set_pixel_data, [("row", "int"), ("col", "int"), ("value", "float")], PType::Void);
-        add_func!(scope, tbl.func_table, "project", ExtFuncIdent::Project, psk_project,
+        add_func!(scope, tbl, "project", ExtFuncIdent::Project, psk_project,
             [("row", "int"), ("col", "int"), ("center", "complex"), ("size", "complex")],
             PType::Complex);
-        add_func!(scope, tbl.func_table, "re", ExtFuncIdent::Re, psk_re, [("c", "complex")],
+        add_func!(scope, tbl, "re", ExtFuncIdent::Re, psk_re, [("c", "complex")],
             PType::Float);
-        add_func!(scope, tbl.func_table, "im", ExtFuncIdent::Im, psk_im, [("c", "complex")],
+        add_func!(scope, tbl, "im", ExtFuncIdent::Im, psk_im, [("c", "complex")],
             PType::Float);
+        add_func!(scope, tbl, "abs", ExtFuncIdent::AbsInt, psk_abs_int,
+            [("x", "int")], PType::Int);
+        add_func!(scope, tbl, "abs", ExtFuncIdent::AbsFloat, psk_abs_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "abs", ExtFuncIdent::AbsComplex, psk_abs_complex,
+            [("z", "complex")], PType::Float);
+        add_func!(scope, tbl, "min", ExtFuncIdent::MinInt, psk_min_int,
+            [("a", "int"), ("b", "int")], PType::Int);
+        add_func!(scope, tbl, "min", ExtFuncIdent::MinFloat, psk_min_float,
+            [("a", "float"), ("b", "float")], PType::Float);
+        add_func!(scope, tbl, "max", ExtFuncIdent::MaxInt, psk_max_int,
+            [("a", "int"), ("b", "int")], PType::Int);
+        add_func!(scope, tbl, "max", ExtFuncIdent::MaxFloat, psk_max_float,
+            [("a", "float"), ("b", "float")], PType::Float);
+        add_func!(scope, tbl, "clamp", ExtFuncIdent::ClampInt, psk_clamp_int,
+            [("x", "int"), ("lo", "int"), ("hi", "int")], PType::Int);
+        add_func!(scope, tbl, "clamp", ExtFuncIdent::ClampFloat, psk_clamp_float,
+            [("x", "float"), ("lo", "float"), ("hi", "float")], PType::Float);
+        add_func!(scope, tbl, "lerp", ExtFuncIdent::Lerp, psk_lerp,
+            [("a", "float"), ("b", "float"), ("t", "float")], PType::Float);
+        add_func!(scope, tbl, "floor", ExtFuncIdent::Floor, psk_floor,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "ceil", ExtFuncIdent::Ceil, psk_ceil,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "round", ExtFuncIdent::Round, psk_round,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "sqrt", ExtFuncIdent::SqrtFloat, psk_sqrt_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "sqrt", ExtFuncIdent::SqrtComplex, psk_sqrt_complex,
+            [("z", "complex")], PType::Complex);
+        add_func!(scope, tbl, "exp", ExtFuncIdent::ExpFloat, psk_exp_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "exp", ExtFuncIdent::ExpComplex, psk_exp_complex,
+            [("z", "complex")], PType::Complex);
+        add_func!(scope, tbl, "log", ExtFuncIdent::LogFloat, psk_log_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "log", ExtFuncIdent::LogComplex, psk_log_complex,
+            [("z", "complex")], PType::Complex);
+        add_func!(scope, tbl, "log10", ExtFuncIdent::Log10, psk_log10,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "sin", ExtFuncIdent::SinFloat, psk_sin_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "sin", ExtFuncIdent::SinComplex, psk_sin_complex,
+            [("z", "complex")], PType::Complex);
+        add_func!(scope, tbl, "cos", ExtFuncIdent::CosFloat, psk_cos_float,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "cos", ExtFuncIdent::CosComplex, psk_cos_complex,
+            [("z", "complex")], PType::Complex);
+        add_func!(scope, tbl, "tan", ExtFuncIdent::Tan, psk_tan,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "asin", ExtFuncIdent::Asin, psk_asin,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "acos", ExtFuncIdent::Acos, psk_acos,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "atan", ExtFuncIdent::Atan, psk_atan,
+            [("x", "float")], PType::Float);
+        add_func!(scope, tbl, "atan2", ExtFuncIdent::Atan2, psk_atan2,
+            [("y", "float"), ("x", "float")], PType::Float);
+        add_func!(scope, tbl, "norm", ExtFuncIdent::Norm, psk_norm,
+            [("z", "complex")], PType::Float);
+        add_func!(scope, tbl, "arg", ExtFuncIdent::Arg, psk_arg,
+            [("z", "complex")], PType::Float);
+        add_func!(scope, tbl, "polar", ExtFuncIdent::Polar, psk_polar,
+            [("z", "complex")], PType::Tuple(vec![PType::Float, PType::Float]));
+        add_func!(scope, tbl, "cis", ExtFuncIdent::Cis, psk_cis,
+            [("theta", "float")], PType::Complex);
         tbl
     }
 }
@@ -84,3 +225,37 @@ add_interpreter_func!(psk_project, project, [i64, i64, Complex, Complex],
     |c| Value::Complex(c.re, c.im));
 add_interpreter_func!(psk_re, re, [Complex], |f| Value::Float(f));
 add_interpreter_func!(psk_im, im, [Complex], |f| Value::Float(f));
+add_interpreter_func!(psk_abs_int, abs_int, [i64], |i| Value::Int(i));
+add_interpreter_func!(psk_abs_float, abs_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_abs_complex, abs_complex, [Complex], |f| Value::Float(f));
+add_interpreter_func!(psk_min_int, min_int, [i64, i64], |i| Value::Int(i));
+add_interpreter_func!(psk_min_float, min_float, [f64, f64], |f| Value::Float(f));
+add_interpreter_func!(psk_max_int, max_int, [i64, i64], |i| Value::Int(i));
+add_interpreter_func!(psk_max_float, max_float, [f64, f64], |f| Value::Float(f));
+add_interpreter_func!(psk_clamp_int, clamp_int, [i64, i64, i64], |i| Value::Int(i));
+add_interpreter_func!(psk_clamp_float, clamp_float, [f64, f64, f64], |f| Value::Float(f));
+add_interpreter_func!(psk_lerp, lerp, [f64, f64, f64], |f| Value::Float(f));
+add_interpreter_func!(psk_floor, floor, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_ceil, ceil, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_round, round, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_sqrt_float, sqrt_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_sqrt_complex, sqrt_complex, [Complex], |c| Value::Complex(c.re, c.im));
+add_interpreter_func!(psk_exp_float, exp_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_exp_complex, exp_complex, [Complex], |c| Value::Complex(c.re, c.im));
+add_interpreter_func!(psk_log_float, log_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_log_complex, log_complex, [Complex], |c| Value::Complex(c.re, c.im));
+add_interpreter_func!(psk_log10, log10, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_sin_float, sin_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_sin_complex, sin_complex, [Complex], |c| Value::Complex(c.re, c.im));
+add_interpreter_func!(psk_cos_float, cos_float, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_cos_complex, cos_complex, [Complex], |c| Value::Complex(c.re, c.im));
+add_interpreter_func!(psk_tan, tan, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_asin, asin, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_acos, acos, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_atan, atan, [f64], |f| Value::Float(f));
+add_interpreter_func!(psk_atan2, atan2, [f64, f64], |f| Value::Float(f));
+add_interpreter_func!(psk_norm, norm, [Complex], |f| Value::Float(f));
+add_interpreter_func!(psk_arg, arg, [Complex], |f| Value::Float(f));
+add_interpreter_func!(psk_polar, polar, [Complex],
+    |(r, theta)| Value::Tuple(vec![Value::Float(r), Value::Float(theta)]));
+add_interpreter_func!(psk_cis, cis, [f64], |c| Value::Complex(c.re, c.im));
@@ -7,13 +7,13 @@ macro_rules! count_args {
 macro_rules! add_interpreter_func {
     ($intrp_fn_name:ident, $fn_name:ident, [$($vtype:ty),*], $result_map:expr) => {
         #[allow(unused_variables)]
-        fn $intrp_fn_name(env: &mut Environment, args: Vec<Value>) -> Result<Value, String> {
+        fn $intrp_fn_name(env: &mut Environment, args: Vec<Value>) -> Result<Value, PiskeError> {
             #[allow(unused_imports)]
             use sindra::value::Extract;
             let arg_count = count_args!($($vtype),*);
             if args.len() != arg_count {
-                return Err(format!("incorrect number of arguments: expected {}, found {}",
-                    arg_count, args.len()));
+                return Err(PiskeError::runtime(format!(
+                    "incorrect number of arguments: expected {}, found {}", arg_count, args.len())));
             }
 
             #[allow(unused_mut)]
@@ -23,17 +23,17 @@ macro_rules! add_interpreter_func {
                 let result: $vtype = args[arg_num].extract()?;
                 arg_num += 1;
                 result
-            }),*).map($result_map)
+            }),*).map($result_map).map_err(PiskeError::from)
         }
     }
 }
 
 #[macro_export]
 macro_rules! add_func {
-    ($scope:expr, $table:expr, $name:expr, $key:expr, $fn:ident,
+    ($scope:expr, $tbl:expr, $name:expr, $key:expr, $fn:ident,
             [$(($pname:expr, $ptype:expr)),*], $ret_ty:expr) => {{
         #[allow(unused_imports)]
-        use ast::Parameter;
+        use ast::{Parameter, TypeSpec};
         #[allow(unused_imports)]
         use sindra::{Typed, Identifier};
         #[allow(unused_imports)]
@@ -46,14 +46,16 @@ macro_rules! add_func {
         $(
             let node = Node::new(Parameter {
                 name: Node::new(Identifier($pname.to_string())),
-                ty: Node::new(Identifier($ptype.to_string()))
+                ty: TypeSpec::Named(Node::new(Identifier($ptype.to_string())))
             });
             node.annotation.borrow_mut().set_type(Some(PType::from($ptype)));
             params.push(node);
         )*
-        $scope.define(ident.clone(), Symbol::ext_function(
+        // library functions with the same name are overloads
+        ::symbol::define_function(&mut *$scope, ident.clone(), Symbol::ext_function(
             ident.clone(), Some($ret_ty), $key, params));
-        $table.insert($key, Box::new($fn));
+        $tbl.fn_names.insert($key, stringify!($fn));
+        $tbl.func_table.insert($key, Box::new($fn));
     }}
 }
 
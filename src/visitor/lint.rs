@@ -0,0 +1,506 @@
+//! Lint abstract syntax tree visitor.
+//!
+//! This module contains the trait and implementation for walking a type-annotated abstract syntax
+//! tree and warning about code which is valid, but likely to be a mistake: unused or shadowed
+//! declarations, implicit numeric promotions, values which are computed but never read, and
+//! unreachable code. This expects that types have already been computed. Lints never fail; they
+//! are reported as warnings on the error output of the visitor state.
+
+use std::collections::HashSet;
+use std::mem;
+
+use sindra::{Node, Identifier, Typed};
+
+use ast::ast::*;
+use ast::{Span, Spanned};
+use PType;
+use visitor::State;
+
+/// Lints checked by the lint visitor, each of which can be enabled or disabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lints {
+    /// Warn about variables which are never read. Closure parameters (whose types are dictated by
+    /// the closure's use), pixel loop coordinates and set-builder variables are not checked.
+    pub unused_variables: bool,
+    /// Warn about functions which are never called or used as a value, other than from their own
+    /// body
+    pub unused_functions: bool,
+    /// Warn about variable declarations which shadow a variable of the same name already in scope
+    pub shadowed_declarations: bool,
+    /// Warn about values implicitly promoted from `int` to `float` or `complex`, or from `float`
+    /// to `complex` (disabled by default, since these promotions are usually intended)
+    pub implicit_promotions: bool,
+    /// Warn about `if` expressions without an `else` block whose value is used
+    pub if_without_else: bool,
+    /// Warn about loops whose body ends with a value which is never read
+    pub unused_loop_values: bool,
+    /// Warn about statements following a `return`, `break` or `continue` statement
+    pub unreachable_code: bool,
+}
+impl Default for Lints {
+    fn default() -> Lints {
+        Lints { implicit_promotions: false, ..Lints::all() }
+    }
+}
+impl Lints {
+    /// All lints enabled.
+    pub fn all() -> Lints {
+        Lints {
+            unused_variables: true,
+            unused_functions: true,
+            shadowed_declarations: true,
+            implicit_promotions: true,
+            if_without_else: true,
+            unused_loop_values: true,
+            unreachable_code: true,
+        }
+    }
+    /// All lints disabled.
+    pub fn none() -> Lints {
+        Lints {
+            unused_variables: false,
+            unused_functions: false,
+            shadowed_declarations: false,
+            implicit_promotions: false,
+            if_without_else: false,
+            unused_loop_values: false,
+            unreachable_code: false,
+        }
+    }
+    /// Names of the lints, as accepted by `set`.
+    pub fn names() -> &'static [&'static str] {
+        &["unused-variables", "unused-functions", "shadowed-declarations", "implicit-promotions",
+            "if-without-else", "unused-loop-values", "unreachable-code"]
+    }
+    /// Enable or disable a lint by name: the name of its field with dashes instead of underscores
+    /// (e.g. `unused-variables`), or `all` for every lint. Fails if no lint has this name.
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let lint = match name {
+            "all" => {
+                *self = if enabled { Lints::all() } else { Lints::none() };
+                return Ok(());
+            },
+            "unused-variables" => &mut self.unused_variables,
+            "unused-functions" => &mut self.unused_functions,
+            "shadowed-declarations" => &mut self.shadowed_declarations,
+            "implicit-promotions" => &mut self.implicit_promotions,
+            "if-without-else" => &mut self.if_without_else,
+            "unused-loop-values" => &mut self.unused_loop_values,
+            "unreachable-code" => &mut self.unreachable_code,
+            _ => { return Err(format!("unknown lint: '{}'", name)); }
+        };
+        *lint = enabled;
+        Ok(())
+    }
+}
+
+/// Declaration of a variable or function, found while linting.
+#[derive(Debug, Clone)]
+struct Declaration {
+    name: Identifier,
+    /// Span of the declaring statement or expression, where warnings about the declaration are
+    /// reported
+    span: Option<Span>,
+    /// Whether the declared variable has been read (always true for variables which are not
+    /// checked for use)
+    used: bool,
+}
+
+/// Declarations seen by the lint visitor while walking a program.
+#[derive(Debug, Default)]
+pub struct LintContext {
+    /// Variables declared in the scopes enclosing the code being visited (innermost last)
+    scopes: Vec<Vec<Declaration>>,
+    /// Functions defined in the program, in order of definition
+    functions: Vec<Declaration>,
+    /// Names of the functions used outside of their own body
+    used_functions: HashSet<Identifier>,
+    /// Function whose body is being visited, if any
+    function: Option<Identifier>,
+}
+
+/// Trait for lint visitor; implemented for all abstract syntax tree nodes.
+pub trait LintVisitor {
+    /// Check this node and its children for lints, logging a warning for each one found.
+    fn visit(&self, state: &mut State);
+}
+
+/// Lint checking for nodes whose value may or may not be used (`used` is true if the value of the
+/// node is read).
+trait CheckLints {
+    fn check_lints(&self, used: bool, state: &mut State);
+}
+
+/// Log a warning about a lint at `span` (or at the node being visited, if `span` is not known).
+fn warn(span: Option<Span>, message: String, state: &mut State) {
+    state.within(span, |state| state.warning(message));
+}
+
+fn enter_scope(state: &mut State) {
+    state.lint_context.scopes.push(vec![]);
+}
+
+/// Leave the innermost scope, warning about the variables declared in it which were never read.
+fn exit_scope(state: &mut State) {
+    let declarations = state.lint_context.scopes.pop().unwrap_or_default();
+    if !state.lints.unused_variables {
+        return;
+    }
+    for declaration in declarations.into_iter().filter(|decl| !decl.used) {
+        warn(declaration.span, format!("unused variable '{}'", declaration.name), state);
+    }
+}
+
+/// Record the declaration of variable `name` in the innermost scope, warning if it shadows a
+/// variable already in scope. Variables which are not `checked` are not reported if unused.
+/// Declarations outside of any scope (in a statement interpreted on its own) are not recorded.
+fn declare(name: &Node<Identifier>, checked: bool, state: &mut State) {
+    let name = &name.item;
+    let span = state.span;
+    let shadows = state.lint_context.scopes.iter().flat_map(|scope| scope.iter())
+        .any(|decl| decl.name == *name);
+    if shadows && state.lints.shadowed_declarations {
+        warn(span, format!("declaration of '{}' shadows a previous declaration", name), state);
+    }
+    if let Some(scope) = state.lint_context.scopes.last_mut() {
+        scope.push(Declaration { name: name.clone(), span: span, used: !checked });
+    }
+}
+
+/// Record a use of `name`: a read of the innermost variable with that name or, if there is no such
+/// variable, a use of the function with that name.
+fn use_name(name: &Identifier, state: &mut State) {
+    let context = &mut state.lint_context;
+    let variable = context.scopes.iter_mut().rev()
+        .filter_map(|scope| scope.iter_mut().rev().find(|decl| decl.name == *name))
+        .next();
+    match variable {
+        Some(variable) => { variable.used = true; },
+        None => {
+            // recursive calls are not uses of a function
+            if context.function.as_ref() != Some(name) {
+                context.used_functions.insert(name.clone());
+            }
+        }
+    }
+}
+
+/// Warn if the type of the node with annotation type `ty` and promotion type `promote_ty` is
+/// implicitly promoted.
+fn check_promotion(ty: Option<PType>, promote_ty: Option<PType>, state: &mut State) {
+    if !state.lints.implicit_promotions {
+        return;
+    }
+    if let (Some(ty), Some(promote_ty)) = (ty, promote_ty) {
+        if ty != promote_ty {
+            state.warning(format!("implicit promotion from '{}' to '{}'", ty, promote_ty));
+        }
+    }
+}
+
+/// Returns true if the last statement of a block is an expression statement with a value.
+fn ends_with_value(block: &Node<Block>) -> bool {
+    match block.item.0.last() {
+        Some(statement) => match statement.item {
+            Statement::Expression(ref expr) => match expr.annotation.borrow().ty() {
+                Some(PType::Void) | None => false,
+                Some(_) => true,
+            },
+            _ => false
+        },
+        None => false
+    }
+}
+
+/// Warn if the value of a loop (the value of the last statement of its body) is not used.
+fn check_loop_value(used: bool, body: &Node<Block>, state: &mut State) {
+    if !used && state.lints.unused_loop_values && ends_with_value(body) {
+        state.warning("value of loop is never read".to_string());
+    }
+}
+
+fn check_statement(statement: &Node<Statement>, used: bool, state: &mut State) {
+    state.within(statement.span(), |state| statement.check_lints(used, state))
+}
+
+fn check_expression(expr: &Node<Expression>, used: bool, state: &mut State) {
+    state.within(expr.span(), |state| expr.check_lints(used, state))
+}
+
+/// Check a block in a scope of its own.
+fn check_block(block: &Node<Block>, used: bool, state: &mut State) {
+    enter_scope(state);
+    block.check_lints(used, state);
+    exit_scope(state);
+}
+
+impl LintVisitor for Node<Program> {
+    fn visit(&self, state: &mut State) {
+        // the value of the last statement of a program is its result
+        check_block(&self.item.0, true, state);
+
+        let functions = mem::replace(&mut state.lint_context.functions, vec![]);
+        if !state.lints.unused_functions {
+            return;
+        }
+        let mut reported = HashSet::new();
+        for function in functions.into_iter() {
+            // overloaded functions are reported once
+            if !state.lint_context.used_functions.contains(&function.name)
+                    && reported.insert(function.name.clone()) {
+                warn(function.span, format!("unused function '{}'", function.name), state);
+            }
+        }
+    }
+}
+
+impl LintVisitor for Node<Block> {
+    fn visit(&self, state: &mut State) {
+        check_block(self, true, state);
+    }
+}
+
+impl CheckLints for Node<Block> {
+    fn check_lints(&self, used: bool, state: &mut State) {
+        let last = self.item.0.len().saturating_sub(1);
+        // keyword of the statement after which the remaining statements are unreachable
+        let mut exited: Option<&str> = None;
+        let mut reported = false;
+        for (i, statement) in self.item.0.iter().enumerate() {
+            if let Some(keyword) = exited {
+                if !reported && state.lints.unreachable_code {
+                    warn(statement.span(), format!("unreachable statement after '{}'", keyword),
+                        state);
+                }
+                reported = true;
+            }
+            check_statement(statement, used && i == last, state);
+            exited = exited.or(match statement.item {
+                Statement::Return(_) => Some("return"),
+                Statement::Break(..) => Some("break"),
+                Statement::Continue(_) => Some("continue"),
+                _ => None
+            });
+        }
+    }
+}
+
+impl LintVisitor for Node<Statement> {
+    fn visit(&self, state: &mut State) {
+        // the value of a statement interpreted on its own is its result
+        check_statement(self, true, state);
+    }
+}
+
+impl CheckLints for Node<Statement> {
+    fn check_lints(&self, used: bool, state: &mut State) {
+        match self.item {
+            Statement::Declare(ref ident, _, ref expr) => {
+                check_expression(expr, true, state);
+                declare(ident, true, state);
+            },
+            Statement::Assign(_, ref expr) | Statement::SetGlobal(_, ref expr)
+                    | Statement::Return(ref expr) | Statement::Break(_, ref expr) => {
+                check_expression(expr, true, state);
+            },
+            Statement::Destructure { declare: declares, ref names, ref value } => {
+                check_expression(value, true, state);
+                if declares {
+                    for name in names.iter().filter_map(|name| name.as_ref()) {
+                        declare(name, true, state);
+                    }
+                }
+            },
+            Statement::IndexAssign { ref array, ref index, ref value } => {
+                check_expression(array, true, state);
+                check_expression(index, true, state);
+                check_expression(value, true, state);
+            },
+            Statement::Expression(ref expr) => {
+                check_expression(expr, used, state);
+            },
+            Statement::FnDefine(FunctionDef { ref name, ref params, ref body, .. }) => {
+                let span = state.span;
+                state.lint_context.functions.push(Declaration {
+                    name: name.item.clone(),
+                    span: span,
+                    used: false,
+                });
+                // functions only see their parameters (and global variables, which are not
+                // checked)
+                let outer_scopes = mem::replace(&mut state.lint_context.scopes, vec![]);
+                let outer_function = mem::replace(&mut state.lint_context.function,
+                    Some(name.item.clone()));
+                enter_scope(state);
+                for param in params.iter() {
+                    declare(&param.item.name, true, state);
+                }
+                // the value of the body is returned
+                body.check_lints(true, state);
+                exit_scope(state);
+                state.lint_context.scopes = outer_scopes;
+                state.lint_context.function = outer_function;
+            },
+            Statement::Print(ref exprs) => {
+                for expr in exprs.iter() {
+                    check_expression(expr, true, state);
+                }
+            },
+            Statement::StructDefine(_) | Statement::Continue(_) | Statement::Import(_) => {}
+        }
+    }
+}
+
+impl LintVisitor for Node<Expression> {
+    fn visit(&self, state: &mut State) {
+        check_expression(self, true, state);
+    }
+}
+
+impl CheckLints for Node<Expression> {
+    fn check_lints(&self, used: bool, state: &mut State) {
+        let (ty, promote_ty) = {
+            let annotation = self.annotation.borrow();
+            (annotation.ty(), annotation.promote_type())
+        };
+        check_promotion(ty, promote_ty, state);
+
+        match self.item {
+            Expression::Literal(_) => {},
+            Expression::Identifier(ref ident) => {
+                use_name(&ident.item, state);
+            },
+            Expression::Infix { ref left, ref right, .. } => {
+                check_expression(left, true, state);
+                check_expression(right, true, state);
+            },
+            Expression::Prefix { right: ref operand, .. }
+                    | Expression::Postfix { left: ref operand, .. }
+                    | Expression::Cast { expr: ref operand, .. }
+                    | Expression::Member { object: ref operand, .. } => {
+                check_expression(operand, true, state);
+            },
+            Expression::Block(ref block) => {
+                check_block(block, used, state);
+            },
+            Expression::StructLiteral { ref fields, .. } => {
+                for &(_, ref expr) in fields.iter() {
+                    check_expression(expr, true, state);
+                }
+            },
+            Expression::Array(ref elems) | Expression::Tuple(ref elems) => {
+                for elem in elems.iter() {
+                    check_expression(elem, true, state);
+                }
+            },
+            Expression::ArrayRepeat { value: ref first, count: ref second }
+                    | Expression::Index { object: ref first, index: ref second } => {
+                check_expression(first, true, state);
+                check_expression(second, true, state);
+            },
+            Expression::Closure { ref params, ref body, .. } => {
+                enter_scope(state);
+                for param in params.iter() {
+                    declare(&param.item.name, false, state);
+                }
+                let (body_ty, body_promote_ty) = {
+                    let annotation = body.annotation.borrow();
+                    (annotation.ty(), annotation.promote_type())
+                };
+                check_promotion(body_ty, body_promote_ty, state);
+                body.check_lints(true, state);
+                exit_scope(state);
+            },
+            Expression::FnCall { ref name, ref args } => {
+                for arg in args.iter() {
+                    check_expression(arg, true, state);
+                }
+                use_name(&name.item, state);
+            },
+            Expression::IfElse { ref cond, ref if_block, ref else_block } => {
+                check_expression(cond, true, state);
+                if used && else_block.is_none() && state.lints.if_without_else {
+                    state.warning("value of 'if' without 'else' is used; it has no value \
+                        when the condition is false".to_string());
+                }
+                check_block(if_block, used, state);
+                if let Some(ref else_block) = *else_block {
+                    check_block(else_block, used, state);
+                }
+            },
+            Expression::Match { ref value, ref arms } => {
+                check_expression(value, true, state);
+                for arm in arms.iter() {
+                    check_expression(&arm.body, used, state);
+                }
+            },
+            Expression::Loop { ref variant, ref set, ref body, .. } => {
+                enter_scope(state);
+                set.visit(state);
+                if let Some(ref variant) = *variant {
+                    declare(variant, true, state);
+                }
+                // the value of the loop is the value of the body in the last iteration
+                body.check_lints(used, state);
+                exit_scope(state);
+                check_loop_value(used, body, state);
+            },
+            Expression::WhileLoop { ref cond, ref body, .. } => {
+                check_expression(cond, true, state);
+                check_block(body, used, state);
+                check_loop_value(used, body, state);
+            },
+            Expression::PixelLoop { ref row, ref col, ref body, .. } => {
+                enter_scope(state);
+                declare(row, false, state);
+                declare(col, false, state);
+                let (body_ty, body_promote_ty) = {
+                    let annotation = body.annotation.borrow();
+                    (annotation.ty(), annotation.promote_type())
+                };
+                check_promotion(body_ty, body_promote_ty, state);
+                // the value of the body is the value of the pixel
+                body.check_lints(true, state);
+                exit_scope(state);
+            }
+        }
+    }
+}
+
+impl LintVisitor for Node<Set> {
+    fn visit(&self, state: &mut State) {
+        state.within(self.span(), |state| {
+            let (ty, promote_ty) = {
+                let annotation = self.annotation.borrow();
+                (annotation.ty(), annotation.promote_type())
+            };
+            check_promotion(ty, promote_ty, state);
+
+            match self.item {
+                Set::Interval { ref start, ref end, ref step, .. } => {
+                    check_expression(start, true, state);
+                    check_expression(end, true, state);
+                    check_expression(step, true, state);
+                },
+                Set::Elements(ref elems) => {
+                    for elem in elems.iter() {
+                        check_expression(elem, true, state);
+                    }
+                },
+                Set::Builder { ref var, ref source, ref cond, .. } => {
+                    source.visit(state);
+                    enter_scope(state);
+                    declare(var, false, state);
+                    if let Some(ref cond) = *cond {
+                        check_expression(cond, true, state);
+                    }
+                    exit_scope(state);
+                },
+                Set::Operation { ref left, ref right, .. } => {
+                    left.visit(state);
+                    right.visit(state);
+                }
+            }
+        })
+    }
+}
@@ -7,6 +7,8 @@ pub mod symbol;
 pub use self::symbol::SymbolDefineVisitor;
 pub mod type_visitor;
 pub use self::type_visitor::TypeComputationVisitor;
+pub mod lint;
+pub use self::lint::LintVisitor;
 pub mod transpile;
 pub use self::transpile::TranspileVisitor;
 
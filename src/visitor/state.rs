@@ -2,16 +2,25 @@
 
 use std::io::{self, Read, Write};
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::f64::consts;
 
 use sindra::Identifier;
 use sindra::log::LogListener;
-use sindra::scope::{MemoryScope, SymbolStore};
+use sindra::scope::{MemoryScope, MemoryTable, SymbolStore};
 
 use Symbol;
+use symbol::Intrinsic;
 use PType;
 use value::Value;
+use ast::{Module, Literal, Span};
+use diagnostic::Source;
+use error::ErrorDetail;
 use visitor::interp::StdFuncTable;
+use visitor::lint::{Lints, LintContext};
 use psk_std::Environment;
 
 /// State carried throughout the tree walker. Contains scope information and logger.
@@ -22,15 +31,57 @@ pub struct State {
     pub global: Rc<RefCell<MemoryScope<Symbol, Value>>>,
     /// Logger
     pub logger: LogListener<String, io::Stdout, io::Stderr>,
+    /// Source code being visited, used to show where logged errors and warnings occur
+    pub source: Option<Arc<Source>>,
+    /// Span of the innermost node being visited which has a known span
+    pub span: Option<Span>,
+    /// Errors logged since they were last collected (with `take_errors`)
+    pub errors: Vec<ErrorDetail>,
+    /// Warnings reported since they were last collected (with `take_warnings`)
+    pub warnings: Vec<ErrorDetail>,
+    /// Lints checked by the lint visitor
+    pub lints: Lints,
+    /// Declarations seen by the lint visitor, used to find unused and shadowed declarations
+    pub lint_context: LintContext,
     /// Standard function table
     pub std_funcs: StdFuncTable,
     /// Standard running environment
     pub std_env: Environment,
     /// Input / output
     pub io: Io,
-    /// Loop depth counter
-    pub loop_depth: usize,
+    /// Loops enclosing the code currently being visited (innermost last)
+    pub loops: Vec<LoopContext>,
+    /// Global variables (declared by `set` statements), in order of declaration
+    pub globals: Vec<Identifier>,
+    /// Whether the code currently being visited is in the body of a function definition
+    pub in_function: bool,
+    /// Stack of active function call frames
+    pub frames: Vec<Frame>,
+    /// Maximum depth of nested function calls during evaluation, after which evaluation fails
+    pub max_call_depth: usize,
+    /// Closures whose bodies are currently being visited (innermost last), used to find the
+    /// variables each closure captures
+    pub closures: Vec<ClosureCaptures>,
+    /// Files loaded by import statements, in the order they were loaded (imported files before
+    /// the files importing them)
+    pub modules: Vec<Rc<Module>>,
 }
+
+/// Default maximum depth of nested function calls during evaluation, deep enough for recursive
+/// subdivision and for recursion over linked structures of a thousand elements.
+///
+/// The evaluator is a recursive tree walker, so each nested call also nests the Rust calls that
+/// visit the function body: around 10KB of Rust stack per call in release builds, and up to 250KB
+/// in debug builds (for bodies with nested loops and conditionals). This depth therefore needs a
+/// larger stack than the 8MB of a program's main thread, so the `glue` interpreter functions
+/// evaluate on a thread with a stack of `EVAL_STACK_SIZE` bytes (see `glue::with_eval_stack`).
+/// Code running `EvaluateVisitor` directly should do the same, or lower `State::max_call_depth`.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// Stack size (in bytes) of threads evaluating programs, large enough for
+/// `DEFAULT_MAX_CALL_DEPTH` nested calls in debug builds. Only the part of the stack which is
+/// actually used is backed by memory.
+pub const EVAL_STACK_SIZE: usize = 512 * 1024 * 1024;
 impl Default for State {
     fn default() -> State {
         let global = Rc::new(RefCell::new(MemoryScope::default()));
@@ -41,10 +92,22 @@ impl Default for State {
             scope: Rc::clone(&global),
             global: global,
             logger: LogListener::new(io::stdout(), io::stderr()),
+            source: None,
+            span: None,
+            errors: vec![],
+            warnings: vec![],
+            lints: Lints::default(),
+            lint_context: LintContext::default(),
             std_funcs: std_funcs,
             std_env: env,
             io: Io::default(),
-            loop_depth: 0,
+            loops: vec![],
+            globals: vec![],
+            in_function: false,
+            frames: vec![],
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            closures: vec![],
+            modules: vec![],
         };
 
         // define builtins in top-level (global) scope
@@ -53,6 +116,68 @@ impl Default for State {
     }
 }
 impl State {
+    /// Log an error at the position of the node being visited.
+    pub fn error(&mut self, message: String) {
+        let rendered = self.diagnostic("error", message.clone());
+        self.logger.error(rendered);
+        self.errors.push(ErrorDetail {
+            message: message,
+            span: self.span,
+            source: self.source.clone(),
+            notes: vec![],
+            logged: true,
+        });
+    }
+    /// Report a warning at the position of the node being visited. Warnings are written straight to
+    /// the error output (instead of through the logger, which would also echo them to stdout).
+    pub fn warning(&mut self, message: String) {
+        let rendered = self.diagnostic("warning", message.clone());
+        // a warning which can't be written doesn't affect the program, so write failures are
+        // ignored
+        let _ = writeln!(self.io.stderr(), "{}", rendered);
+        self.warnings.push(ErrorDetail {
+            message: message,
+            span: self.span,
+            source: self.source.clone(),
+            notes: vec![],
+            logged: true,
+        });
+    }
+    /// Collect the errors logged since the errors were last collected.
+    pub fn take_errors(&mut self) -> Vec<ErrorDetail> {
+        mem::replace(&mut self.errors, vec![])
+    }
+    /// Collect the warnings logged since the warnings were last collected.
+    pub fn take_warnings(&mut self) -> Vec<ErrorDetail> {
+        mem::replace(&mut self.warnings, vec![])
+    }
+    fn diagnostic(&self, level: &str, message: String) -> String {
+        match (self.source.as_ref(), self.span) {
+            (Some(source), Some(span)) => source.render(level, &message, span),
+            _ => format!("{}: {}", level, message),
+        }
+    }
+    /// Visit a node spanning `span` with `visit`, so that errors and warnings logged while visiting
+    /// are reported at the node's position. Nodes without a known span are reported at the
+    /// position of the innermost enclosing node with one.
+    pub fn within<F, R>(&mut self, span: Option<Span>, visit: F) -> R
+            where F: FnOnce(&mut State) -> R {
+        let outer = self.span;
+        if span.is_some() {
+            self.span = span;
+        }
+        let result = visit(self);
+        self.span = outer;
+        result
+    }
+    /// Position in `loops` of the loop targeted by a break or continue statement: the innermost
+    /// loop with the specified label, or the innermost loop if there is no label.
+    pub fn loop_target(&self, label: Option<&Identifier>) -> Option<usize> {
+        match label {
+            Some(label) => self.loops.iter().rposition(|lp| lp.label.as_ref() == Some(label)),
+            None => if self.loops.is_empty() { None } else { Some(self.loops.len() - 1) },
+        }
+    }
     /// Defines the piske built-in types and links standard library.
     pub fn define_builtins(&mut self) {
         let mut sc = self.scope.borrow_mut();
@@ -63,9 +188,110 @@ impl State {
         sc.define(Identifier("bool".to_string()), Symbol::builtin(Identifier("bool".to_string()),
             PType::Boolean));
         sc.define(Identifier("complex".to_string()),
-            Symbol::builtin(Identifier("complex".to_string()), PType::Boolean));
+            Symbol::builtin(Identifier("complex".to_string()), PType::Complex));
         sc.define(Identifier("string".to_string()),
             Symbol::builtin(Identifier("string".to_string()), PType::String));
+        sc.define(Identifier("len".to_string()),
+            Symbol::intrinsic(Identifier("len".to_string()), Intrinsic::Len));
+        sc.define(Identifier("push".to_string()),
+            Symbol::intrinsic(Identifier("push".to_string()), Intrinsic::Push));
+        sc.define(Identifier("str".to_string()),
+            Symbol::intrinsic(Identifier("str".to_string()), Intrinsic::Str));
+        sc.define(Identifier("format".to_string()),
+            Symbol::intrinsic(Identifier("format".to_string()), Intrinsic::Format));
+        sc.define(Identifier("pi".to_string()),
+            Symbol::constant(Identifier("pi".to_string()), Literal::Float(consts::PI)));
+        sc.define(Identifier("tau".to_string()),
+            Symbol::constant(Identifier("tau".to_string()), Literal::Float(2.0 * consts::PI)));
+        sc.define(Identifier("e".to_string()),
+            Symbol::constant(Identifier("e".to_string()), Literal::Float(consts::E)));
+    }
+}
+
+/// Activation record for a function call during evaluation, holding the variables of the call.
+///
+/// The scopes attached to the abstract syntax tree are shared between all invocations of a
+/// function, so the variables written during a call are instead stored in the call's own frame.
+/// They are keyed by the scope defining them (the function body and each block within it has its
+/// own scope), so that variables of the same name in different blocks stay distinct. Global
+/// variables, and variables outside of any call, are stored in the scopes of the tree.
+#[derive(Default)]
+pub struct Frame {
+    values: HashMap<(usize, Identifier), Value>,
+}
+impl Frame {
+    /// Value of variable `ident` (as seen from `scope`) in this call, if it has been set.
+    pub fn get(&self, scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: &Identifier)
+            -> Option<&Value> {
+        let key = defining_scope(scope, ident)?;
+        self.values.get(&(key, ident.clone()))
+    }
+
+    /// Set the value of variable `ident` (as seen from `scope`) in this call.
+    pub fn set(&mut self, scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: Identifier,
+            value: Value) -> Result<(), String> {
+        let key = defining_scope(scope, &ident).ok_or(
+            format!("attempt to set memory for missing symbol: {}", ident))?;
+        self.values.insert((key, ident), value);
+        Ok(())
+    }
+}
+
+/// Identify the scope defining variable `ident`: `scope` or the closest of its parents which does.
+fn defining_scope(scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, ident: &Identifier)
+        -> Option<usize> {
+    let sc = scope.borrow();
+    if sc.item.memory(ident).is_some() {
+        return Some(scope.as_ptr() as usize);
+    }
+    match sc.parent {
+        Some(ref parent) => defining_scope(parent, ident),
+        None => None,
+    }
+}
+
+/// Loop enclosing the code currently being visited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopContext {
+    /// Loop label, if any
+    pub label: Option<Identifier>,
+    /// Whether the loop produces a value (only known after type computation)
+    pub has_value: bool,
+    /// Whether any break or continue statement targets the loop
+    pub targeted: bool,
+}
+impl LoopContext {
+    /// Create a loop context for a loop with optional label
+    pub fn new(label: Option<Identifier>, has_value: bool) -> LoopContext {
+        LoopContext { label: label, has_value: has_value, targeted: false }
+    }
+}
+
+/// Variables captured by a closure, collected while visiting its body.
+pub struct ClosureCaptures {
+    /// Scope in which the closure is created
+    pub outer: Rc<RefCell<MemoryScope<Symbol, Value>>>,
+    /// Scope of the closure parameters, where captured variables are defined
+    pub inner: Rc<RefCell<MemoryScope<Symbol, Value>>>,
+    /// Names declared by the closure (parameters and local variables)
+    pub locals: HashSet<Identifier>,
+    /// Captured variable names, in order of first use
+    pub captured: Vec<Identifier>,
+}
+impl ClosureCaptures {
+    /// Record the use of variable `ident` within the closure body, capturing it if it refers to a
+    /// variable of an enclosing scope.
+    pub fn record_use(&mut self, ident: &Identifier) {
+        if self.locals.contains(ident) || self.captured.contains(ident) {
+            return;
+        }
+        let sym = self.outer.borrow().resolve(ident);
+        // global variables are shared, not captured
+        if let Some(Symbol::Variable { global: false, .. }) = sym {
+            // the closure gets its own copy of the variable
+            self.inner.borrow_mut().define(ident.clone(), Symbol::variable(ident.clone(), None));
+            self.captured.push(ident.clone());
+        }
     }
 }
 
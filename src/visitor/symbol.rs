@@ -3,14 +3,19 @@
 //! This module contains the trait and implementation for walking an abstract syntax tree and
 //! filling in the symbol table. No previous annotation information needs to exist.
 
+use std::collections::HashSet;
+use std::mem;
 use std::rc::Rc;
 
-use sindra::Node;
+use sindra::{Node, Identifier};
 use sindra::scope::{Stack, Scoped, SymbolStore};
 
 use visitor::State;
+use visitor::state::{ClosureCaptures, LoopContext};
 use Symbol;
+use symbol::{FunctionBody, define_function};
 use ast::ast::*;
+use ast::Spanned;
 
 type Result = ::std::result::Result<(), String>;
 
@@ -20,6 +25,12 @@ pub trait SymbolDefineVisitor {
     fn visit(&self, &mut State) -> Result;
 }
 
+/// Symbol definition for nodes with a position in the source code, which is where errors found
+/// while visiting them are reported.
+trait DefineSymbols {
+    fn define_symbols(&self, state: &mut State) -> Result;
+}
+
 
 fn visit_block(block: &Node<Block>, state: &mut State) -> Result {
     state.scope = state.scope.push();
@@ -33,6 +44,55 @@ fn visit_block(block: &Node<Block>, state: &mut State) -> Result {
     Ok(())
 }
 
+/// Verify that a break or continue statement is within a loop (with the specified label, if any).
+fn check_loop_target(statement: &str, label: &Option<Node<Identifier>>, state: &mut State) {
+    let label = label.as_ref().map(|label| &label.item);
+    if state.loop_target(label).is_none() {
+        match label {
+            Some(label) => {
+                state.error(format!("{} to undefined loop label '{}", statement, label));
+            },
+            None => {
+                state.error(format!("{} outside of a loop", statement));
+            }
+        }
+    }
+}
+
+/// Enter a loop with the specified label, while visiting its body.
+fn push_loop(label: &Option<Node<Identifier>>, state: &mut State) {
+    state.loops.push(LoopContext::new(label.as_ref().map(|label| label.item.clone()), false));
+}
+
+/// Record the use of a variable, so that closures being defined capture it if necessary.
+fn record_use(ident: &Identifier, state: &mut State) {
+    for closure in state.closures.iter_mut() {
+        closure.record_use(ident);
+    }
+}
+
+/// Record the declaration of a variable local to the innermost closure being defined (if any).
+fn record_local(ident: &Identifier, state: &mut State) {
+    if let Some(closure) = state.closures.last_mut() {
+        closure.locals.insert(ident.clone());
+    }
+}
+
+/// Whether the functions of `imported` can be added as overloads of function `existing`: they may
+/// replace library functions, but not other user-defined functions with the same parameter types.
+fn can_overload(existing: &Symbol, imported: &Symbol) -> bool {
+    let existing = existing.functions();
+    let imported = imported.functions();
+    !existing.is_empty() && !imported.is_empty() && imported.iter().all(|function| {
+        existing.iter().all(|other| {
+            other == function || other.is_stdlib_func() || match *function {
+                Symbol::Function { ref params, .. } => !other.has_params(params),
+                _ => false
+            }
+        })
+    })
+}
+
 impl SymbolDefineVisitor for Node<Program> {
     fn visit(&self, state: &mut State) -> Result {
         visit_block(&self.item.0, state)?;
@@ -53,13 +113,20 @@ impl SymbolDefineVisitor for Node<Block> {
 
 impl SymbolDefineVisitor for Node<Statement> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.define_symbols(state))
+    }
+}
+
+impl DefineSymbols for Node<Statement> {
+    fn define_symbols(&self, state: &mut State) -> Result {
         self.annotation.borrow_mut().set_scope(Some(Rc::clone(&state.scope)));
         match self.item {
-            Statement::Declare(ref id, ref expr) => {
+            Statement::Declare(ref id, _, ref expr) => {
                 expr.visit(state)?;
                 let id = id.item.clone();
                 state.scope.borrow_mut().define(id.clone(),
                     Symbol::variable(id.clone(), None));
+                record_local(&id, state);
                 Ok(())
             },
             Statement::Assign(ref id, ref expr) => {
@@ -69,15 +136,70 @@ impl SymbolDefineVisitor for Node<Statement> {
                 match sym {
                     Some(_) => {
                         //TODO: check for attempted redefinitions of symbols are different variants
+                        record_use(&id, state);
                         Ok(())
                     },
                     None => {
-                        state.logger.error(format!("symbol '{}' does not exist in scope",
+                        state.error(format!("symbol '{}' does not exist in scope",
                             id));
                         Ok(())
                     }
                 }
             },
+            Statement::SetGlobal(ref id, ref expr) => {
+                expr.visit(state)?;
+                let id = id.item.clone();
+                let existing: Option<Symbol> = state.global.borrow().resolve(&id);
+                if existing.is_some() {
+                    // assignments to existing non-variables are reported during type computation
+                    return Ok(());
+                }
+                // global variables are declared by a set statement at global scope
+                let parent = state.scope.peek();
+                if !state.in_function && parent.is_some()
+                        && Rc::ptr_eq(&parent.unwrap(), &state.global) {
+                    let local = state.scope.borrow().resolve(&id);
+                    if let Some(Symbol::Variable { .. }) = local {
+                        state.error(format!("cannot declare global variable '{}': a local \
+                            variable of the same name is in scope", id));
+                        return Ok(());
+                    }
+                    state.global.borrow_mut().define(id.clone(),
+                        Symbol::global_variable(id.clone(), None));
+                    state.globals.push(id);
+                } else {
+                    state.error(format!("global variable '{}' does not exist (global \
+                        variables are declared with 'set' at global scope)", id));
+                }
+                Ok(())
+            },
+            Statement::Destructure { declare, ref names, ref value } => {
+                value.visit(state)?;
+                for name in names.iter().filter_map(|name| name.as_ref()) {
+                    let id = name.item.clone();
+                    if declare {
+                        state.scope.borrow_mut().define(id.clone(),
+                            Symbol::variable(id.clone(), None));
+                        record_local(&id, state);
+                    } else {
+                        let sym: Option<Symbol> = state.scope.borrow().resolve(&id);
+                        match sym {
+                            Some(_) => { record_use(&id, state); },
+                            None => {
+                                state.error(format!("symbol '{}' does not exist in scope",
+                                    id));
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            },
+            Statement::IndexAssign { ref array, ref index, ref value } => {
+                array.visit(state)?;
+                index.visit(state)?;
+                value.visit(state)?;
+                Ok(())
+            },
             Statement::Expression(ref expr) => {
                 expr.visit(state)
             },
@@ -85,6 +207,11 @@ impl SymbolDefineVisitor for Node<Statement> {
                 // make sure function definition is at top scope
                 let parent = state.scope.peek();
                 if parent.is_some() && Rc::ptr_eq(&parent.unwrap(), &state.global) {
+                    // add function symbol to global scope before visiting the body, so that the
+                    // function can be called from its own body (and from later functions)
+                    define_function(&mut *state.global.borrow_mut(), name.item.clone(),
+                        Symbol::function(name.item.clone(), None, body.clone(),
+                            params.clone()));
                     let prev_scope = Rc::clone(&state.scope);
                     // create new branch of scope tree under global
                     state.scope = state.global.push();
@@ -95,29 +222,91 @@ impl SymbolDefineVisitor for Node<Statement> {
                             Symbol::variable(param_name.clone(), None));
                     }
                     // define symbols in the body of the function
-                    body.visit(state)?;
+                    let prev_in_function = mem::replace(&mut state.in_function, true);
+                    let result = body.visit(state);
+                    state.in_function = prev_in_function;
                     // return to previous top-level scope
                     state.scope = prev_scope;
-                    // add function symbol to scope
-                    state.scope.borrow_mut().define(name.item.clone(),
-                        Symbol::function(name.item.clone(), None, body.clone(),
-                            params.clone()));
+                    result
+                } else {
+                    state.error(format!("function definition '{}' only allowed at \
+                        global scope", name.item));
+                    Ok(())
+                }
+            },
+            Statement::StructDefine(StructDef { ref name, ref fields }) => {
+                // make sure struct definition is at top scope
+                let parent = state.scope.peek();
+                if parent.is_some() && Rc::ptr_eq(&parent.unwrap(), &state.global) {
+                    // verify that field names are unique
+                    for (i, field) in fields.iter().enumerate() {
+                        let field_name = &field.item.name.item;
+                        if fields[..i].iter().any(|prev| prev.item.name.item == *field_name) {
+                            state.error(format!("duplicate field '{}' in struct '{}'",
+                                field_name, name.item));
+                        }
+                    }
+                    // structs are defined in global scope so they are visible within functions
+                    state.global.borrow_mut().define(name.item.clone(),
+                        Symbol::structure(name.item.clone(), fields.clone()));
                     Ok(())
                 } else {
-                    state.logger.error(format!("function definition '{}' only allowed at \
+                    state.error(format!("struct definition '{}' only allowed at \
                         global scope", name.item));
                     Ok(())
                 }
             },
-            Statement::Return(ref expr) | Statement::Break(ref expr) => {
+            Statement::Return(ref expr) => {
+                expr.visit(state)?;
+                Ok(())
+            },
+            Statement::Break(ref label, ref expr) => {
+                check_loop_target("break", label, state);
                 expr.visit(state)?;
                 Ok(())
             },
+            Statement::Continue(ref label) => {
+                check_loop_target("continue", label, state);
+                Ok(())
+            },
             Statement::Print(ref exprs) => {
                 for expr in exprs {
                     expr.visit(state)?;
                 }
                 Ok(())
+            },
+            Statement::Import(ref path) => {
+                // make sure import is at top scope
+                let parent = state.scope.peek();
+                if parent.is_some() && Rc::ptr_eq(&parent.unwrap(), &state.global) {
+                    let module = self.annotation.borrow().module().ok_or(
+                        format!("imported file '{}' has not been loaded", path))?;
+                    // imported functions and structs are defined in global scope, alongside
+                    // those defined in this file
+                    for (name, symbol) in module.exports() {
+                        let existing: Option<Symbol> = state.global.borrow().resolve(&name);
+                        match existing {
+                            Some(ref existing) if *existing == symbol => {},
+                            Some(ref existing) if can_overload(existing, &symbol) => {
+                                for function in symbol.functions() {
+                                    define_function(&mut *state.global.borrow_mut(),
+                                        name.clone(), function);
+                                }
+                            },
+                            Some(_) => {
+                                state.error(format!("symbol '{}' imported from '{}' \
+                                    conflicts with an existing definition", name, path));
+                            },
+                            None => {
+                                state.global.borrow_mut().define(name, symbol);
+                            }
+                        }
+                    }
+                } else {
+                    state.error(format!("import of '{}' only allowed at global scope",
+                        path));
+                }
+                Ok(())
             }
         }
     }
@@ -125,6 +314,12 @@ impl SymbolDefineVisitor for Node<Statement> {
 
 impl SymbolDefineVisitor for Node<Expression> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.define_symbols(state))
+    }
+}
+
+impl DefineSymbols for Node<Expression> {
+    fn define_symbols(&self, state: &mut State) -> Result {
         self.annotation.borrow_mut().set_scope(Some(Rc::clone(&state.scope)));
         match self.item {
             Expression::Literal(_) => {
@@ -133,9 +328,12 @@ impl SymbolDefineVisitor for Node<Expression> {
             Expression::Identifier(ref id) => {
                 let sym: Option<Symbol> = state.scope.borrow().resolve(&id.item);
                 match sym {
-                    Some(_) => Ok(()),
+                    Some(_) => {
+                        record_use(&id.item, state);
+                        Ok(())
+                    },
                     None => {
-                        state.logger.error(format!("symbol '{}' does not exist in scope",
+                        state.error(format!("symbol '{}' does not exist in scope",
                             id.item));
                         Ok(())
                     }
@@ -154,31 +352,133 @@ impl SymbolDefineVisitor for Node<Expression> {
                 left.visit(state)?;
                 Ok(())
             },
+            Expression::Cast { ref expr, .. } => {
+                expr.visit(state)?;
+                Ok(())
+            },
             Expression::Block(ref block) => {
                 visit_block(block, state)
             },
+            Expression::StructLiteral { ref name, ref fields } => {
+                for &(_, ref expr) in fields.iter() {
+                    expr.visit(state)?;
+                }
+                let sym: Option<Symbol> = state.scope.borrow().resolve(&name.item);
+                match sym {
+                    Some(Symbol::Struct { .. }) => Ok(()),
+                    Some(_) => {
+                        state.error(format!("symbol '{}' is not a struct", name.item));
+                        Ok(())
+                    },
+                    None => {
+                        state.error(format!("struct '{}' does not exist in scope",
+                            name.item));
+                        Ok(())
+                    }
+                }
+            },
+            Expression::Member { ref object, .. } => {
+                object.visit(state)
+            },
+            Expression::Array(ref elems) | Expression::Tuple(ref elems) => {
+                for elem in elems.iter() {
+                    elem.visit(state)?;
+                }
+                Ok(())
+            },
+            Expression::ArrayRepeat { ref value, ref count } => {
+                value.visit(state)?;
+                count.visit(state)?;
+                Ok(())
+            },
+            Expression::Index { ref object, ref index } => {
+                object.visit(state)?;
+                index.visit(state)?;
+                Ok(())
+            },
+            Expression::Closure { ref params, ref body, .. } => {
+                let outer = Rc::clone(&state.scope);
+                // closure parameters (and captured variables) are defined in a new scope
+                state.scope = state.scope.push();
+                let mut locals = HashSet::new();
+                for param in params.iter() {
+                    let param_name = param.item.name.item.clone();
+                    state.scope.borrow_mut().define(param_name.clone(),
+                        Symbol::variable(param_name.clone(), None));
+                    locals.insert(param_name);
+                }
+                state.closures.push(ClosureCaptures {
+                    outer: Rc::clone(&outer),
+                    inner: Rc::clone(&state.scope),
+                    locals: locals,
+                    captured: vec![],
+                });
+                // break and continue statements in the closure body can't exit enclosing loops
+                let outer_loops = mem::replace(&mut state.loops, vec![]);
+                let result = body.visit(state);
+                state.loops = outer_loops;
+                result?;
+                let closure = state.closures.pop().ok_or("invalid closure nesting".to_string())?;
+                self.annotation.borrow_mut().set_captures(closure.captured);
+                state.scope = outer;
+                Ok(())
+            },
             Expression::FnCall { name: ref ident, ref args } => {
                 for ref arg in args.iter() {
                     arg.visit(state)?;
                 }
                 let id = ident.item.clone();
-                match state.scope.borrow().resolve(&id) {
+                let sym: Option<Symbol> = state.scope.borrow().resolve(&id);
+                match sym {
+                    Some(Symbol::Function { body: FunctionBody::Intrinsic(intrinsic), .. }) => {
+                        match intrinsic.arity() {
+                            Some(arity) if args.len() != arity => {
+                                state.error(format!("invalid number of arguments for \
+                                    function '{}': expected {}, found {}", id, arity,
+                                    args.len()));
+                            },
+                            None if args.is_empty() => {
+                                state.error(format!("function '{}' requires a format \
+                                    string argument", id));
+                            },
+                            _ => {}
+                        }
+                        Ok(())
+                    },
                     Some(Symbol::Function { ref params, .. }) => {
                         // verify that number of arguments matches number of parameters
                         if args.len() != params.len() {
-                            state.logger.error(format!("invalid number of arguments for function \
+                            state.error(format!("invalid number of arguments for function \
                                 '{}': expected {}, found {}", id, params.len(),
                                 args.len()));
                         }
                         Ok(())
                     },
+                    Some(Symbol::Overloaded { ref functions, .. }) => {
+                        // the definition called is selected during type computation; at least
+                        // one must take this number of arguments
+                        if !functions.iter().any(|function| match *function {
+                            Symbol::Function { ref params, .. } => params.len() == args.len(),
+                            _ => false
+                        }) {
+                            state.error(format!("no definition of function '{}' takes {} \
+                                arguments", id, args.len()));
+                        }
+                        Ok(())
+                    },
+                    Some(Symbol::Variable { .. }) => {
+                        // variables holding functions can be called; their types are checked
+                        // during type computation
+                        record_use(&id, state);
+                        Ok(())
+                    },
                     Some(_) => {
-                        state.logger.error(format!("attempt to call non-function '{}' as function",
+                        state.error(format!("attempt to call non-function '{}' as function",
                             id));
                         Ok(())
                     }
                     None => {
-                        state.logger.error(format!("function '{}' does not exist in scope",
+                        state.error(format!("function '{}' does not exist in scope",
                             id));
                         Ok(())
                     }
@@ -192,7 +492,14 @@ impl SymbolDefineVisitor for Node<Expression> {
                 }
                 Ok(())
             },
-            Expression::Loop { ref variant, ref set, ref body } => {
+            Expression::Match { ref value, ref arms } => {
+                value.visit(state)?;
+                for arm in arms.iter() {
+                    arm.body.visit(state)?;
+                }
+                Ok(())
+            },
+            Expression::Loop { ref label, ref variant, ref set, ref body } => {
                 // create new scope for loop variant and body
                 state.scope = state.scope.push();
                 set.visit(state)?;
@@ -201,11 +508,15 @@ impl SymbolDefineVisitor for Node<Expression> {
                     Some(ref var) => {
                         state.scope.borrow_mut().define(var.item.clone(),
                             Symbol::variable(var.item.clone(), None));
+                        record_local(&var.item, state);
                     },
                     None => {}
                 }
                 // visit body (in this scope)
-                body.visit(state)?;
+                push_loop(label, state);
+                let result = body.visit(state);
+                state.loops.pop();
+                result?;
                 // return to parent
                 match state.scope.pop() {
                     Some(parent_scope) => { state.scope = parent_scope; }
@@ -214,6 +525,33 @@ impl SymbolDefineVisitor for Node<Expression> {
                     }
                 }
                 Ok(())
+            },
+            Expression::WhileLoop { ref label, ref cond, ref body } => {
+                cond.visit(state)?;
+                push_loop(label, state);
+                let result = visit_block(body, state);
+                state.loops.pop();
+                result
+            },
+            Expression::PixelLoop { ref label, ref row, ref col, ref body } => {
+                // create new scope for pixel coordinates and body
+                state.scope = state.scope.push();
+                for var in &[row, col] {
+                    state.scope.borrow_mut().define(var.item.clone(),
+                        Symbol::variable(var.item.clone(), None));
+                    record_local(&var.item, state);
+                }
+                push_loop(label, state);
+                let result = body.visit(state);
+                state.loops.pop();
+                result?;
+                match state.scope.pop() {
+                    Some(parent_scope) => { state.scope = parent_scope; }
+                    None => {
+                        return Err("invalid descoping".to_string());
+                    }
+                }
+                Ok(())
             }
         }
     }
@@ -221,6 +559,12 @@ impl SymbolDefineVisitor for Node<Expression> {
 
 impl SymbolDefineVisitor for Node<Set> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.define_symbols(state))
+    }
+}
+
+impl DefineSymbols for Node<Set> {
+    fn define_symbols(&self, state: &mut State) -> Result {
         self.annotation.borrow_mut().set_scope(Some(Rc::clone(&state.scope)));
         match self.item {
             Set::Interval { ref start, ref end, ref step, .. } => {
@@ -228,6 +572,39 @@ impl SymbolDefineVisitor for Node<Set> {
                 end.visit(state)?;
                 step.visit(state)?;
                 Ok(())
+            },
+            Set::Elements(ref elems) => {
+                for elem in elems.iter() {
+                    elem.visit(state)?;
+                }
+                Ok(())
+            },
+            Set::Builder { ref elem, ref var, ref source, ref cond } => {
+                source.visit(state)?;
+                if elem.item != var.item {
+                    state.error(format!("set-builder element '{}' does not match variable \
+                        '{}'", elem.item, var.item));
+                }
+                // create new scope for the element variable and condition
+                state.scope = state.scope.push();
+                state.scope.borrow_mut().define(var.item.clone(),
+                    Symbol::variable(var.item.clone(), None));
+                record_local(&var.item, state);
+                if let Some(ref cond) = *cond {
+                    cond.visit(state)?;
+                }
+                match state.scope.pop() {
+                    Some(parent_scope) => { state.scope = parent_scope; }
+                    None => {
+                        return Err("invalid descoping".to_string());
+                    }
+                }
+                Ok(())
+            },
+            Set::Operation { ref left, ref right, .. } => {
+                left.visit(state)?;
+                right.visit(state)?;
+                Ok(())
             }
         }
     }
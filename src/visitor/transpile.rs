@@ -4,15 +4,19 @@
 //! and translating it into Rust code.. This implementation expects that the symbol table and type
 //! computation annotations already exist on the tree.
 
+use std::rc::Rc;
+use std::cell::RefCell;
+
 use quote::{Tokens, ToTokens, Ident};
 
 use sindra::{Typed, Identifier, Node};
-use sindra::scope::{SymbolStore, Scoped};
+use sindra::scope::{SymbolStore, Scoped, MemoryScope};
 
-use symbol::Symbol;
+use symbol::{Symbol, FunctionBody, Intrinsic, overload_name};
 use PType;
 use ast::*;
-use visitor::state::State;
+use value::Value;
+use visitor::state::{State, LoopContext};
 
 type Result = ::std::result::Result<Tokens, String>;
 
@@ -25,14 +29,24 @@ pub trait TranspileVisitor {
 impl TranspileVisitor for Node<Program> {
     fn visit(&self, state: &mut State) -> Result {
         let prog = self.item.0.visit(state)?;
-        let pref = preface();
         let nl = nl();
+        let mut qmodules = vec![];
+        for module in state.modules.clone() {
+            let qmodule = module_to_tokens(&module, state)?;
+            qmodules.push(quote! { #qmodule #nl });
+        }
+        let pref = preface();
+        let (qglobals, qinit_globals) = globals_to_tokens(state)?;
         Ok(quote! {
 #pref #nl
 
+#qglobals #nl
+
+#(#qmodules)*
+
 fn run() -> Result<(), String> { #nl
     #![allow(unused_mut, unused_variables)]
-    let mut env = Environment::default(); #nl
+    let ctx = &mut Context { env: Environment::default(), globals: #qinit_globals }; #nl
     #prog
     ; Ok(())
 }
@@ -40,6 +54,69 @@ fn run() -> Result<(), String> { #nl
     }
 }
 
+/// Global variables (declared with `set`) are fields of a `Globals` struct. It is part of the
+/// `Context` passed to every function, along with the library environment. Returns the struct
+/// definitions and the initial value of the globals.
+fn globals_to_tokens(state: &mut State)
+        -> ::std::result::Result<(Tokens, Tokens), String> {
+    let mut qfields = vec![];
+    let mut qinits = vec![];
+    for name in state.globals.iter() {
+        let ty = match state.global.borrow().resolve(name) {
+            Some(Symbol::Variable { ty: Some(ty), .. }) => ty,
+            _ => { return Err(format!("missing type for global variable {}", name)); }
+        };
+        // globals start with a default value, and are set when their declaration is reached
+        let qinit = start_value(ty.clone()).ok_or(format!("global variable '{}' of type '{}' \
+            is not supported in translated code", name, ty))?;
+        let qname = Ident::new(name.0.clone());
+        let qty = rust_type(&ty)?;
+        qfields.push(quote! { pub #qname: #qty });
+        qinits.push(quote! { #qname: #qinit });
+    }
+    Ok((quote! {
+        pub struct Globals { #(#qfields),* }
+        pub struct Context { pub env: Environment, pub globals: Globals }
+    }, quote! { Globals { #(#qinits),* } }))
+}
+
+/// Translate a use of variable `ident`, as resolved in `scope`.
+fn variable_path(ident: &Node<Identifier>, scope: Option<Rc<RefCell<MemoryScope<Symbol, Value>>>>,
+        state: &mut State) -> Result {
+    let qident = ident.visit(state)?;
+    let symbol: Option<Symbol> = scope.and_then(|scope| scope.borrow().resolve(&ident.item));
+    let global = symbol.map_or(false, |symbol| symbol.is_global());
+    Ok(if global { quote! { ctx.globals.#qident } } else { qident })
+}
+
+/// Imported files are translated into Rust modules, from which importing code uses all items
+/// (their functions and structs are public).
+fn module_to_tokens(module: &Module, state: &mut State) -> Result {
+    let mut qitems = vec![];
+    for statement in (module.program.item.0).item.0.iter() {
+        qitems.push(statement.visit(state)?);
+    }
+    let qname = Ident::new(module.name.0.clone());
+    Ok(quote! {
+        mod #qname {
+            use super::*;
+            #(#qitems)*
+        }
+    })
+}
+
+/// Name of the Rust function for one definition of an overloaded function (see
+/// `symbol::overload_name`).
+fn overload_fn_name(name: &str, params: &[Node<Parameter>])
+        -> ::std::result::Result<String, String> {
+    let mut param_tys = vec![];
+    for param in params {
+        param_tys.push(param.annotation.borrow().ty().ok_or(format!("missing type for parameter \
+            '{}' of function {}", param.item.name.item, name))?);
+    }
+    Ok(overload_name(name, &param_tys))
+}
+
 fn raw(s: &str) -> Tokens {
     let mut t = Tokens::new();
     t.append(s);
@@ -48,11 +125,46 @@ fn raw(s: &str) -> Tokens {
 fn nl() -> Tokens {
     raw("\n")
 }
-fn loop_var_name(state: &State) -> Tokens {
-    raw(&format!("loop_return_value_{}", state.loop_depth))
+// loops are named after their nesting depth
+/// Variable holding the value of the latest iteration of a loop with a value, which becomes the
+/// value of the loop once it runs out of elements (or its condition becomes false). Unlike the
+/// value of a break statement, this cannot be passed straight to `break`: the final iteration is
+/// only known after checking for another element (or the condition) again, and an iteration ended
+/// by `continue` keeps the value of the iteration before it.
+fn loop_last_value(depth: usize) -> Tokens {
+    raw(&format!("loop_last_value_{}", depth))
+}
+fn loop_iter_name(depth: usize) -> Tokens {
+    raw(&format!("loop_iter_{}", depth))
 }
-fn outer_loop_var_name(state: &State) -> Tokens {
-    raw(&format!("loop_return_value_{}", state.loop_depth - 1))
+fn loop_label(depth: usize) -> Tokens {
+    raw(&format!("'loop_{}", depth))
+}
+/// Nesting depth of the loop targeted by a break or continue statement.
+fn loop_target(label: &Option<Node<Identifier>>, state: &mut State)
+        -> ::std::result::Result<usize, String> {
+    let depth = state.loop_target(label.as_ref().map(|label| &label.item)).ok_or(match *label {
+        Some(ref label) => format!("undefined loop label '{}", label.item),
+        None => "break or continue outside of a loop".to_string(),
+    })?;
+    state.loops[depth].targeted = true;
+    Ok(depth)
+}
+/// Visit the body of a loop, returning the translated body and the label of the loop (empty if
+/// no break or continue statement targets it).
+fn loop_body(label: &Option<Node<Identifier>>, has_value: bool, body: &Node<Block>,
+        state: &mut State) -> ::std::result::Result<(Tokens, Tokens), String> {
+    let depth = state.loops.len();
+    state.loops.push(LoopContext::new(label.as_ref().map(|label| label.item.clone()), has_value));
+    let qbody = body.visit(state);
+    let targeted = state.loops.pop().map_or(false, |lp| lp.targeted);
+    let qlabel = if targeted {
+        let qlabel = loop_label(depth);
+        quote! { #qlabel: }
+    } else {
+        Tokens::new()
+    };
+    Ok((qbody?, qlabel))
 }
 
 impl TranspileVisitor for Node<Block> {
@@ -70,16 +182,73 @@ impl TranspileVisitor for Node<Block> {
 impl TranspileVisitor for Node<Statement> {
     fn visit(&self, state: &mut State) -> Result {
         match (&self.item, &self.annotation) {
-            (&Statement::Declare(ref ident, ref expr), _) => {
+            (&Statement::Declare(ref ident, _, ref expr), _) => {
                 let rhs = expr.visit(state)?;
                 let lhs = ident.visit(state)?;
                 Ok(quote! { let mut #lhs = #rhs; })
             },
-            (&Statement::Assign(ref ident, ref expr), _) => {
+            (&Statement::Assign(ref ident, ref expr), ref annotation) => {
                 let rhs = expr.visit(state)?;
-                let lhs = ident.visit(state)?;
+                let lhs = variable_path(ident, annotation.borrow().scope(), state)?;
                 Ok(quote! { #lhs = #rhs; })
             },
+            (&Statement::SetGlobal(ref ident, ref expr), _) => {
+                let rhs = expr.visit(state)?;
+                let lhs = ident.visit(state)?;
+                Ok(quote! { ctx.globals.#lhs = #rhs; })
+            },
+            (&Statement::Destructure { declare, ref names, ref value }, ref annotation) => {
+                let qvalue = value.visit(state)?;
+                if declare {
+                    let mut qnames = vec![];
+                    for name in names {
+                        qnames.push(match *name {
+                            Some(ref name) => {
+                                let qname = name.visit(state)?;
+                                quote! { mut #qname }
+                            },
+                            None => raw("_"),
+                        });
+                    }
+                    return Ok(quote! { let (#(#qnames),*) = #qvalue; });
+                }
+                // destructure into temporaries, then assign (promoting if necessary) to the
+                // existing variables
+                let scope = annotation.borrow().scope().ok_or(
+                    "no scope associated with destructuring statement".to_string())?;
+                let elem_tys = match value.annotation.borrow().ty() {
+                    Some(PType::Tuple(elem_tys)) => elem_tys,
+                    _ => { return Err("destructuring a value which is not a tuple".to_string()); }
+                };
+                let mut qtemps = vec![];
+                let mut qassigns = vec![];
+                for (i, (name, elem_ty)) in names.iter().zip(elem_tys).enumerate() {
+                    let name = match *name {
+                        Some(ref name) => name,
+                        None => {
+                            qtemps.push(raw("_"));
+                            continue;
+                        }
+                    };
+                    let qtemp = raw(&format!("destructure_{}", i));
+                    let var_ty = match scope.borrow().resolve(&name.item) {
+                        Some(Symbol::Variable { ty: Some(var_ty), .. }) => var_ty,
+                        _ => { return Err(format!("missing type for variable {}", name.item)); }
+                    };
+                    let promote_ty = if var_ty == elem_ty { None } else { Some(var_ty) };
+                    let qelem = add_cast(&qtemp, Some(elem_ty), promote_ty)?;
+                    let qname = variable_path(name, Some(Rc::clone(&scope)), state)?;
+                    qassigns.push(quote! { #qname = #qelem; });
+                    qtemps.push(qtemp);
+                }
+                Ok(quote! { { let (#(#qtemps),*) = #qvalue; #(#qassigns)* } })
+            },
+            (&Statement::IndexAssign { ref array, ref index, ref value }, _) => {
+                let qarray = place_path(array, state)?;
+                let qindex = index.visit(state)?;
+                let qvalue = value.visit(state)?;
+                Ok(quote! { #qarray[(#qindex) as usize] = #qvalue; })
+            },
             (&Statement::Expression(ref expr), _) => {
                 let qexpr = expr.visit(state)?;
                 // only add a semi-colon to expressions if the return type is void
@@ -88,39 +257,100 @@ impl TranspileVisitor for Node<Statement> {
                     _ => Ok(quote! { #qexpr })
                 }
             },
-            (&Statement::FnDefine(FunctionDef { ref name, ref ret_type, ref params, ref body } ),
-                    _) => {
+            (&Statement::FnDefine(FunctionDef { ref name, ref params, ref body, .. } ),
+                    ref annotation) => {
                 let mut qparams = vec![];
                 for param in params {
                     qparams.push(param.visit(state)?);
                 }
-                let qname = name.visit(state)?;
-                let qret_ty = ret_type.visit(state)?;
+                let scope = annotation.borrow().scope().ok_or(
+                    format!("no scope associated with function {}", name.item))?;
+                let symbol = scope.borrow().resolve(&name.item);
+                // overloaded functions are distinguished by their parameter types
+                let qname = match symbol {
+                    Some(Symbol::Overloaded { .. }) => {
+                        let qident = Ident::new(overload_fn_name(&name.item.0, params)?);
+                        quote! { #qident }
+                    },
+                    _ => name.visit(state)?
+                };
+                let ret_ty = match symbol.as_ref().and_then(|symbol| symbol.overload(params)) {
+                    Some(&Symbol::Function { ret_ty: Some(ref ret_ty), .. }) => ret_ty.clone(),
+                    _ => { return Err(format!("missing return type for function {}",
+                        name.item)); }
+                };
+                let qret_ty = rust_type(&ret_ty)?;
                 let qbody = body.visit(state)?;
+                // functions return a result, so that run-time errors can be propagated
                 Ok(quote! {
-                    fn #qname(#(#qparams),*) -> #qret_ty {
-                        #qbody
+                    pub fn #qname(ctx: &mut Context, #(#qparams),*) -> Result<#qret_ty, String> {
+                        Ok({ #qbody })
+                    }
+                })
+            },
+            (&Statement::StructDefine(StructDef { ref name, ref fields }), _) => {
+                let mut qfields = vec![];
+                for field in fields {
+                    qfields.push(field.visit(state)?);
+                }
+                let qname = name.visit(state)?;
+                // structs are printed in the same format as in the interpreter
+                let struct_name = name.item.0.clone();
+                let mut qshown = vec![];
+                for field in fields {
+                    let field_name = field.item.name.item.0.clone();
+                    let qfield = field.item.name.visit(state)?;
+                    qshown.push(quote! {
+                        (#field_name, &self.#qfield as &dyn psk_std::display::Show)
+                    });
+                }
+                Ok(quote! {
+                    #[derive(Debug, Clone, PartialEq)]
+                    pub struct #qname {
+                        #(#qfields),*
+                    }
+                    impl psk_std::display::Show for #qname {
+                        fn show(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                            psk_std::display::show_struct(#struct_name, &[#(#qshown),*], f)
+                        }
                     }
                 })
             },
             (&Statement::Return(ref expr), _) => {
                 let qexpr = expr.visit(state)?;
-                Ok(quote! { return #qexpr; })
+                Ok(quote! { return Ok(#qexpr); })
             }
-            (&Statement::Break(ref expr), _) => {
+            (&Statement::Break(ref label, ref expr), _) => {
                 let qexpr = expr.visit(state)?;
-                let loop_var_name = outer_loop_var_name(state);
-                Ok(quote! { #loop_var_name = #qexpr; break; })
+                let depth = loop_target(label, state)?;
+                let qlabel = loop_label(depth);
+                if state.loops[depth].has_value {
+                    Ok(quote! { break #qlabel #qexpr; })
+                } else {
+                    Ok(quote! { #qexpr; break #qlabel; })
+                }
+            },
+            (&Statement::Continue(ref label), _) => {
+                let qlabel = loop_label(loop_target(label, state)?);
+                Ok(quote! { continue #qlabel; })
             },
             (&Statement::Print(ref exprs), _) => {
                 let mut qexprs = vec![];
                 let mut pattern = String::new();
                 for expr in exprs {
-                    qexprs.push(expr.visit(state)?);
+                    // values are printed in the same format as in the interpreter
+                    let qexpr = expr.visit(state)?;
+                    qexprs.push(quote! { psk_std::display::display(&(#qexpr)) });
                     pattern.push_str("{}");
                 }
                 //TODO: update this to use writeln! to an arbitrary output
                 Ok(quote! { println!(#pattern, #(#qexprs),*); })
+            },
+            (&Statement::Import(ref path), ref annotation) => {
+                let module = annotation.borrow().module().ok_or(
+                    format!("imported file '{}' has not been loaded", path))?;
+                let qname = Ident::new(module.name.0.clone());
+                Ok(quote! { use #qname::*; })
             }
 
         }
@@ -131,11 +361,25 @@ impl TranspileVisitor for Node<Statement> {
 impl TranspileVisitor for Node<Parameter> {
     fn visit(&self, state: &mut State) -> Result {
         let qname = self.item.name.visit(state)?;
-        let qty = self.item.name.visit(state)?;
+        let qty = match self.annotation.borrow().ty() {
+            Some(ref ty) => rust_type(ty)?,
+            None => { return Err(format!("missing type for parameter {}", self.item.name.item)); }
+        };
         Ok(quote ! { #qname: #qty })
     }
 }
 
+impl TranspileVisitor for Node<Field> {
+    fn visit(&self, state: &mut State) -> Result {
+        let qname = self.item.name.visit(state)?;
+        let qty = match self.annotation.borrow().ty() {
+            Some(ref ty) => rust_type(ty)?,
+            None => { return Err(format!("missing type for field {}", self.item.name.item)); }
+        };
+        Ok(quote ! { pub #qname: #qty })
+    }
+}
+
 impl TranspileVisitor for Node<Expression> {
     fn visit(&self, state: &mut State) -> Result {
         match (&self.item, &self.annotation) {
@@ -145,7 +389,22 @@ impl TranspileVisitor for Node<Expression> {
                 Ok(quote! { #qlit })
             },
             (&Expression::Identifier(ref ident), ref annotation) => {
-                let qident = add_cast(ident.visit(state)?, annotation.borrow().ty(),
+                let scope = annotation.borrow().scope().ok_or(
+                    format!("no scope associated with identifier {}", ident.item))?;
+                let sym = scope.borrow().resolve(&ident.item);
+                if let Some(Symbol::Function { .. }) = sym {
+                    // named functions used as values are wrapped in the same way as closures
+                    let qname = ident.visit(state)?;
+                    let qty = rust_type(&annotation.borrow().ty().ok_or(
+                        format!("missing type for function {}", ident.item))?)?;
+                    return Ok(quote! { (Rc::new(#qname) as #qty) });
+                }
+                if let Some(Symbol::Constant { ref value, .. }) = sym {
+                    return add_cast(quote! { #value }, annotation.borrow().ty(),
+                        annotation.borrow().promote_type());
+                }
+                let qident = add_cast(add_clone(variable_path(ident, Some(scope), state)?,
+                    annotation.borrow().ty()), annotation.borrow().ty(),
                     annotation.borrow().promote_type())?;
                 Ok(quote! { #qident })
             },
@@ -164,32 +423,148 @@ impl TranspileVisitor for Node<Expression> {
                     annotation.borrow().ty(), annotation.borrow().promote_type())?;
                 Ok(quote! { #postfix_op })
             },
+            (&Expression::Cast { ref expr, .. }, ref annotation) => {
+                let qexpr = expr.visit(state)?;
+                let src_ty = expr.annotation.borrow().promote_type().or(
+                    expr.annotation.borrow().ty());
+                let dest_ty = annotation.borrow().ty();
+                let qcast = if src_ty == dest_ty {
+                    qexpr
+                } else {
+                    add_cast(quote! { (#qexpr) }, src_ty, dest_ty)?
+                };
+                add_cast(qcast, annotation.borrow().ty(), annotation.borrow().promote_type())
+            },
             (&Expression::Block(ref block), ref annotation) => {
                 let qblock = block.visit(state)?;
                 let braced_qblock = quote! { { #qblock } };
                 add_cast(braced_qblock, annotation.borrow().ty(),
                     annotation.borrow().promote_type())
             },
+            (&Expression::StructLiteral { ref name, ref fields }, ref annotation) => {
+                let mut qfields = vec![];
+                for &(ref field_name, ref expr) in fields {
+                    let qfield_name = field_name.visit(state)?;
+                    let qexpr = expr.visit(state)?;
+                    qfields.push(quote! { #qfield_name: #qexpr });
+                }
+                let qname = name.visit(state)?;
+                add_cast(quote! { #qname { #(#qfields),* } }, annotation.borrow().ty(),
+                    annotation.borrow().promote_type())
+            },
+            (&Expression::Member { .. }, ref annotation)
+                    | (&Expression::Index { .. }, ref annotation) => {
+                let qplace = add_clone(place_path(self, state)?, annotation.borrow().ty());
+                add_cast(qplace, annotation.borrow().ty(), annotation.borrow().promote_type())
+            },
+            (&Expression::Array(ref elems), ref annotation) => {
+                let mut qelems = vec![];
+                for elem in elems {
+                    qelems.push(elem.visit(state)?);
+                }
+                add_cast(quote! { vec![#(#qelems),*] }, annotation.borrow().ty(),
+                    annotation.borrow().promote_type())
+            },
+            (&Expression::Tuple(ref elems), _) => {
+                let mut qelems = vec![];
+                for elem in elems {
+                    qelems.push(elem.visit(state)?);
+                }
+                Ok(quote! { (#(#qelems),*) })
+            },
+            (&Expression::ArrayRepeat { ref value, ref count }, ref annotation) => {
+                let qvalue = value.visit(state)?;
+                let qcount = count.visit(state)?;
+                add_cast(quote! { vec![#qvalue; (#qcount) as usize] }, annotation.borrow().ty(),
+                    annotation.borrow().promote_type())
+            },
+            (&Expression::Closure { ref params, ref body, .. }, ref annotation) => {
+                let mut qparams = vec![];
+                for param in params {
+                    qparams.push(param.visit(state)?);
+                }
+                let qty = rust_type(&annotation.borrow().ty().ok_or(
+                    "missing type for closure".to_string())?)?;
+                let qret_ty = match annotation.borrow().ty() {
+                    Some(PType::Function(_, ref ret_ty)) => rust_type(ret_ty)?,
+                    _ => { return Err("invalid type for closure".to_string()); }
+                };
+                // captured variables are copied into the closure when it is created, and copied
+                // again on each call so that the body can modify them
+                let mut qcaptures = vec![];
+                let mut qcopies = vec![];
+                for ident in annotation.borrow().captures() {
+                    let qident = Ident::new(ident.0.clone());
+                    qcaptures.push(quote! { let #qident = #qident.clone(); });
+                    qcopies.push(quote! { let mut #qident = #qident.clone(); });
+                }
+                let qbody = body.visit(state)?;
+                let qbody = add_cast(quote! { { #qbody } }, body.annotation.borrow().ty(),
+                    body.annotation.borrow().promote_type())?;
+                add_cast(quote! { {
+                    #(#qcaptures)*
+                    (Rc::new(move |ctx: &mut Context, #(#qparams),*| -> Result<#qret_ty, String> {
+                        #(#qcopies)* Ok(#qbody)
+                    }) as #qty)
+                } }, annotation.borrow().ty(), annotation.borrow().promote_type())
+            },
             (&Expression::FnCall { ref name, ref args }, ref annotation) => {
                 let mut qargs = vec![];
                 let scope = annotation.borrow().scope().unwrap();
                 let symbol: Option<Symbol> = scope.borrow().resolve(&name.item);
-                let is_stdlib_call = if let Some(sym) = symbol {
+                let overloaded = match symbol {
+                    Some(Symbol::Overloaded { .. }) => true,
+                    _ => false
+                };
+                let symbol = match symbol {
+                    Some(symbol) => Some(symbol.select_overload(annotation.borrow().overload())?),
+                    None => None
+                };
+                if let Some(Symbol::Function { body: FunctionBody::Intrinsic(intrinsic), .. })
+                        = symbol {
+                    let qintrinsic = intrinsic_to_tokens(intrinsic, args, state)?;
+                    return add_cast(qintrinsic, annotation.borrow().ty(),
+                        annotation.borrow().promote_type());
+                }
+                let is_stdlib_call = if let Some(ref sym) = symbol {
                     sym.is_stdlib_func()
                 } else {
                     false
                 };
+                // arguments are evaluated before the context is borrowed by the call, since they
+                // may themselves be function calls
+                let mut qbindings = vec![];
                 if is_stdlib_call {
-                    qargs.push(quote! { &mut env });
+                    qargs.push(quote! { &mut ctx.env });
+                } else {
+                    qargs.push(quote! { ctx });
                 }
-                for arg in args {
-                    qargs.push(arg.visit(state)?);
+                for (i, arg) in args.iter().enumerate() {
+                    let qarg = arg.visit(state)?;
+                    let qbinding = Ident::new(format!("call_arg{}", i));
+                    qbindings.push(quote! { let #qbinding = #qarg; });
+                    qargs.push(quote! { #qbinding });
                 }
-                let qname = name.visit(state)?;
-                let fn_call = if is_stdlib_call {
+                let qname = match symbol {
+                    Some(Symbol::Function { body: FunctionBody::External(ext_func_id), .. }) => {
+                        // library functions are called by their full path, so that they can't
+                        // clash with user-defined functions
+                        let lib_name = state.std_funcs.lib_name(ext_func_id).ok_or(format!(
+                            "unknown library function '{}'", name.item))?;
+                        let qident = Ident::new(format!("::psk_std::stdlib::{}", lib_name));
+                        quote! { #qident }
+                    },
+                    Some(Symbol::Function { ref params, .. }) if overloaded => {
+                        // keeps any module prefix of the called name
+                        let qident = Ident::new(overload_fn_name(&name.item.0, params)?);
+                        quote! { #qident }
+                    },
+                    _ => name.visit(state)?
+                };
+                let fn_call = if qbindings.is_empty() {
                     quote! { #qname(#(#qargs),*)? }
                 } else {
-                    quote! { #qname(#(#qargs),*) }
+                    quote! { { #(#qbindings)* #qname(#(#qargs),*)? } }
                 };
                 add_cast(fn_call, annotation.borrow().ty(), annotation.borrow().promote_type())
             },
@@ -209,12 +584,37 @@ impl TranspileVisitor for Node<Expression> {
                     }
                 }
             },
-            (&Expression::Loop { ref variant, ref set, ref body }, ref annotation) => {
+            (&Expression::Match { ref value, ref arms }, ref annotation) => {
+                let qvalue = value.visit(state)?;
+                let value_ty = value.annotation.borrow().ty();
+                // arms after a wildcard are never reached; if there is no wildcard, the type
+                // checker has verified that the final arm matches anything the others do not
+                let last = arms.iter().position(|arm| arm.pattern == Pattern::Wildcard)
+                    .unwrap_or(arms.len() - 1);
+                // the index of the matching arm is selected first, so that the matched value
+                // doesn't need to be bound in the scope of the arm bodies
+                let mut qselect = vec![];
+                let mut qarms = vec![];
+                for (i, arm) in arms.iter().take(last + 1).enumerate() {
+                    let qbody = arm.body.visit(state)?;
+                    if i == last {
+                        qarms.push(quote! { _ => { #qbody } });
+                    } else {
+                        let qcond = pattern_to_tokens(&arm.pattern, &value_ty);
+                        qselect.push(quote! { if #qcond { #i } else });
+                        qarms.push(quote! { #i => { #qbody }, });
+                    }
+                }
+                add_cast(quote! {
+                    match { let match_value = #qvalue; #(#qselect)* { #last } } { #(#qarms)* }
+                }, annotation.borrow().ty(), annotation.borrow().promote_type())
+            },
+            (&Expression::Loop { ref label, ref variant, ref set, ref body }, ref annotation) => {
                 let nl = nl();
-                let loop_var_name = loop_var_name(state);
-                state.loop_depth += 1;
+                let start_value = start_value(annotation.borrow().ty().unwrap());
+                let depth = state.loops.len();
                 let qset = set.visit(state)?;
-                let qbody = body.visit(state)?;
+                let (qbody, qlabel) = loop_body(label, start_value.is_some(), body, state)?;
                 let qvar = match *variant {
                     Some(ref variant) => {
                         variant.visit(state)?
@@ -223,26 +623,158 @@ impl TranspileVisitor for Node<Expression> {
                         raw("_")
                     }
                 };
-                state.loop_depth -= 1;
-                match start_value(annotation.borrow().ty().unwrap()) {
+                match start_value {
+                    Some(start_value) => {
+                        // loops with a value are translated to `loop` (the only Rust loop which
+                        // can break with a value), stepping through the set explicitly
+                        let qlabel = loop_label(depth);
+                        let last_value = loop_last_value(depth);
+                        let iter_name = loop_iter_name(depth);
+                        add_cast(quote! { {
+                            let mut #iter_name = (#qset).into_iter();
+                            let mut #last_value = #start_value;
+                            #qlabel: loop {
+                                let #qvar = match #iter_name.next() {
+                                    Some(elem) => elem,
+                                    None => { break #qlabel #last_value; }
+                                };
+                                #nl #last_value = { #nl #qbody };
+                            }
+                        } }, annotation.borrow().ty(), annotation.borrow().promote_type())
+                    },
+                    None => {
+                        add_cast(quote! {
+                            #qlabel for #qvar in #qset { #nl #qbody }
+                        }, annotation.borrow().ty(), annotation.borrow().promote_type())
+                    }
+                }
+            },
+            (&Expression::WhileLoop { ref label, ref cond, ref body }, ref annotation) => {
+                let nl = nl();
+                let start_value = start_value(annotation.borrow().ty().unwrap());
+                let depth = state.loops.len();
+                let qcond = cond.visit(state)?;
+                let (qbody, qlabel) = loop_body(label, start_value.is_some(), body, state)?;
+                match start_value {
                     Some(start_value) => {
+                        let qlabel = loop_label(depth);
+                        let last_value = loop_last_value(depth);
                         add_cast(quote! { {
-                            let mut #loop_var_name = #start_value;
-                            for #qvar in #qset { #nl #loop_var_name = { #nl #qbody }; }
-                            #loop_var_name
+                            let mut #last_value = #start_value;
+                            #qlabel: loop {
+                                if !(#qcond) { break #qlabel #last_value; }
+                                #nl #last_value = { #nl #qbody };
+                            }
                         } }, annotation.borrow().ty(), annotation.borrow().promote_type())
                     },
                     None => {
                         add_cast(quote! {
-                            for #qvar in #qset { #nl #qbody }
+                            #qlabel while #qcond { #nl #qbody }
                         }, annotation.borrow().ty(), annotation.borrow().promote_type())
                     }
                 }
             },
+            (&Expression::PixelLoop { ref label, ref row, ref col, ref body }, _) => {
+                let nl = nl();
+                let qrow = row.visit(state)?;
+                let qcol = col.visit(state)?;
+                let (qbody, qlabel) = loop_body(label, false, body, state)?;
+                let qvalue = add_cast(quote! { { #nl #qbody } }, body.annotation.borrow().ty(),
+                    body.annotation.borrow().promote_type())?;
+                // pixels are visited in a single loop, so that break and continue statements
+                // apply to it directly
+                Ok(quote! { {
+                    let pixel_rows = ::psk_std::stdlib::get_image_height(&mut ctx.env)?;
+                    let pixel_cols = ::psk_std::stdlib::get_image_width(&mut ctx.env)?;
+                    #qlabel for pixel in 0..pixel_rows * pixel_cols { #nl
+                        let mut #qrow = pixel / pixel_cols;
+                        let mut #qcol = pixel % pixel_cols;
+                        let pixel_value = #qvalue;
+                        ::psk_std::stdlib::set_pixel_data(&mut ctx.env, #qrow, #qcol,
+                            pixel_value)?;
+                    }
+                } })
+            },
+        }
+    }
+}
+
+/// Produce the condition under which a (non-wildcard) match pattern matches `match_value`.
+fn pattern_to_tokens(pattern: &Pattern, value_ty: &Option<PType>) -> Tokens {
+    // pattern literals are promoted to the type of the matched value
+    let promote = |lit: &Literal| {
+        match (lit, value_ty) {
+            (&Literal::Int(i), &Some(PType::Float)) => Literal::Float(i as f64),
+            _ => lit.clone(),
+        }
+    };
+    match *pattern {
+        Pattern::Value(ref lit) => {
+            let qlit = promote(lit);
+            quote! { match_value == #qlit }
+        },
+        Pattern::Interval { ref start, ref end, end_inclusive } => {
+            let (qstart, qend) = (promote(start), promote(end));
+            if end_inclusive {
+                quote! { (#qstart <= match_value && match_value <= #qend) }
+            } else {
+                quote! { (#qstart <= match_value && match_value < #qend) }
+            }
+        },
+        Pattern::Wildcard => quote! { true },
+    }
+}
+
+/// Produce the (uncloned) path to a location, e.g. `line.start.x` or `points[2]`.
+fn place_path(expr: &Node<Expression>, state: &mut State) -> Result {
+    match expr.item {
+        Expression::Identifier(ref ident) => {
+            variable_path(ident, expr.annotation.borrow().scope(), state)
+        },
+        Expression::Member { ref object, ref field } => {
+            let qobject = place_path(object, state)?;
+            let qfield = field.visit(state)?;
+            Ok(quote! { #qobject.#qfield })
+        },
+        Expression::Index { ref object, ref index } => {
+            let qobject = place_path(object, state)?;
+            let qindex = index.visit(state)?;
+            Ok(quote! { #qobject[(#qindex) as usize] })
+        },
+        _ => {
+            let qexpr = expr.visit(state)?;
+            Ok(quote! { (#qexpr) })
         }
     }
 }
 
+fn intrinsic_to_tokens(intrinsic: Intrinsic, args: &[Node<Expression>], state: &mut State)
+        -> Result {
+    Ok(match intrinsic {
+        Intrinsic::Len => {
+            let qarray = place_path(&args[0], state)?;
+            quote! { (#qarray.len() as i64) }
+        },
+        Intrinsic::Push => {
+            let qarray = place_path(&args[0], state)?;
+            let qvalue = args[1].visit(state)?;
+            quote! { #qarray.push(#qvalue) }
+        },
+        Intrinsic::Str => {
+            let qvalue = args[0].visit(state)?;
+            quote! { psk_std::display::display(&(#qvalue)).to_string() }
+        },
+        Intrinsic::Format => {
+            let qfmt = args[0].visit(state)?;
+            let mut qargs = vec![];
+            for arg in args[1..].iter() {
+                qargs.push(arg.visit(state)?);
+            }
+            quote! { format_values(&#qfmt, &[#(&(#qargs) as &dyn Format),*])? }
+        }
+    })
+}
+
 fn start_value(ty: PType) -> Option<Tokens> {
     match ty {
         PType::String => Some(raw("String::new()")),
@@ -250,7 +782,62 @@ fn start_value(ty: PType) -> Option<Tokens> {
         PType::Int => Some(raw("0")),
         PType::Boolean => Some(raw("false")),
         PType::Complex => Some(raw("Complex::new(0.0, 0.0)")),
-        PType::Set | PType::Void => None,
+        PType::Array(_) => Some(raw("Vec::new()")),
+        PType::Tuple(elems) => {
+            let mut qelems = vec![];
+            for elem in elems {
+                qelems.push(start_value(elem)?);
+            }
+            Some(quote! { (#(#qelems),*) })
+        },
+        PType::Set | PType::Void | PType::Struct(_) | PType::Function(_, _) => None,
+    }
+}
+
+fn rust_type(ty: &PType) -> Result {
+    Ok(match *ty {
+        PType::String => quote! { String },
+        PType::Float => quote! { f64 },
+        PType::Int => quote! { i64 },
+        PType::Boolean => quote! { bool },
+        PType::Complex => quote! { Complex },
+        PType::Void => quote! { () },
+        PType::Struct(ref name) => {
+            let ident = Ident::new(name.0.clone());
+            quote! { #ident }
+        },
+        PType::Array(ref elem) => {
+            let qelem = rust_type(elem)?;
+            quote! { Vec<#qelem> }
+        },
+        PType::Function(ref params, ref ret) => {
+            let mut qparams = vec![];
+            for param in params.iter() {
+                qparams.push(rust_type(param)?);
+            }
+            let qret = rust_type(ret)?;
+            quote! { Rc<Fn(&mut Context, #(#qparams),*) -> Result<#qret, String>> }
+        },
+        PType::Tuple(ref elems) => {
+            let mut qelems = vec![];
+            for elem in elems.iter() {
+                qelems.push(rust_type(elem)?);
+            }
+            quote! { (#(#qelems),*) }
+        },
+        PType::Set => { return Err(format!("sets cannot be used as a value type")); }
+    })
+}
+
+/// Values of non-`Copy` types are cloned when read, so that variables and struct fields can be
+/// used more than once.
+fn add_clone(elem: Tokens, ty: Option<PType>) -> Tokens {
+    match ty {
+        Some(PType::String) | Some(PType::Struct(_)) | Some(PType::Array(_))
+                | Some(PType::Function(_, _)) | Some(PType::Tuple(_)) => {
+            quote! { #elem.clone() }
+        },
+        _ => elem
     }
 }
 
@@ -261,12 +848,57 @@ impl TranspileVisitor for Node<Set> {
                 let qstart = start.visit(state)?;
                 let qend = end.visit(state)?;
                 let qstep = step.visit(state)?;
-                Ok(quote!{ StepRange::new(#qstart, #qend, #end_inclusive, #qstep) })
+                Ok(quote!{ StepRange::new(#qstart, #qend, #end_inclusive, #qstep)? })
+            },
+            Set::Elements(ref elems) => {
+                let mut qelems = vec![];
+                for elem in elems {
+                    qelems.push(elem.visit(state)?);
+                }
+                Ok(quote! { ::psk_std::set::unique(vec![#(#qelems),*]) })
+            },
+            Set::Builder { ref var, ref source, ref cond, .. } => {
+                let qvar = var.visit(state)?;
+                let qsource = source.visit(state)?;
+                let qcond = match *cond {
+                    Some(ref cond) => cond.visit(state)?,
+                    None => quote! { true },
+                };
+                Ok(quote! { {
+                    let mut set_elements = vec![];
+                    for #qvar in #qsource {
+                        if #qcond { set_elements.push(#qvar); }
+                    }
+                    set_elements
+                } })
+            },
+            Set::Operation { op, ref left, ref right } => {
+                let qleft = set_to_operand(left, state)?;
+                let qright = set_to_operand(right, state)?;
+                Ok(match op {
+                    SetOp::Union => quote! { ::psk_std::set::union(#qleft, #qright) },
+                    SetOp::Intersection => {
+                        quote! { ::psk_std::set::intersection(#qleft, #qright) }
+                    },
+                    SetOp::Difference => quote! { ::psk_std::set::difference(#qleft, #qright) },
+                })
             }
         }
     }
 }
 
+/// Translate an operand of a set operation, promoting its elements if necessary.
+fn set_to_operand(set: &Node<Set>, state: &mut State) -> Result {
+    let qset = set.visit(state)?;
+    match set.annotation.borrow().promote_type() {
+        Some(promote_ty) => {
+            let qelem = add_cast(raw("elem"), set.annotation.borrow().ty(), Some(promote_ty))?;
+            Ok(quote! { (#qset).into_iter().map(|elem| #qelem) })
+        },
+        None => Ok(qset)
+    }
+}
+
 impl TranspileVisitor for Node<Literal> {
     fn visit(&self, _: &mut State) -> Result {
         let item = &self.item;
@@ -292,10 +924,22 @@ fn add_cast<T: ToTokens + ::std::fmt::Display>(elem: T, actual_ty: Option<PType>
                         PType::String => { return Err(format!("invalid promotion to string")); },
                         PType::Float => quote! { (#elem as f64) },
                         PType::Int => quote! { (#elem as i64) },
-                        PType::Boolean => quote! { #elem as bool },
+                        PType::Boolean => quote! { (#elem as bool) },
                         PType::Complex => quote! { Complex::new((#elem) as f64, 0.0) },
                         PType::Set => { return Err(format!("invalid promotion to set")); },
                         PType::Void => { return Err(format!("invliad promotion to void")); },
+                        PType::Struct(_) => {
+                            return Err(format!("invalid promotion to struct"));
+                        },
+                        PType::Array(_) => {
+                            return Err(format!("invalid promotion to array"));
+                        },
+                        PType::Function(_, _) => {
+                            return Err(format!("invalid promotion to function"));
+                        },
+                        PType::Tuple(_) => {
+                            return Err(format!("invalid promotion to tuple"));
+                        },
                     })
                 },
                 None => Ok(quote! { #elem })
@@ -310,39 +954,59 @@ fn infix_to_tokens(op: &InfixOp, left: &Node<Expression>, right: &Node<Expressio
     let qleft = left.visit(state)?;
     let qright = right.visit(state)?;
     Ok(match *op {
-        InfixOp::Add => { quote! { #qleft + #qright } },
-        InfixOp::Subtract => { quote! { #qleft - #qright } },
-        InfixOp::Multiply => { quote! { #qleft * #qright } },
-        InfixOp::Divide => { quote! { #qleft / #qright } },
+        InfixOp::Add => {
+            match left.annotation.borrow().promoted() {
+                Some(PType::String) => quote! { format!("{}{}", #qleft, #qright) },
+                _ => quote! { (#qleft + #qright) }
+            }
+        },
+        InfixOp::Subtract => { quote! { (#qleft - #qright) } },
+        InfixOp::Multiply => { quote! { (#qleft * #qright) } },
+        InfixOp::Divide => { quote! { (#qleft / #qright) } },
+        InfixOp::FloorDivide => { quote! { (#qleft).floor_div(#qright)? } },
+        InfixOp::Modulo => { quote! { (#qleft).floor_mod(#qright)? } },
+        InfixOp::BitAnd => { quote! { (#qleft & #qright) } },
+        InfixOp::BitOr => { quote! { (#qleft | #qright) } },
+        InfixOp::BitXor => { quote! { (#qleft ^ #qright) } },
+        InfixOp::ShiftLeft => { quote! { (#qleft).shift_left(#qright)? } },
+        InfixOp::ShiftRight => { quote! { (#qleft).shift_right(#qright)? } },
         InfixOp::Power => {
             match (left.annotation.borrow().promoted(), right.annotation.borrow().promoted()) {
-                (Some(PType::Int), Some(PType::Int)) => quote! { #qleft.pow(#qright) },
+                (Some(PType::Int), Some(PType::Int)) => quote! { (#qleft).pow(#qright) },
                 (Some(PType::Int), Some(PType::Float)) => {
                     return Err("integer raised to floating point power".to_string());
                 },
-                (Some(PType::Float), Some(PType::Int)) => quote! { #qleft.powi(#qright) },
-                (Some(PType::Float), Some(PType::Float)) => quote! { #qleft.powf(#qright) },
+                (Some(PType::Float), Some(PType::Int)) => quote! { (#qleft).powi(#qright) },
+                (Some(PType::Float), Some(PType::Float)) => quote! { (#qleft).powf(#qright) },
+                (Some(PType::Complex), Some(PType::Int)) => quote! { (#qleft).powi(#qright) },
+                (Some(PType::Complex), Some(PType::Float)) => quote! { (#qleft).powf(#qright) },
+                (Some(PType::Complex), Some(PType::Complex)) => {
+                    quote! { (#qleft).powc(#qright) }
+                },
                 _ => { return Err("invalid exponentiation".to_string()) }
             }
         },
         InfixOp::Comparison(compare_op) => {
             match compare_op {
-                CompareOp::LessThan => { quote! { #qleft < #qright } },
-                CompareOp::LessThanEqual => { quote! { #qleft <= #qright } },
-                CompareOp::GreaterThan => { quote! { #qleft > #qright } },
-                CompareOp::GreaterThanEqual => { quote! { #qleft >= #qright } },
-                CompareOp::Equal => { quote! { #qleft == #qright } },
-                CompareOp::NotEqual => { quote! { #qleft != #qright } },
+                CompareOp::LessThan => { quote! { (#qleft < #qright) } },
+                CompareOp::LessThanEqual => { quote! { (#qleft <= #qright) } },
+                CompareOp::GreaterThan => { quote! { (#qleft > #qright) } },
+                CompareOp::GreaterThanEqual => { quote! { (#qleft >= #qright) } },
+                CompareOp::Equal => { quote! { (#qleft == #qright) } },
+                CompareOp::NotEqual => { quote! { (#qleft != #qright) } },
             }
         },
+        InfixOp::And => { quote! { (#qleft && #qright) } },
+        InfixOp::Or => { quote! { (#qleft || #qright) } },
     })
 }
 
 fn prefix_to_tokens(op: &PrefixOp, right: &Node<Expression>, state: &mut State) -> Result {
     let qright = right.visit(state)?;
     Ok(match *op {
-        PrefixOp::UnaryMinus => { quote! { -#qright } },
+        PrefixOp::UnaryMinus => { quote! { (-#qright) } },
         PrefixOp::UnaryPlus => { quote! { #qright } },
+        PrefixOp::Not => { quote! { !(#qright) } },
     })
 }
 
@@ -352,9 +1016,9 @@ fn postfix_to_tokens(op: &PostfixOp, left: &Node<Expression>, state: &mut State)
         PostfixOp::Imaginary => { quote! { Complex::new(0.0, (#qleft) as f64) } },
         PostfixOp::Conjugate => {
             match left.annotation.borrow().promoted() {
-                Some(PType::Int) => { quote! { 1.0 / (#qleft as f64) } },
-                Some(PType::Float) => { quote! { 1.0 / #qleft } },
-                Some(PType::Complex) => { quote! { #qleft.conj() } },
+                Some(PType::Int) => { quote! { (1.0 / (#qleft as f64)) } },
+                Some(PType::Float) => { quote! { (1.0 / #qleft) } },
+                Some(PType::Complex) => { quote! { (#qleft).conj() } },
                 Some(ty) => { return Err(format!("invalid type for conjugation: {}", ty)) },
                 _ => { return Err("missing type information".to_string()) }
             }
@@ -365,7 +1029,7 @@ fn postfix_to_tokens(op: &PostfixOp, left: &Node<Expression>, state: &mut State)
 impl ToTokens for Literal {
     fn to_tokens(&self, tokens: &mut Tokens) {
         match *self {
-            Literal::String(ref s) => { tokens.append(format!("\"{}\".to_string()", s)); },
+            Literal::String(ref s) => { tokens.append(format!("{:?}.to_string()", s)); },
             Literal::Float(f) => { tokens.append(format!("{}f64", f)); },
             Literal::Int(i) => { tokens.append(format!("{}i64", i)); },
             Literal::Boolean(b) => { tokens.append(format!("{}", b)); },
@@ -380,10 +1044,12 @@ fn preface() -> Tokens {
 extern crate psk_std;
 
 use std::io::Write;
+use std::rc::Rc;
 
 use psk_std::step_range::StepRange;
-use psk_std::stdlib::*;
 use psk_std::complex::Complex;
+use psk_std::ops::{FloorDivMod, Shift};
+use psk_std::format::{Format, format as format_values};
 use psk_std::Environment;
 
 fn main() {
@@ -5,22 +5,454 @@
 //! expects that the symbol table annotations already exist on the tree.
 
 use std::rc::Rc;
+use std::cell::RefCell;
 
-use sindra::Typed;
-use sindra::scope::{Scoped, SymbolStore};
+use sindra::{Typed, Identifier};
+use sindra::scope::{Scoped, SymbolStore, MemoryScope};
 use sindra::inference::{InferTypesBinary, BinaryOpTypes, InferTypesUnary, UnaryOpTypes,
     InferPromotion};
 
 use ast::ast::*;
+use ast::Spanned;
 
 use sindra::Node;
 
+use psk_std::format::placeholder_count;
+
 use PType;
 use Symbol;
+use value::Value;
+use symbol::{FunctionBody, Intrinsic, define_function};
 use visitor::State;
 
 type Result = ::std::result::Result<(), String>;
 
+/// Type of an already visited node. If its type could not be computed (which has already been
+/// reported), the type of the node being visited is left unknown as well.
+macro_rules! visited_ty {
+    ($node:expr) => {
+        match $node.annotation.borrow().ty() {
+            Some(ty) => ty,
+            None => { return Ok(()); }
+        }
+    }
+}
+
+/// Compute the piske type described by a type specification. On failure, returns a message
+/// describing why the specification is not a valid type.
+fn resolve_type_spec<Sc: SymbolStore<Symbol>>(scope: &Sc, spec: &TypeSpec)
+        -> ::std::result::Result<PType, String> {
+    match *spec {
+        TypeSpec::Named(ref name) => {
+            match scope.resolve(&name.item) {
+                Some(Symbol::BuiltinType { ty, .. }) => Ok(ty),
+                Some(Symbol::Struct { name, .. }) => Ok(PType::Struct(name)),
+                Some(Symbol::Variable { .. }) => {
+                    Err(format!("variable '{}' not valid as type", name.item))
+                },
+                Some(Symbol::Constant { .. }) => {
+                    Err(format!("constant '{}' not valid as type", name.item))
+                },
+                Some(Symbol::Function { .. }) | Some(Symbol::Overloaded { .. }) => {
+                    Err(format!("function '{}' not valid as type", name.item))
+                },
+                None => Err(format!("type '{}' unknown", name.item))
+            }
+        },
+        TypeSpec::Array(ref elem) => {
+            Ok(PType::Array(Box::new(resolve_type_spec(scope, elem)?)))
+        },
+        TypeSpec::Function { ref params, ref ret } => {
+            let mut param_tys = vec![];
+            for param in params.iter() {
+                param_tys.push(resolve_type_spec(scope, param)?);
+            }
+            Ok(PType::Function(param_tys, Box::new(resolve_type_spec(scope, ret)?)))
+        },
+        TypeSpec::Tuple(ref elems) => {
+            let mut elem_tys = vec![];
+            for elem in elems.iter() {
+                elem_tys.push(resolve_type_spec(scope, elem)?);
+            }
+            Ok(PType::Tuple(elem_tys))
+        }
+    }
+}
+
+/// Compute the type of a function with the specified parameters (whose types have already been
+/// computed) and return type.
+fn function_type(params: &[Node<Parameter>], ret_ty: Option<PType>) -> Option<PType> {
+    let mut param_tys = vec![];
+    for param in params.iter() {
+        param_tys.push(param.annotation.borrow().ty()?);
+    }
+    Some(PType::Function(param_tys, Box::new(ret_ty?)))
+}
+
+/// Look up the field definitions of the struct named `name`, if it exists in the scope.
+fn struct_fields<Sc: SymbolStore<Symbol>>(scope: &Sc, name: &Identifier)
+        -> Option<Vec<Node<Field>>> {
+    match scope.resolve(name) {
+        Some(Symbol::Struct { fields, .. }) => Some(fields),
+        _ => None
+    }
+}
+
+/// Check whether the value of an expression can be stored in a location of type `dest_ty`,
+/// setting the expression's promotion type if necessary. Returns false if not possible.
+fn check_assignable(expr: &Node<Expression>, dest_ty: &PType) -> bool {
+    let expr_ty = match expr.annotation.borrow().ty() {
+        Some(ty) => ty,
+        None => { return false; }
+    };
+    if expr_ty == *dest_ty {
+        return true;
+    }
+    match expr_ty.infer_promotion(dest_ty.clone()) {
+        Some(promoted) => {
+            expr.annotation.borrow_mut().set_promote_type(Some(promoted));
+            true
+        },
+        None => false
+    }
+}
+
+/// Check an assignment of `expr` (of type `expr_ty`) to the variable `ident`, as resolved in
+/// `scope`. Assigning to a variable which does not yet have a type sets its type.
+fn check_assignment(ident: &Identifier, expr: &Node<Expression>, expr_ty: &PType,
+        scope: &Rc<RefCell<MemoryScope<Symbol, Value>>>, state: &mut State) {
+    let existing = scope.borrow().resolve(ident);
+    if let Some(existing) = existing {
+        match existing {
+            Symbol::Variable { ty: existing_ty, global, .. } => {
+                if let Some(dest_ty) = existing_ty {
+                    if !check_assignable(expr, &dest_ty) {
+                        state.error(format!(
+                            "attempt to change variable type of '{}'", ident));
+                    }
+                } else {
+                    // ident exists in scope but doesn't have a type, update it (global
+                    // variables in the global scope)
+                    if global {
+                        state.global.borrow_mut().define(ident.clone(),
+                            Symbol::global_variable(ident.clone(),
+                            Some(expr_ty.clone())));
+                    } else {
+                        scope.borrow_mut().define(ident.clone(),
+                            Symbol::variable(ident.clone(), Some(expr_ty.clone())));
+                    }
+                }
+            },
+            Symbol::Constant { .. } => {
+                state.error(format!(
+                    "attempt to assign to constant '{}'", ident));
+            },
+            Symbol::Function { .. } | Symbol::Overloaded { .. } => {
+                state.error(format!(
+                    "function '{}' invalid as lvalue", ident));
+            },
+            Symbol::BuiltinType { .. } => {
+                state.error(
+                    format!("attempt to redefine built-in type '{}'", ident));
+            },
+            Symbol::Struct { .. } => {
+                state.error(
+                    format!("attempt to redefine struct '{}'", ident));
+            }
+        }
+    } else {
+        state.error(
+            format!("attempt to assign to undefined variable '{}'", ident));
+    }
+}
+
+/// Check that a literal in a match pattern can be compared with a matched value of type `ty`.
+fn check_pattern_literal(lit: &Literal, ty: &PType, state: &mut State) {
+    let lit_ty = match *lit {
+        Literal::Float(_) => PType::Float,
+        Literal::Int(_) => PType::Int,
+        Literal::String(_) => PType::String,
+        Literal::Boolean(_) => PType::Boolean,
+    };
+    if lit_ty != *ty && lit_ty.infer_promotion(ty.clone()).is_none() {
+        state.error(format!("pattern value '{}' of type '{}' does not match type of \
+            matched value '{}'", lit, lit_ty, ty));
+    }
+}
+
+/// Find a range of integer values which are not matched by any of the specified match arms.
+/// Returns the first such (inclusive) range, or `None` if the arms cover all integers.
+fn uncovered_ints(arms: &[MatchArm]) -> Option<(i64, i64)> {
+    let mut ranges = vec![];
+    for arm in arms.iter() {
+        match arm.pattern {
+            Pattern::Value(Literal::Int(value)) => {
+                ranges.push((value, value));
+            },
+            Pattern::Interval { start: Literal::Int(start), end: Literal::Int(end),
+                    end_inclusive } => {
+                let last = if end_inclusive { Some(end) } else { end.checked_sub(1) };
+                if let Some(last) = last {
+                    if start <= last {
+                        ranges.push((start, last));
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    ranges.sort();
+    // smallest value not yet known to be covered (None once all values are covered)
+    let mut next = Some(::std::i64::MIN);
+    for (low, high) in ranges {
+        let uncovered = match next {
+            Some(uncovered) => uncovered,
+            None => { break; }
+        };
+        if low > uncovered {
+            return Some((uncovered, low - 1));
+        }
+        if high >= uncovered {
+            next = high.checked_add(1);
+        }
+    }
+    next.map(|uncovered| (uncovered, ::std::i64::MAX))
+}
+
+/// Returns true if the expression is a (possibly negated) literal zero.
+fn is_zero_literal(expr: &Node<Expression>) -> bool {
+    match expr.item {
+        Expression::Literal(ref literal) => {
+            match literal.item {
+                Literal::Int(value) => value == 0,
+                Literal::Float(value) => value == 0.0,
+                _ => false
+            }
+        },
+        Expression::Prefix { ref right, .. } => is_zero_literal(right),
+        _ => false
+    }
+}
+
+/// Returns true if the expression refers to a modifiable location: a variable, or a member or
+/// element of a modifiable location.
+fn is_place(expr: &Node<Expression>) -> bool {
+    match expr.item {
+        Expression::Identifier(_) => true,
+        Expression::Member { ref object, .. } | Expression::Index { ref object, .. } => {
+            is_place(object)
+        },
+        _ => false
+    }
+}
+
+/// Check the types of an array index expression.
+fn check_index(index: &Node<Expression>, state: &mut State) {
+    match index.annotation.borrow().ty() {
+        Some(PType::Int) | None => {},
+        Some(ty) => {
+            state.error(format!("array index must be of type 'int', found '{}'", ty));
+        }
+    }
+}
+
+/// Check the (already visited) arguments of a call to function `name` against its parameters,
+/// marking arguments which need promotion, and return the result type of the call.
+fn function_call_type(name: &Identifier, ret_ty: Option<PType>, params: &[Node<Parameter>],
+        args: &[Node<Expression>], state: &mut State) -> Option<PType> {
+    if args.len() != params.len() {
+        state.error(format!("function '{}' expects {} arguments, {} found", name,
+            params.len(), args.len()));
+        return None;
+    }
+    // check the parameter types
+    for (ref param, ref arg) in params.iter().zip(args) {
+        let arg_ty = arg.annotation.borrow().ty();
+        let param_ty = param.annotation.borrow().ty();
+        let (arg_ty, param_ty) = match (arg_ty, param_ty) {
+            (Some(arg_ty), Some(param_ty)) => (arg_ty, param_ty),
+            _ => { continue; }
+        };
+
+        if param_ty != arg_ty {
+            match arg_ty.infer_promotion(param_ty.clone()) {
+                Some(promoted) => {
+                    arg.annotation.borrow_mut().set_promote_type(Some(promoted));
+                },
+                None => {
+                    state.error(format!("invalid argument type for parameter '{}' of \
+                        function '{}': expected '{}', found '{}'", param.item.name.item, name,
+                        param_ty, arg_ty));
+                }
+            }
+        }
+    }
+    if ret_ty.is_none() {
+        state.error(format!("function '{}' does not have a valid return type", name));
+    }
+    ret_ty
+}
+
+/// Select which definition of overloaded function `name` is called with the (already visited)
+/// arguments: the definition whose parameter types match the argument types exactly or, failing
+/// that, the most specific definition whose parameter types the arguments can be promoted to.
+/// Library definitions are only considered if no user-defined definition accepts the arguments.
+fn select_overload<Sc: SymbolStore<Symbol>>(name: &Identifier, functions: &[Symbol],
+        args: &[Node<Expression>], scope: &Sc, state: &mut State) -> Option<usize> {
+    let arg_tys = match args.iter().map(|arg| arg.annotation.borrow().ty())
+            .collect::<Option<Vec<PType>>>() {
+        Some(arg_tys) => arg_tys,
+        // argument errors have already been reported
+        None => { return None; }
+    };
+    let promotes_to = |from: &[PType], to: &[PType]| {
+        from.iter().zip(to.iter()).all(|(from, to)| {
+            from == to || from.infer_promotion(to.clone()).is_some()
+        })
+    };
+    let mut exact = vec![];
+    let mut promotable = vec![];
+    for (index, function) in functions.iter().enumerate() {
+        let params = match *function {
+            Symbol::Function { ref params, .. } if params.len() == args.len() => params,
+            _ => { continue; }
+        };
+        let param_tys = params.iter().map(|param| param.annotation.borrow().ty()
+            .or_else(|| resolve_type_spec(scope, &param.item.ty).ok()))
+            .collect::<Option<Vec<PType>>>();
+        let param_tys = match param_tys {
+            Some(param_tys) => param_tys,
+            None => { continue; }
+        };
+        if param_tys == arg_tys {
+            exact.push(index);
+        } else if promotes_to(&arg_tys, &param_tys) {
+            promotable.push((index, param_tys));
+        }
+    }
+    // user-defined functions take precedence over library functions of the same name, so that
+    // adding library functions doesn't change which function existing programs call
+    let user_defined = |index: usize| !functions[index].is_stdlib_func();
+    if exact.iter().cloned().chain(promotable.iter().map(|&(index, _)| index)).any(user_defined) {
+        exact.retain(|&index| user_defined(index));
+        promotable.retain(|&(index, _)| user_defined(index));
+    }
+    // among several definitions requiring promotion, the most specific one (whose parameter types
+    // can be promoted to those of all the others) is preferred, e.g. `sqrt(float)` over
+    // `sqrt(complex)` for an int argument
+    let most_specific = promotable.iter().filter(|&&(_, ref param_tys)| {
+        promotable.iter().all(|&(_, ref other_tys)| promotes_to(param_tys, other_tys))
+    }).map(|&(index, _)| index).collect::<Vec<_>>();
+    let candidates = if !exact.is_empty() {
+        exact
+    } else if most_specific.len() == 1 {
+        most_specific
+    } else {
+        promotable.into_iter().map(|(index, _)| index).collect()
+    };
+    let arg_list = arg_tys.iter().map(|ty| ty.to_string()).collect::<Vec<_>>().join(", ");
+    match candidates.len() {
+        1 => Some(candidates[0]),
+        0 => {
+            state.error(format!("no definition of function '{}' accepts arguments of \
+                types ({})", name, arg_list));
+            None
+        },
+        _ => {
+            state.error(format!("ambiguous call to overloaded function '{}' with \
+                arguments of types ({})", name, arg_list));
+            None
+        }
+    }
+}
+
+/// Compute the result type of a call to an intrinsic function (arguments have already been
+/// visited).
+fn intrinsic_type(intrinsic: Intrinsic, args: &[Node<Expression>], state: &mut State)
+        -> Option<PType> {
+    // invalid numbers of arguments are reported by the symbol visitor
+    match intrinsic.arity() {
+        Some(arity) if args.len() != arity => { return None; },
+        None if args.is_empty() => { return None; },
+        _ => {}
+    }
+    match intrinsic {
+        Intrinsic::Len | Intrinsic::Push => array_intrinsic_type(intrinsic, args, state),
+        Intrinsic::Str => {
+            if check_formattable(&args[0], state) { Some(PType::String) } else { None }
+        },
+        Intrinsic::Format => {
+            match args[0].annotation.borrow().ty() {
+                Some(PType::String) => {},
+                Some(ty) => {
+                    state.error(format!("format string must be of type 'string', found \
+                        '{}'", ty));
+                    return None;
+                },
+                None => { return None; }
+            }
+            // literal format strings are checked now; others are checked when evaluated
+            if let Expression::Literal(ref literal) = args[0].item {
+                if let Literal::String(ref fmt) = literal.item {
+                    match placeholder_count(fmt) {
+                        Ok(count) if count != args.len() - 1 => {
+                            state.error(format!("format string \"{}\" expects {} \
+                                arguments, found {}", fmt, count, args.len() - 1));
+                        },
+                        Ok(_) => {},
+                        Err(e) => { state.error(e); }
+                    }
+                }
+            }
+            let mut valid = true;
+            for arg in args[1..].iter() {
+                valid = check_formattable(arg, state) && valid;
+            }
+            if valid { Some(PType::String) } else { None }
+        },
+    }
+}
+
+/// Returns true if `expr` has a type which can be converted to a string, logging an error if not.
+fn check_formattable(expr: &Node<Expression>, state: &mut State) -> bool {
+    match expr.annotation.borrow().ty() {
+        Some(PType::String) | Some(PType::Float) | Some(PType::Int) | Some(PType::Boolean)
+                | Some(PType::Complex) => true,
+        Some(ty) => {
+            state.error(format!("unable to convert value of type '{}' to a string", ty));
+            false
+        },
+        None => false
+    }
+}
+
+/// Compute the result type of a call to an array intrinsic (`len` or `push`).
+fn array_intrinsic_type(intrinsic: Intrinsic, args: &[Node<Expression>], state: &mut State)
+        -> Option<PType> {
+    let array_ty = args[0].annotation.borrow().ty();
+    match (intrinsic, array_ty) {
+        (Intrinsic::Len, Some(PType::Array(_))) => Some(PType::Int),
+        (Intrinsic::Push, Some(PType::Array(elem_ty))) => {
+            if !is_place(&args[0]) {
+                state.error(format!("push requires a variable array, found {}",
+                    args[0].item));
+                None
+            } else if !check_assignable(&args[1], &elem_ty) {
+                state.error(format!("invalid type for push onto array of type '[{}]'",
+                    elem_ty));
+                None
+            } else {
+                Some(PType::Void)
+            }
+        },
+        (_, Some(ty)) => {
+            state.error(format!("expected array argument, found type '{}'", ty));
+            None
+        },
+        (_, None) => None
+    }
+}
+
 /// Trait for type computation visitor; implemented for all abstract syntax tree nodes.
 pub trait TypeComputationVisitor {
     /// Infer types, enforce type safety, and compute type promotion for this node, and visit any
@@ -28,6 +460,12 @@ pub trait TypeComputationVisitor {
     fn visit(&self, &mut State) -> Result;
 }
 
+/// Type computation for nodes with a position in the source code, which is where errors found
+/// while visiting them are reported.
+trait ComputeType {
+    fn compute_type(&self, state: &mut State) -> Result;
+}
+
 impl TypeComputationVisitor for Node<Program> {
     fn visit(&self, state: &mut State) -> Result {
         self.item.0.visit(state)?;
@@ -48,7 +486,7 @@ impl TypeComputationVisitor for Node<Block> {
                 match ret_ty {
                     Some(_) => {
                         if last_ty != ret_ty {
-                            state.logger.error(format!("return types do not match"));
+                            state.error(format!("return types do not match"));
                         }
                     },
                     None => {
@@ -61,7 +499,7 @@ impl TypeComputationVisitor for Node<Block> {
         match ret_ty {
             Some(_) => {
                 if last_ty != ret_ty {
-                    state.logger.error("return type does not match type of \
+                    state.error("return type does not match type of \
                         final block statement".to_string());
                 }
             },
@@ -74,16 +512,42 @@ impl TypeComputationVisitor for Node<Block> {
 
 impl TypeComputationVisitor for Node<Statement> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.compute_type(state))
+    }
+}
+
+impl ComputeType for Node<Statement> {
+    fn compute_type(&self, state: &mut State) -> Result {
         let ty = match (&self.item, &self.annotation) {
-            (&Statement::Declare(ref ident, ref expr), &ref annotation) => {
+            (&Statement::Declare(ref ident, ref spec, ref expr), &ref annotation) => {
                 expr.visit(state)?;
-                let ty = expr.annotation.borrow().ty();
-                // update the variable type in scope
+                let mut ty = expr.annotation.borrow().ty();
                 let ident = ident.item.clone();
+                // an explicit type overrides the inferred type, if the value can be stored in it
+                if let Some(ref spec) = *spec {
+                    let scope = annotation.borrow().scope().ok_or(
+                        format!("no scope associated with declaration of {}", ident))?;
+                    let resolved = resolve_type_spec(&*scope.borrow(), spec);
+                    match resolved {
+                        Ok(declared) => {
+                            if ty.is_some() && !check_assignable(expr, &declared) {
+                                state.error(format!("cannot declare variable '{}' of type \
+                                    '{}' with value of type '{}'", ident, declared,
+                                    ty.clone().unwrap()));
+                            }
+                            ty = Some(declared);
+                        },
+                        Err(e) => {
+                            state.error(format!("invalid type for variable '{}': {}",
+                                ident, e));
+                        }
+                    }
+                }
+                // update the variable type in scope
                 if let Some(ref mut scope) = annotation.borrow().scope() {
-                    if let Some(ty) = ty {
+                    if let Some(ref ty) = ty {
                         scope.borrow_mut().define(ident.clone(),
-                            Symbol::variable(ident.clone(), Some(ty)));
+                            Symbol::variable(ident.clone(), Some(ty.clone())));
                     }
                 }
                 ty
@@ -96,44 +560,91 @@ impl TypeComputationVisitor for Node<Statement> {
                     format!("no scope associated with identifier {}", ident))?;
                 let expr_ty = expr_ty.ok_or(
                     format!("type computation for expression failed in assignment of {}", ident))?;
-
-                if let Some(existing) = scope.borrow().resolve(&ident) {
-                    match existing {
-                        Symbol::Variable { ty: existing_ty, .. } => {
-                            if let Some(dest_ty) = existing_ty {
-                                if expr_ty != dest_ty {
-                                    match expr_ty.infer_promotion(dest_ty) {
-                                        Some(promoted) => {
-                                            annotation.borrow_mut().set_promote_type(Some(promoted));
-                                        },
-                                        None => {
-                                            state.logger.error(format!(
-                                                "attempt to change variable type of '{}'",
-                                                ident));
-                                        }
-                                    }
-                                }
-                            } else {
-                                // ident exists in scope but doesn't have a type,
-                                // update it
-                                scope.borrow_mut().define(ident.clone(),
-                                    Symbol::variable(ident.clone(), Some(expr_ty)));
+                check_assignment(&ident, expr, &expr_ty, &scope, state);
+                Some(expr_ty)
+            },
+            (&Statement::SetGlobal(ref ident, ref expr), _) => {
+                expr.visit(state)?;
+                let ident = ident.item.clone();
+                let expr_ty = expr.annotation.borrow().ty().ok_or(
+                    format!("type computation for expression failed in assignment of {}", ident))?;
+                // set statements always refer to global variables, even if shadowed
+                let global = Rc::clone(&state.global);
+                check_assignment(&ident, expr, &expr_ty, &global, state);
+                Some(expr_ty)
+            },
+            (&Statement::Destructure { declare, ref names, ref value }, &ref annotation) => {
+                value.visit(state)?;
+                let scope = annotation.borrow().scope().ok_or(
+                    "no scope associated with destructuring statement".to_string())?;
+                let value_ty = value.annotation.borrow().ty();
+                let elem_tys: Vec<Option<PType>> = match value_ty {
+                    Some(PType::Tuple(ref elem_tys)) if elem_tys.len() == names.len() => {
+                        elem_tys.iter().cloned().map(Some).collect()
+                    },
+                    Some(PType::Tuple(ref elem_tys)) => {
+                        state.error(format!("cannot destructure tuple with {} elements \
+                            into {} variables", elem_tys.len(), names.len()));
+                        vec![None; names.len()]
+                    },
+                    Some(ref ty) => {
+                        state.error(format!("cannot destructure value of non-tuple type \
+                            '{}'", ty));
+                        vec![None; names.len()]
+                    },
+                    None => vec![None; names.len()]
+                };
+                for (name, elem_ty) in names.iter().zip(elem_tys) {
+                    let name = match *name {
+                        Some(ref name) => name.item.clone(),
+                        None => { continue; }
+                    };
+                    if declare {
+                        scope.borrow_mut().define(name.clone(),
+                            Symbol::variable(name.clone(), elem_ty));
+                        continue;
+                    }
+                    let existing = scope.borrow().resolve(&name);
+                    match (existing, elem_ty) {
+                        (Some(Symbol::Variable { ty: Some(var_ty), .. }), Some(elem_ty)) => {
+                            // elements are promoted to the type of the variable if necessary
+                            if elem_ty != var_ty && elem_ty.infer_promotion(var_ty.clone())
+                                    .is_none() {
+                                state.error(format!("cannot assign value of type '{}' \
+                                    to variable '{}' of type '{}'", elem_ty, name, var_ty));
                             }
                         },
-                        Symbol::Function { .. } => {
-                            state.logger.error(format!(
-                                "function '{}' invalid as lvalue", ident));
-                        },
-                        Symbol::BuiltinType { .. } => {
-                            state.logger.error(
-                                format!("attempt to redefine built-in type '{}'", ident));
+                        (Some(Symbol::Variable { .. }), _) | (None, _) => {},
+                        (Some(_), _) => {
+                            state.error(format!("invalid assignment target '{}'", name));
                         }
                     }
-                } else {
-                    state.logger.error(
-                        format!("attempt to assign to undefined variable '{}'", ident));
                 }
-                Some(expr_ty)
+                value_ty
+            },
+            (&Statement::IndexAssign { ref array, ref index, ref value }, _) => {
+                array.visit(state)?;
+                index.visit(state)?;
+                value.visit(state)?;
+                check_index(index, state);
+                if !is_place(array) {
+                    state.error(format!("invalid target for indexed assignment: {}",
+                        array.item));
+                }
+                let array_ty = array.annotation.borrow().ty();
+                match array_ty {
+                    Some(PType::Array(ref elem_ty)) => {
+                        if !check_assignable(value, elem_ty) {
+                            state.error(format!("invalid type for element of array of \
+                                type '[{}]'", elem_ty));
+                        }
+                    },
+                    Some(ref ty) => {
+                        state.error(format!("attempt to index non-array type '{}'", ty));
+                    },
+                    None => {}
+                }
+                Some(PType::Void)
             },
             (&Statement::Expression(ref expr), _) => {
                 expr.visit(state)?;
@@ -143,114 +654,138 @@ impl TypeComputationVisitor for Node<Statement> {
                     ref params }), &ref annotation) => {
                 for param in params.iter() {
                     let param_name = param.item.name.item.clone();
-                    let param_ty = param.item.ty.item.clone();
 
                     let fn_scope = body.annotation.borrow().scope().unwrap();
-                    let pm_ty = if let Some(param_ty_sym) = fn_scope.borrow().resolve(&param_ty) {
-                        match param_ty_sym {
-                            Symbol::Variable { .. } => {
-                                state.logger.error(format!("variable '{}' not valid as type \
-                                    for parameter '{}'", param_ty, param_name));
-                                None
-                            },
-                            Symbol::Function { .. } => {
-                                state.logger.error(format!("function '{}' not valid as type \
-                                    for parameter '{}'", param_ty, param_name));
-                                None
-                            },
-                            Symbol::BuiltinType { ty, .. } => {
-                                Some(ty)
-                            }
+                    let pm_ty = match resolve_type_spec(&*fn_scope.borrow(), &param.item.ty) {
+                        Ok(ty) => Some(ty),
+                        Err(e) => {
+                            state.within(param.span(), |state| {
+                                state.error(format!("{} for parameter '{}'", e, param_name));
+                            });
+                            None
                         }
-                    } else {
-                        state.logger.error(format!("type '{}' unknown for parameter '{}'",
-                            param_ty, param_name));
-                        None
                     };
                     // re-declare parameter as a variable with computed type in the funciton scope
                     fn_scope.borrow_mut().define(param_name.clone(),
-                        Symbol::variable(param_name.clone(), pm_ty));
+                        Symbol::variable(param_name.clone(), pm_ty.clone()));
                     // set the parameter type
                     param.annotation.borrow_mut().set_type(pm_ty);
                 }
 
-                body.visit(state)?;
-                let body_ty = body.annotation.borrow_mut().ty();
                 // lookup the declared return type
                 let name = name.item.clone();
                 let scope = annotation.borrow().scope().ok_or(
                     format!("no scope associated with function {}", name))?;
-                let body_ty = body_ty.ok_or(
-                    format!("type computation for body failed in function {}", name))?;
-                let ret_ty = &ret_type.item;
 
-                let r_ty = if let Some(ret_type_sym) = scope.borrow().resolve(&ret_ty) {
-                    match ret_type_sym {
-                        Symbol::Variable { .. } => {
-                            state.logger.error(format!("variable '{}' not valid as return type \
-                                for function '{}'", ret_ty, name));
-                            None
-                        },
-                        Symbol::Function { .. } => {
-                            state.logger.error(format!("function '{}' not valid as return type \
-                                for function '{}'", ret_ty, name));
-                            None
-                        },
-                        Symbol::BuiltinType { ty, .. } => {
-                            // return type symbol exists, and it's a valid type. check if the
-                            // symbol already exists, and update the symbol in scope if not
-
-                            if let Some(existing) = scope.borrow().resolve(&name) {
-                                match existing {
-                                    Symbol::Variable { .. } => {
-                                        return Err(format!("symbol mismatch for '{}': expected \
-                                            Function, found Variable", name));
-                                    },
-                                    Symbol::BuiltinType { .. } => {
-                                        return Err(format!("symbol mismatch for '{}': expected \
-                                            Function, found BuiltinType", name));
+                let declared_ty = match resolve_type_spec(&*scope.borrow(), ret_type) {
+                    Ok(ty) => Some(ty),
+                    Err(e) => {
+                        state.error(format!("{} for return type of function '{}'", e,
+                            name));
+                        None
+                    }
+                };
+                let r_ty = if let Some(ty) = declared_ty {
+                    // return type is a valid type. check if the symbol already exists, and
+                    // update the symbol in scope if not
+                    let existing: Option<Symbol> = scope.borrow().resolve(&name);
+                    // for overloaded functions, the existing definition is the one with the same
+                    // parameter types
+                    if let Some(existing) = existing.and_then(|sym| sym.overload(params).cloned()
+                            .or(Some(sym))) {
+                        match existing {
+                            Symbol::Variable { .. } => {
+                                return Err(format!("symbol mismatch for '{}': expected \
+                                    Function, found Variable", name));
+                            },
+                            Symbol::BuiltinType { .. } => {
+                                return Err(format!("symbol mismatch for '{}': expected \
+                                    Function, found BuiltinType", name));
+                            },
+                            Symbol::Constant { .. } => {
+                                return Err(format!("symbol mismatch for '{}': expected \
+                                    Function, found Constant", name));
+                            },
+                            Symbol::Struct { .. } => {
+                                return Err(format!("symbol mismatch for '{}': expected \
+                                    Function, found Struct", name));
+                            },
+                            Symbol::Overloaded { .. } => {
+                                return Err(format!("function '{}' with these parameter types \
+                                    does not exist in scope", name));
+                            },
+                            Symbol::Function { ret_ty: ref existing_ret_ty, .. } => {
+                                if let Some(ref existing_ret_ty) = *existing_ret_ty {
+                                    if *existing_ret_ty != ty {
+                                        state.error(format!(
+                                            "attempt to change return type of '{}'", name));
                                     }
-                                    Symbol::Function { ret_ty: ref existing_ret_ty, .. } => {
-                                        if let Some(existing_ret_ty) = *existing_ret_ty {
-                                            if existing_ret_ty != ty {
-                                                state.logger.error(format!(
-                                                    "attempt to change return type of '{}'",
-                                                    name));
-                                            }
-                                            Some(ty)
-                                        } else {
-                                            // return type not previously specified, update it
-                                            // with computed type
-                                            Some(ty)
-                                        }
-                                    },
+                                    Some(ty)
+                                } else {
+                                    // return type not previously specified, update it
+                                    // with computed type
+                                    Some(ty)
                                 }
-                            } else {
-                                // function does not exist to update, failure mode
-                                return Err(format!("function '{}' does not exist in scope", name));
-                            }
+                            },
                         }
+                    } else {
+                        // function does not exist to update, failure mode
+                        return Err(format!("function '{}' does not exist in scope", name));
                     }
                 } else {
-                    state.logger.error(format!("return type '{}' for function '{}' unknown",
-                        ret_ty, name));
                     None
                 };
-                scope.borrow_mut().define(name.clone(),
+                // update the function symbol before computing the body type, so that recursive
+                // calls have a known return type
+                define_function(&mut *state.global.borrow_mut(), name.clone(),
                         Symbol::function(name.clone(), r_ty,
                             body.clone(), params.clone()));
 
+                body.visit(state)?;
+                let body_ty = body.annotation.borrow_mut().ty().ok_or(
+                    format!("type computation for body failed in function {}", name))?;
                 Some(body_ty)
             },
-            (&Statement::Return(ref expr), _) | (&Statement::Break(ref expr), _) => {
+            (&Statement::StructDefine(StructDef { ref name, ref fields }), &ref annotation) => {
+                let scope = annotation.borrow().scope().ok_or(
+                    format!("no scope associated with struct {}", name.item))?;
+                for field in fields.iter() {
+                    let field_ty = match resolve_type_spec(&*scope.borrow(), &field.item.ty) {
+                        Ok(PType::Struct(ref struct_name)) if *struct_name == name.item => {
+                            state.error(format!("struct '{}' cannot contain itself \
+                                (field '{}')", name.item, field.item.name.item));
+                            None
+                        },
+                        Ok(ty) => Some(ty),
+                        Err(e) => {
+                            state.within(field.span(), |state| {
+                                state.error(format!("{} for field '{}' of struct '{}'", e,
+                                    field.item.name.item, name.item));
+                            });
+                            None
+                        }
+                    };
+                    // field annotations are shared with the struct symbol
+                    field.annotation.borrow_mut().set_type(field_ty);
+                }
+                Some(PType::Void)
+            },
+            (&Statement::Return(ref expr), _) | (&Statement::Break(_, ref expr), _) => {
                 expr.visit(state)?;
                 expr.annotation.borrow().ty()
             },
+            (&Statement::Continue(_), _) => {
+                Some(PType::Void)
+            },
             (&Statement::Print(ref exprs), _)  => {
                 for expr in exprs {
                     expr.visit(state)?;
                 }
                 Some(PType::Void)
+            },
+            (&Statement::Import(_), _) => {
+                // imported files have their types computed when loaded
+                Some(PType::Void)
             }
         };
         self.annotation.borrow_mut().set_type(ty);
@@ -260,6 +795,12 @@ impl TypeComputationVisitor for Node<Statement> {
 
 impl TypeComputationVisitor for Node<Expression> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.compute_type(state))
+    }
+}
+
+impl ComputeType for Node<Expression> {
+    fn compute_type(&self, state: &mut State) -> Result {
         // borrow the scope
         let scope = match self.annotation.borrow().scope() {
             Some(ref s) => Rc::clone(&s),
@@ -281,9 +822,28 @@ impl TypeComputationVisitor for Node<Expression> {
                 match scope.borrow().resolve(&node.item) {
                     Some(ref sym) => {
                         match *sym {
-                            Symbol::Variable { ty, .. } => { ty },
-                            Symbol::Function { ret_ty, .. } => ret_ty,
-                            Symbol::BuiltinType { ty, .. } => Some(ty),
+                            Symbol::Variable { ref ty, .. } => { ty.clone() },
+                            Symbol::Constant { ref value, .. } => Some(PType::from(value)),
+                            Symbol::Function { body: FunctionBody::Ast(_), ref params,
+                                    ref ret_ty, .. } => {
+                                function_type(params, ret_ty.clone())
+                            },
+                            Symbol::Function { ref name, .. } => {
+                                state.error(format!("built-in function '{}' cannot be \
+                                    used as a value", name));
+                                None
+                            },
+                            Symbol::Overloaded { ref name, .. } => {
+                                state.error(format!("overloaded function '{}' cannot be \
+                                    used as a value", name));
+                                None
+                            },
+                            Symbol::BuiltinType { ref ty, .. } => Some(ty.clone()),
+                            Symbol::Struct { ref name, .. } => {
+                                state.error(format!("struct '{}' not valid as a value",
+                                    name));
+                                None
+                            }
                         }
                     },
                     None => None
@@ -292,9 +852,9 @@ impl TypeComputationVisitor for Node<Expression> {
             (&Expression::Infix { ref left, ref right, ref op }, _) => {
                 left.visit(state)?;
                 right.visit(state)?;
-                let tleft = left.annotation.borrow().ty().unwrap();
-                let tright = right.annotation.borrow().ty().unwrap();
-                match op.infer_types(tleft, tright) {
+                let tleft = visited_ty!(left);
+                let tright = visited_ty!(right);
+                match op.infer_types(tleft.clone(), tright.clone()) {
                     Some(BinaryOpTypes { result: ty, left: promo_left, right: promo_right }) => {
                         left.annotation.borrow_mut().set_promote_type(
                             tleft.infer_promotion(promo_left));
@@ -303,7 +863,7 @@ impl TypeComputationVisitor for Node<Expression> {
                         Some(ty)
                     },
                     None => {
-                        state.logger.error(format!("incompatible types for {}: {}, {}",
+                        state.error(format!("incompatible types for {}: {}, {}",
                             op, left.item, right.item));
                         None
                     }
@@ -312,15 +872,15 @@ impl TypeComputationVisitor for Node<Expression> {
             },
             (&Expression::Prefix { ref right, ref op }, _) => {
                 right.visit(state)?;
-                let tright = right.annotation.borrow().ty().unwrap();
-                match op.infer_types(tright) {
+                let tright = visited_ty!(right);
+                match op.infer_types(tright.clone()) {
                     Some(UnaryOpTypes { result: result_ty, operand: promo_ty }) => {
                         right.annotation.borrow_mut().set_promote_type(
                             tright.infer_promotion(promo_ty));
                         Some(result_ty)
                     },
                     None => {
-                        state.logger.error(format!("incompatible type for {}: {}", op,
+                        state.error(format!("incompatible type for {}: {}", op,
                             right.item));
                         None
                     }
@@ -328,94 +888,343 @@ impl TypeComputationVisitor for Node<Expression> {
             },
             (&Expression::Postfix { ref left, ref op }, _) => {
                 left.visit(state)?;
-                let tleft = left.annotation.borrow().ty().unwrap();
-                match op.infer_types(tleft) {
+                let tleft = visited_ty!(left);
+                match op.infer_types(tleft.clone()) {
                     Some(UnaryOpTypes { result: result_ty, operand: promo_ty }) => {
                         left.annotation.borrow_mut().set_promote_type(
                             tleft.infer_promotion(promo_ty));
                         Some(result_ty)
                     },
                     None => {
-                        state.logger.error(format!("incompatible type for {}: {}", op,
+                        state.error(format!("incompatible type for {}: {}", op,
                             left.item));
                         None
                     }
                 }
             },
+            (&Expression::Cast { ref expr, ref ty }, _) => {
+                expr.visit(state)?;
+                let src_ty = expr.annotation.borrow().ty();
+                match resolve_type_spec(&*scope.borrow(), ty) {
+                    Ok(dest_ty) => {
+                        if let Some(src_ty) = src_ty {
+                            let valid = src_ty == dest_ty || match (&src_ty, &dest_ty) {
+                                (&PType::Int, &PType::Float) | (&PType::Float, &PType::Int)
+                                    | (&PType::Int, &PType::Complex)
+                                    | (&PType::Float, &PType::Complex) => true,
+                                _ => false
+                            };
+                            if !valid {
+                                state.error(format!("invalid cast from '{}' to '{}'",
+                                    src_ty, dest_ty));
+                            }
+                        }
+                        Some(dest_ty)
+                    },
+                    Err(e) => {
+                        state.error(format!("invalid cast target: {}", e));
+                        None
+                    }
+                }
+            },
             (&Expression::Block(ref block), _) => {
                 block.visit(state)?;
                 block.annotation.borrow().ty()
             },
-            (&Expression::FnCall { name: ref ident, ref args }, _) => {
+            (&Expression::StructLiteral { ref name, ref fields }, _) => {
+                for &(_, ref expr) in fields.iter() {
+                    expr.visit(state)?;
+                }
+                let name = &name.item;
+                let struct_fields = struct_fields(&*scope.borrow(), name).ok_or(
+                    format!("struct '{}' does not exist", name))?;
+                let mut valid = true;
+                // every provided field must exist in the struct definition
+                for &(ref field_name, _) in fields.iter() {
+                    if !struct_fields.iter().any(|f| f.item.name.item == field_name.item) {
+                        state.error(format!("struct '{}' has no field named '{}'",
+                            name, field_name.item));
+                        valid = false;
+                    }
+                }
+                // every field in the struct definition must be provided exactly once
+                for field in struct_fields.iter() {
+                    let field_name = &field.item.name.item;
+                    let provided = fields.iter().filter(|&&(ref f, _)| f.item == *field_name)
+                        .collect::<Vec<_>>();
+                    if provided.len() != 1 {
+                        state.error(format!("field '{}' of struct '{}' must be specified \
+                            exactly once, found {}", field_name, name, provided.len()));
+                        valid = false;
+                        continue;
+                    }
+                    let expr = &provided[0].1;
+                    let (expr_ty, field_ty) = match (expr.annotation.borrow().ty(),
+                            field.annotation.borrow().ty()) {
+                        (Some(expr_ty), Some(field_ty)) => (expr_ty, field_ty),
+                        _ => {
+                            valid = false;
+                            continue;
+                        }
+                    };
+                    if expr_ty != field_ty {
+                        match expr_ty.infer_promotion(field_ty.clone()) {
+                            Some(promoted) => {
+                                expr.annotation.borrow_mut().set_promote_type(Some(promoted));
+                            },
+                            None => {
+                                state.error(format!("invalid type for field '{}' of \
+                                    struct '{}': expected '{}', found '{}'", field_name, name,
+                                    field_ty, expr_ty));
+                                valid = false;
+                            }
+                        }
+                    }
+                }
+                if valid { Some(PType::Struct(name.clone())) } else { None }
+            },
+            (&Expression::Member { ref object, ref field }, _) => {
+                object.visit(state)?;
+                match object.annotation.borrow().ty() {
+                    Some(PType::Struct(ref struct_name)) => {
+                        let struct_fields = struct_fields(&*scope.borrow(), struct_name).ok_or(
+                            format!("struct '{}' does not exist", struct_name))?;
+                        match struct_fields.iter().find(|f| f.item.name.item == field.item) {
+                            Some(f) => f.annotation.borrow().ty(),
+                            None => {
+                                state.error(format!("struct '{}' has no field named '{}'",
+                                    struct_name, field.item));
+                                None
+                            }
+                        }
+                    },
+                    Some(ty) => {
+                        state.error(format!("attempt to access field '{}' of non-struct \
+                            type '{}'", field.item, ty));
+                        None
+                    },
+                    None => None
+                }
+            },
+            (&Expression::Closure { ref params, ref ret_type, ref body }, &ref annotation) => {
+                let closure_scope = body.annotation.borrow().scope().ok_or(
+                    "no scope associated with closure".to_string())?;
+                // captured variables have the type of the variable they capture
+                for ident in annotation.borrow().captures() {
+                    let ty = match scope.borrow().resolve(ident) {
+                        Some(Symbol::Variable { ty, .. }) => ty,
+                        _ => None
+                    };
+                    closure_scope.borrow_mut().define(ident.clone(),
+                        Symbol::variable(ident.clone(), ty));
+                }
+                for param in params.iter() {
+                    let param_name = param.item.name.item.clone();
+                    let pm_ty = match resolve_type_spec(&*closure_scope.borrow(), &param.item.ty) {
+                        Ok(ty) => Some(ty),
+                        Err(e) => {
+                            state.within(param.span(), |state| {
+                                state.error(format!("{} for closure parameter '{}'", e,
+                                    param_name));
+                            });
+                            None
+                        }
+                    };
+                    closure_scope.borrow_mut().define(param_name.clone(),
+                        Symbol::variable(param_name.clone(), pm_ty.clone()));
+                    param.annotation.borrow_mut().set_type(pm_ty);
+                }
+                let ret_ty = match resolve_type_spec(&*scope.borrow(), ret_type) {
+                    Ok(ty) => Some(ty),
+                    Err(e) => {
+                        state.error(format!("{} for return type of closure", e));
+                        None
+                    }
+                };
+                body.visit(state)?;
+                // the value of the body is promoted to the return type if necessary
+                let body_ty = body.annotation.borrow().ty();
+                if let (Some(body_ty), Some(ret_ty)) = (body_ty, ret_ty.clone()) {
+                    if body_ty != ret_ty {
+                        match body_ty.infer_promotion(ret_ty.clone()) {
+                            Some(promoted) => {
+                                body.annotation.borrow_mut().set_promote_type(Some(promoted));
+                            },
+                            None => {
+                                state.error(format!("closure body of type '{}' does not \
+                                    match return type '{}'", body_ty, ret_ty));
+                            }
+                        }
+                    }
+                }
+                function_type(params, ret_ty)
+            },
+            (&Expression::Tuple(ref elems), _) => {
+                let mut elem_tys = vec![];
+                for elem in elems.iter() {
+                    elem.visit(state)?;
+                    elem_tys.push(elem.annotation.borrow().ty());
+                }
+                elem_tys.into_iter().collect::<Option<Vec<PType>>>().map(PType::Tuple)
+            },
+            (&Expression::FnCall { name: ref ident, ref args }, &ref annotation) => {
                 for ref arg in args.iter() {
                     arg.visit(state)?;
                 }
                 let id = &ident.item;
                 match scope.borrow().resolve(&id) {
-                    Some(Symbol::Variable { .. }) => {
-                        state.logger.error(format!(
-                            "attempt to call function on variable {}", id));
-                        None
+                    Some(Symbol::Function { body: FunctionBody::Intrinsic(intrinsic), .. }) => {
+                        intrinsic_type(intrinsic, args, state)
                     },
-                    Some(Symbol::Function { ret_ty, ref name, ref params, .. }) => {
-                        if args.len() != params.len() {
-                            state.logger.error(format!("function '{}' expects {} arguments,
-                                {} found", name, params.len(), args.len()));
+                    Some(Symbol::Variable { ty: Some(PType::Function(param_tys, ret_ty)), .. }) => {
+                        if args.len() != param_tys.len() {
+                            state.error(format!("function '{}' expects {} arguments, \
+                                {} found", id, param_tys.len(), args.len()));
                             None
                         } else {
-                            // check the parameter types
-                            for (ref param, ref arg) in params.iter().zip(args) {
-                                let arg_ty = arg.annotation.borrow().ty().unwrap();
-                                let param_ty = param.annotation.borrow().ty().unwrap();
-
-                                if param_ty != arg_ty {
-                                    match arg_ty.infer_promotion(param_ty) {
-                                        Some(promoted) => {
-                                            arg.annotation.borrow_mut().set_promote_type(
-                                                Some(promoted));
-                                        },
-                                        None => {
-                                            state.logger.error(format!(
-                                                "invalid argument type for parameter '{}' \
-                                                of function '{}': expected '{}', found '{}'",
-                                                param.item.name.item, name, param_ty, arg_ty));
-                                        }
-                                    }
+                            for (i, (param_ty, arg)) in param_tys.iter().zip(args).enumerate() {
+                                if !check_assignable(arg, param_ty) {
+                                    state.error(format!("invalid type for argument {} of \
+                                        function '{}': expected '{}'", i + 1, id, param_ty));
                                 }
                             }
-                            if ret_ty.is_none() {
-                                state.logger.error(format!(
-                                    "function '{}' does not have a valid return type", name));
-                            }
-                            ret_ty
+                            Some(*ret_ty)
+                        }
+                    },
+                    Some(Symbol::Variable { ty: Some(ty), .. }) => {
+                        state.error(format!(
+                            "attempt to call variable '{}' of non-function type '{}'", id, ty));
+                        None
+                    },
+                    Some(Symbol::Variable { ty: None, .. }) => None,
+                    Some(Symbol::Function { ret_ty, ref name, ref params, .. }) => {
+                        function_call_type(name, ret_ty, params, args, state)
+                    },
+                    Some(Symbol::Overloaded { ref name, ref functions }) => {
+                        match select_overload(name, functions, args, &*scope.borrow(), state) {
+                            Some(index) => {
+                                annotation.borrow_mut().set_overload(Some(index));
+                                match functions[index] {
+                                    Symbol::Function { ref ret_ty, ref params, .. } => {
+                                        function_call_type(name, ret_ty.clone(), params, args,
+                                            state)
+                                    },
+                                    _ => None
+                                }
+                            },
+                            None => None
                         }
                     },
                     Some(Symbol::BuiltinType { .. }) => {
-                        state.logger.error(format!(
+                        state.error(format!(
                             "attempt to call function on built-in type {}", id));
                         None
                     },
+                    Some(Symbol::Constant { .. }) => {
+                        state.error(format!(
+                            "attempt to call constant '{}' as function", id));
+                        None
+                    },
+                    Some(Symbol::Struct { .. }) => {
+                        state.error(format!(
+                            "attempt to call function on struct {}", id));
+                        None
+                    },
                     None => {
                         return Err(format!("function '{}' does not exist", id));
                     }
                 }
             },
+            (&Expression::Array(ref elems), _) => {
+                for elem in elems.iter() {
+                    elem.visit(state)?;
+                }
+                // find the element type that all elements can be promoted to
+                let mut elem_ty: Option<PType> = None;
+                let mut valid = true;
+                for elem in elems.iter() {
+                    let ty = match elem.annotation.borrow().ty() {
+                        Some(ty) => ty,
+                        None => {
+                            valid = false;
+                            continue;
+                        }
+                    };
+                    elem_ty = match elem_ty {
+                        None => Some(ty),
+                        Some(current) => {
+                            if current == ty || ty.infer_promotion(current.clone()).is_some() {
+                                Some(current)
+                            } else if current.infer_promotion(ty.clone()).is_some() {
+                                Some(ty)
+                            } else {
+                                state.error(format!("incompatible array element types: \
+                                    '{}', '{}'", current, ty));
+                                valid = false;
+                                Some(current)
+                            }
+                        }
+                    };
+                }
+                match elem_ty {
+                    Some(elem_ty) => {
+                        for elem in elems.iter() {
+                            check_assignable(elem, &elem_ty);
+                        }
+                        if valid { Some(PType::Array(Box::new(elem_ty))) } else { None }
+                    },
+                    None => {
+                        if valid {
+                            state.error("unable to infer element type of empty array; \
+                                use [<value>; 0] instead".to_string());
+                        }
+                        None
+                    }
+                }
+            },
+            (&Expression::ArrayRepeat { ref value, ref count }, _) => {
+                value.visit(state)?;
+                count.visit(state)?;
+                match count.annotation.borrow().ty() {
+                    Some(PType::Int) | None => {},
+                    Some(ty) => {
+                        state.error(format!("array length must be of type 'int', found \
+                            '{}'", ty));
+                    }
+                }
+                let value_ty = value.annotation.borrow().ty();
+                value_ty.map(|ty| PType::Array(Box::new(ty)))
+            },
+            (&Expression::Index { ref object, ref index }, _) => {
+                object.visit(state)?;
+                index.visit(state)?;
+                check_index(index, state);
+                let object_ty = object.annotation.borrow().ty();
+                match object_ty {
+                    Some(PType::Array(elem_ty)) => Some(*elem_ty),
+                    Some(ty) => {
+                        state.error(format!("attempt to index non-array type '{}'", ty));
+                        None
+                    },
+                    None => None
+                }
+            },
             (&Expression::IfElse { ref cond, ref if_block, ref else_block }, _) => {
                 cond.visit(state)?;
                 // check type of conditional
-                let tcond = cond.annotation.borrow().ty().unwrap();
+                let tcond = visited_ty!(cond);
                 if tcond == PType::Boolean {
                     if_block.visit(state)?;
                     if let Some(ref else_block) = *else_block {
                         else_block.visit(state)?;
 
-                        let tif = if_block.annotation.borrow().ty().unwrap();
-                        let telse = else_block.annotation.borrow().ty().unwrap();
+                        let tif = visited_ty!(if_block);
+                        let telse = visited_ty!(else_block);
 
                         if tif == telse {
                             Some(tif)
                         } else {
-                            state.logger.error(format!(
+                            state.error(format!(
                                 "invalid if-else construct: if block returns type '{}', \
                                 else block returns type '{}'", tif, telse));
                             Some(PType::Void)
@@ -424,14 +1233,82 @@ impl TypeComputationVisitor for Node<Expression> {
                         Some(PType::Void)
                     }
                 } else {
-                    state.logger.error(format!(
+                    state.error(format!(
                         "conditional expression must be boolean, found type '{}'", tcond));
                     Some(PType::Void)
                 }
             },
-            (&Expression::Loop { ref variant, ref set, ref body }, _) => {
+            (&Expression::Match { ref value, ref arms }, _) => {
+                value.visit(state)?;
+                let tvalue = visited_ty!(value);
+                if tvalue != PType::Int && tvalue != PType::Float {
+                    state.error(format!(
+                        "match value must be int or float, found type '{}'", tvalue));
+                }
+
+                let mut has_wildcard = false;
+                for arm in arms.iter() {
+                    if has_wildcard {
+                        state.warning(format!("unreachable match arm: '{}'",
+                            arm.pattern));
+                    }
+                    match arm.pattern {
+                        Pattern::Value(ref lit) => {
+                            check_pattern_literal(lit, &tvalue, state);
+                        },
+                        Pattern::Interval { ref start, ref end, .. } => {
+                            check_pattern_literal(start, &tvalue, state);
+                            check_pattern_literal(end, &tvalue, state);
+                        },
+                        Pattern::Wildcard => {
+                            has_wildcard = true;
+                        }
+                    }
+                    arm.body.visit(state)?;
+                }
+
+                // make sure every possible value is matched by some arm
+                if !has_wildcard {
+                    if tvalue == PType::Float {
+                        state.error(format!("non-exhaustive match: matches on float \
+                            values require a wildcard ('_') arm"));
+                    } else if tvalue == PType::Int {
+                        if let Some((low, high)) = uncovered_ints(arms) {
+                            state.error(format!("non-exhaustive match: values in \
+                                [{}, {}] are not covered", low, high));
+                        }
+                    }
+                }
+
+                // find the type that all arms can be promoted to
+                let mut arm_ty: Option<PType> = None;
+                for arm in arms.iter() {
+                    let ty = visited_ty!(arm.body);
+                    arm_ty = match arm_ty {
+                        None => Some(ty),
+                        Some(current) => {
+                            if current == ty || ty.infer_promotion(current.clone()).is_some() {
+                                Some(current)
+                            } else if current.infer_promotion(ty.clone()).is_some() {
+                                Some(ty)
+                            } else {
+                                state.error(format!("incompatible match arm types: \
+                                    '{}', '{}'", current, ty));
+                                Some(current)
+                            }
+                        }
+                    };
+                }
+                if let Some(ref arm_ty) = arm_ty {
+                    for arm in arms.iter() {
+                        check_assignable(&arm.body, arm_ty);
+                    }
+                }
+                arm_ty
+            },
+            (&Expression::Loop { ref variant, ref set, ref body, .. }, _) => {
                 set.visit(state)?;
-                let var_ty = set.annotation.borrow().ty().unwrap();
+                let var_ty = visited_ty!(set);
                 match *variant {
                     Some(ref var) => {
                         body.annotation.borrow_mut().define(var.item.clone(),
@@ -440,7 +1317,39 @@ impl TypeComputationVisitor for Node<Expression> {
                     None => {}
                 }
                 body.visit(state)?;
-                Some(body.annotation.borrow().ty().unwrap())
+                Some(visited_ty!(body))
+            },
+            (&Expression::WhileLoop { ref cond, ref body, .. }, _) => {
+                cond.visit(state)?;
+                let tcond = visited_ty!(cond);
+                if tcond != PType::Boolean {
+                    state.error(format!(
+                        "loop condition must be boolean, found type '{}'", tcond));
+                }
+                body.visit(state)?;
+                Some(visited_ty!(body))
+            },
+            (&Expression::PixelLoop { ref row, ref col, ref body, .. }, _) => {
+                for var in &[row, col] {
+                    body.annotation.borrow_mut().define(var.item.clone(),
+                        Symbol::variable(var.item.clone(), Some(PType::Int)));
+                }
+                body.visit(state)?;
+                // the body computes the value of each pixel
+                let body_ty = body.annotation.borrow().ty();
+                match body_ty {
+                    Some(PType::Float) => {},
+                    Some(PType::Int) => {
+                        body.annotation.borrow_mut().set_promote_type(Some(PType::Float));
+                    },
+                    Some(ty) => {
+                        state.error(format!(
+                            "pixel loop body must evaluate to a float value, found type '{}'",
+                            ty));
+                    },
+                    None => {}
+                }
+                Some(PType::Void)
             }
         };
         self.annotation.borrow_mut().set_type(ty);
@@ -450,25 +1359,101 @@ impl TypeComputationVisitor for Node<Expression> {
 
 impl TypeComputationVisitor for Node<Set> {
     fn visit(&self, state: &mut State) -> Result {
+        state.within(self.span(), |state| self.compute_type(state))
+    }
+}
+
+impl ComputeType for Node<Set> {
+    fn compute_type(&self, state: &mut State) -> Result {
         let ty = match self.item {
             Set::Interval { ref start, ref end, ref step, .. } => {
                 start.visit(state)?;
                 end.visit(state)?;
                 step.visit(state)?;
 
-                //TODO: handle promotion from int to float
                 let (tstart, tend, tstep) = (
-                    start.annotation.borrow().ty().unwrap(),
-                    end.annotation.borrow().ty().unwrap(),
-                    step.annotation.borrow().ty().unwrap(),
+                    visited_ty!(start),
+                    visited_ty!(end),
+                    visited_ty!(step),
                 );
 
-                if tstart == tend && tstart == tstep {
-                    Some(tstart)
+                // the interval type is the type both 'start' and 'end' can be promoted to
+                let ty = if tstart == tend || tend.infer_promotion(tstart.clone()).is_some() {
+                    tstart.clone()
+                } else if tstart.infer_promotion(tend.clone()).is_some() {
+                    tend.clone()
                 } else {
-                    state.logger.error(format!("'start' ({}), 'end' ({}), and 'step' ({}) values \
-                        are required to be of same type", tstart, tend, tstep));
-                    None
+                    state.error(format!("'start' ({}) and 'end' ({}) values are required \
+                        to be of same type", tstart, tend));
+                    tstart.clone()
+                };
+                if ty != PType::Int && ty != PType::Float {
+                    state.error(format!("interval values must be numeric, found type '{}'",
+                        ty));
+                } else if !check_assignable(step, &ty) {
+                    state.error(format!("'step' ({}) does not match interval type ({})",
+                        tstep, ty));
+                } else if is_zero_literal(step) {
+                    state.error(format!("interval step cannot be zero"));
+                }
+                check_assignable(start, &ty);
+                check_assignable(end, &ty);
+                Some(ty)
+            },
+            Set::Elements(ref elems) => {
+                // sets have numeric elements, which are all floats if any of them is a float
+                let mut ty = PType::Int;
+                for elem in elems.iter() {
+                    elem.visit(state)?;
+                    match elem.annotation.borrow().ty() {
+                        Some(PType::Int) | None => {},
+                        Some(PType::Float) => { ty = PType::Float; },
+                        Some(elem_ty) => {
+                            state.error(format!("set elements must be numeric, found \
+                                type '{}'", elem_ty));
+                        }
+                    }
+                }
+                for elem in elems.iter() {
+                    check_assignable(elem, &ty);
+                }
+                Some(ty)
+            },
+            Set::Builder { ref var, ref source, ref cond, .. } => {
+                source.visit(state)?;
+                let ty = source.annotation.borrow().ty();
+                if let Some(ref cond) = *cond {
+                    cond.annotation.borrow_mut().define(var.item.clone(),
+                        Symbol::variable(var.item.clone(), ty.clone()));
+                    cond.visit(state)?;
+                    match cond.annotation.borrow().ty() {
+                        Some(PType::Boolean) | None => {},
+                        Some(cond_ty) => {
+                            state.error(format!("set-builder condition must be boolean, \
+                                found type '{}'", cond_ty));
+                        }
+                    }
+                }
+                ty
+            },
+            Set::Operation { op, ref left, ref right } => {
+                left.visit(state)?;
+                right.visit(state)?;
+                let tleft = visited_ty!(left);
+                let tright = visited_ty!(right);
+                // the elements of one set are promoted to the element type of the other if needed
+                if tleft == tright {
+                    Some(tleft)
+                } else if tleft.infer_promotion(tright.clone()).is_some() {
+                    left.annotation.borrow_mut().set_promote_type(Some(tright.clone()));
+                    Some(tright)
+                } else if tright.infer_promotion(tleft.clone()).is_some() {
+                    right.annotation.borrow_mut().set_promote_type(Some(tleft.clone()));
+                    Some(tleft)
+                } else {
+                    state.error(format!("set operation '{}' on sets with elements of \
+                        type '{}' and '{}'", op, tleft, tright));
+                    Some(tleft)
                 }
             }
         };
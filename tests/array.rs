@@ -0,0 +1,111 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_translated_output};
+
+use piske::value::Value;
+
+#[test]
+fn test_array_literal() {
+    expect_prog("[1, 2, 3]", Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]));
+    // elements are promoted to a common type
+    expect_prog("[1, 2.5]", Value::Array(vec![Value::Float(1.0), Value::Float(2.5)]));
+    expect_prog("[0; 3]", Value::Array(vec![Value::Int(0), Value::Int(0), Value::Int(0)]));
+}
+
+#[test]
+fn test_array_index() {
+    let prog = r#"
+let a = [1, 2, 3];
+let i = 1;
+a[i + 1]
+    "#;
+    expect_prog(prog, Value::Int(3));
+
+    let prog = r#"
+let grid = [[1, 2], [3, 4]];
+grid[1][0]
+    "#;
+    expect_prog(prog, Value::Int(3));
+}
+
+#[test]
+fn test_array_index_assign() {
+    let prog = r#"
+let a = [1.0, 2.0, 3.0];
+a[0] = 5;
+a[2] = a[0] + a[1];
+a
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(5.0), Value::Float(2.0),
+        Value::Float(7.0)]));
+
+    let prog = r#"
+let grid = [[0; 2]; 2];
+grid[1][0] = 4;
+grid
+    "#;
+    expect_prog(prog, Value::Array(vec![
+        Value::Array(vec![Value::Int(0), Value::Int(0)]),
+        Value::Array(vec![Value::Int(4), Value::Int(0)]),
+    ]));
+}
+
+#[test]
+fn test_array_len_push() {
+    let prog = r#"
+let squares = [0; 0];
+iterate i = [1, 4] {
+    push(squares, i * i);
+}
+len(squares)
+    "#;
+    expect_prog(prog, Value::Int(4));
+
+    let prog = r#"
+struct Palette { stops: [float] }
+let palette = Palette { stops: [0.0, 0.5] };
+push(palette.stops, 1);
+palette.stops
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(0.0), Value::Float(0.5),
+        Value::Float(1.0)]));
+}
+
+#[test]
+fn test_array_function() {
+    let prog = r#"
+fn total(values: [int]) -> int {
+    let sum = 0;
+    iterate i = [0, len(values)) {
+        sum = sum + values[i];
+    }
+    sum
+}
+total([1, 2, 3, 4])
+    "#;
+    expect_prog(prog, Value::Int(10));
+}
+
+#[test]
+fn test_array_transpile() {
+    let prog = r#"
+fn total(values: [int]) -> int {
+    let sum = 0;
+    iterate i = [0, len(values)) {
+        sum = sum + values[i];
+        sum
+    }
+}
+let squares = [0; 0];
+iterate i = [1, 4] {
+    push(squares, i * i);
+}
+squares[0] = 2;
+print total(squares), " ", squares;
+let scales = [1.5, 2, 3];
+print scales, " ", str(scales[1]);
+    "#;
+    expect_translated_output("array", prog);
+}
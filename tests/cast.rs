@@ -0,0 +1,65 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_typed_declare() {
+    expect_prog("let x: float = 1; x", Value::Float(1.0));
+    expect_prog("let z: complex = 2.5; z", Value::Complex(2.5, 0.0));
+    expect_prog("let a: [int] = [1, 2]; a", Value::Array(vec![Value::Int(1), Value::Int(2)]));
+
+    // the declared type is kept for later assignments
+    let prog = r#"
+let total: float = 0;
+iterate i = [1, 4] {
+    total = total + i * 0.5;
+}
+total
+    "#;
+    expect_prog(prog, Value::Float(5.0));
+}
+
+#[test]
+fn test_assign_promotion() {
+    expect_prog("let x = 1.5; x = 2; x", Value::Float(2.0));
+}
+
+#[test]
+fn test_cast() {
+    expect_prog("7.9 as int", Value::Int(7));
+    // float to int casts truncate toward zero
+    expect_prog("-7.9 as int", Value::Int(-7));
+    expect_prog("let x = 7; x as float / 2", Value::Float(3.5));
+    expect_prog("3 as complex", Value::Complex(3.0, 0.0));
+    expect_prog("2.5 as float as int", Value::Int(2));
+    expect_prog("let n = 10; let w = 4.0; (n as float / w) as int", Value::Int(2));
+}
+
+#[test]
+fn test_cast_errors() {
+    expect_type_error("let x: int = 1.5;");
+    expect_type_error("let x: Missing = 1;");
+    expect_type_error("(1 + 2i) as int");
+    expect_type_error("true as int");
+    expect_type_error("1 as bool");
+}
+
+#[test]
+fn test_cast_transpile() {
+    let prog = r#"
+let halves = [0.0; 0];
+iterate i = [1, 4] {
+    push(halves, i as float / 2);
+}
+let width = 640;
+let count: float = len(halves);
+let scale = (halves[3] as int + width) as float;
+let z: complex = scale;
+print count, " ", scale, " ", re(z);
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,136 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, expect_translated_output};
+
+use piske::value::Value;
+
+#[test]
+fn test_closure_call() {
+    let prog = r#"
+let add = |a: int, b: int| -> int { a + b };
+add(2, 3)
+    "#;
+    expect_prog(prog, Value::Int(5));
+
+    // the closure body is promoted to the return type
+    let prog = r#"
+let half = |x: int| -> float { x / 2 };
+let zero = || -> float { 0 };
+half(5)
+    "#;
+    expect_prog(prog, Value::Float(2.0));
+}
+
+#[test]
+fn test_function_arguments() {
+    let prog = r#"
+fn square(x: float) -> float { x * x }
+fn apply(f: fn(float) -> float, x: float) -> float {
+    f(x)
+}
+let a = apply(square, 3);
+let b = apply(|x: float| -> float { x + 1 }, 3);
+[a, b]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(9.0), Value::Float(4.0)]));
+
+    let prog = r#"
+fn iterate_map(f: fn(complex) -> complex, z0: complex, n: int) -> complex {
+    let z = z0;
+    iterate over [0, n) {
+        z = f(z);
+    }
+    z
+}
+let c = 1 + 1i;
+iterate_map(|z: complex| -> complex { z * z + c }, 0, 2)
+    "#;
+    expect_prog(prog, Value::Complex(1.0, 3.0));
+}
+
+#[test]
+fn test_closure_capture() {
+    // variables are captured by value when the closure is created
+    let prog = r#"
+let offset = 10;
+let shift = |x: int| -> int { x + offset };
+offset = 20;
+shift(1)
+    "#;
+    expect_prog(prog, Value::Int(11));
+
+    // closures can outlive the function call that created them
+    let prog = r#"
+fn adder(n: int) -> fn(int) -> int {
+    |x: int| -> int { x + n }
+}
+let add_one = adder(1);
+let add_ten = adder(10);
+let a = add_one(5);
+let b = add_ten(5);
+[a, b]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Int(6), Value::Int(15)]));
+
+    // modifying a captured variable only changes the closure's own copy, for that call
+    let prog = r#"
+let total = 1;
+let bump = |x: int| -> int {
+    total = total + x;
+    total
+};
+let a = bump(2);
+let b = bump(3);
+[a, b, total]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Int(3), Value::Int(4), Value::Int(1)]));
+
+    // nested closures capture through the enclosing closure
+    let prog = r#"
+let scale = 3;
+let make = |n: int| -> fn(int) -> int { |x: int| -> int { x * scale + n } };
+let f = make(1);
+f(2)
+    "#;
+    expect_prog(prog, Value::Int(7));
+}
+
+#[test]
+fn test_closure_errors() {
+    expect_type_error("let f = |x: int| -> int { 1.5 };");
+    expect_type_error("let f = |x: int| -> int { x }; f(1.5)");
+    expect_type_error("let f = |x: int| -> int { x }; f(1, 2)");
+    expect_type_error("fn apply(f: fn(int) -> int) -> int { f(1) } apply(3)");
+    expect_type_error("fn apply(f: fn(int) -> int) -> int { f(1) } \
+        apply(|x: float| -> float { x })");
+    expect_type_error("let x = 3; x(1)");
+    expect_type_error("let f = len;");
+}
+
+#[test]
+fn test_closure_transpile() {
+    let prog = r#"
+fn render(width: int, shade: fn(int) -> float) -> [float] {
+    let pixels = [0.0; 0];
+    iterate col = [0, width) {
+        push(pixels, shade(col));
+    }
+    pixels
+}
+fn brightness(col: int) -> float { col / 4.0 }
+let gamma = 2.0;
+let pixels = render(4, |col: int| -> float {
+    let b = brightness(col);
+    b ^ gamma
+});
+let plain = render(2, brightness);
+let plain_len = len(plain);
+iterate i = [0, len(pixels)) {
+    print pixels[i], " ", plain[i % plain_len];
+}
+    "#;
+    // the transpiled closures compile, and behave like the interpreted ones
+    expect_translated_output("closure_transpile", prog);
+}
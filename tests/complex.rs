@@ -1,10 +1,17 @@
 extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::examine_translated_source;
+
+use std::f64::consts::{PI, FRAC_PI_2};
 
 use piske::parse::program;
 use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
 use piske::value::Value;
+use piske::PiskeError;
 
-fn eval_prog(prog: &str) -> Result<Value, String> {
+fn eval_prog(prog: &str) -> Result<Value, PiskeError> {
     let ast = program(prog).unwrap();
     let mut state = State::default();
     SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
@@ -92,3 +99,63 @@ z
     "#;
     expect_prog(prog, Value::Complex(0.5, 0.5));
 }
+
+#[test]
+fn test_complex_power() {
+    // integer powers are exact
+    expect_prog(r#"(1 + 1i) ^ 2"#, Value::Complex(0.0, 2.0));
+    expect_prog(r#"let z = 1 + 2i; z ^ 3"#, Value::Complex(-11.0, -2.0));
+    expect_prog(r#"(1 + 1i) ^ 0"#, Value::Complex(1.0, 0.0));
+    expect_prog_approx_complex(r#"(1 + 1i) ^ (0 - 2)"#, 0.0, -0.5);
+
+    // real and complex exponents use the principal value
+    expect_prog_approx_complex(r#"(0 + 1i) ^ 0.5"#, 0.5f64.sqrt(), 0.5f64.sqrt());
+    expect_prog_approx_complex(r#"(0 + 1i) ^ (0 + 1i)"#, (-FRAC_PI_2).exp(), 0.0);
+    expect_prog_approx_complex(r#"2 ^ (0 + 1i)"#, 2f64.ln().cos(), 2f64.ln().sin());
+    expect_prog(r#"(0 + 0i) ^ (0 + 0i)"#, Value::Complex(1.0, 0.0));
+
+    // multibrot iteration
+    let prog = r#"
+let c = 0.25 + 0.5i;
+let z = 0i;
+iterate n = [0, 2) {
+    z = z ^ 3 + c;
+}
+z
+    "#;
+    expect_prog_approx_complex(prog, 0.078125, 0.46875);
+}
+
+#[test]
+fn test_complex_math() {
+    expect_prog_approx_complex(r#"exp(1i * 3.141592653589793)"#, -1.0, 0.0);
+    expect_prog_approx_complex(r#"exp(1 + 0i)"#, 1f64.exp(), 0.0);
+    expect_prog_approx_complex(r#"log(-1 + 0i)"#, 0.0, PI);
+    expect_prog(r#"sqrt(-4 + 0i)"#, Value::Complex(0.0, 2.0));
+    expect_prog_approx_complex(r#"sqrt(0 - 2i)"#, 1.0, -1.0);
+    expect_prog_approx_complex(r#"sin(1i)"#, 0.0, 1f64.sinh());
+    expect_prog_approx_complex(r#"cos(1i)"#, 1f64.cosh(), 0.0);
+    expect_prog(r#"abs(3 + 4i)"#, Value::Float(5.0));
+    expect_prog(r#"norm(3 + 4i)"#, Value::Float(25.0));
+    expect_prog(r#"arg(1i)"#, Value::Float(FRAC_PI_2));
+    expect_prog(r#"polar(1i)"#, Value::Tuple(vec![Value::Float(1.0), Value::Float(FRAC_PI_2)]));
+    expect_prog(r#"cis(0)"#, Value::Complex(1.0, 0.0));
+    expect_prog_approx_complex(r#"let (r, theta) = polar(1 + 1i); let u = cis(theta); r * u"#, 1.0, 1.0);
+}
+
+#[test]
+fn test_complex_math_transpile() {
+    let prog = r#"
+let z = 0.25 + 0.5i;
+let w = z ^ 3 + z;
+let p = (1 + 1i) ^ 0.5;
+let q = 2 ^ (0 + 1i);
+let r = z ^ (1 + 1i);
+let e = exp(1i * 3.141592653589793);
+let (m, t) = polar(1i);
+print w, " ", p, " ", q, " ", r, " ", e, " ", m, " ", t;
+print log(-1 + 0i), " ", sqrt(-4 + 0i), " ", sin(1i), " ", cos(1i), " ", norm(3 + 4i), " ",
+    arg(1i), " ", cis(0), " ", abs(z);
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,130 @@
+extern crate piske;
+extern crate sindra;
+
+use sindra::Node;
+
+use piske::ast::{Statement, Expression, Span, Spanned};
+use piske::diagnostic::Source;
+use piske::parse::program;
+use piske::glue;
+
+fn statements(prog: &str) -> Vec<Node<Statement>> {
+    let ast = program(prog).unwrap();
+    let statements = (ast.item.0).item.0.clone();
+    statements
+}
+
+fn declared_value(statement: &Node<Statement>) -> Node<Expression> {
+    match statement.item {
+        Statement::Declare(_, _, ref expr) => expr.clone(),
+        _ => panic!("expected declaration, found {:?}", statement.item),
+    }
+}
+
+/// Source code covered by the span of a node.
+fn spanned_text<'a, T: Spanned>(prog: &'a str, node: &T) -> &'a str {
+    let span = node.span().unwrap();
+    prog[span.start..span.end].trim()
+}
+
+#[test]
+fn test_statement_spans() {
+    let prog = "let a = 1;\nlet b = a * 2;\n\nprint a, b;";
+    let statements = statements(prog);
+    assert_eq!(statements.len(), 3);
+    assert_eq!(spanned_text(prog, &statements[0]), "let a = 1;");
+    assert_eq!(spanned_text(prog, &statements[1]), "let b = a * 2;");
+    assert_eq!(spanned_text(prog, &statements[2]), "print a, b;");
+    assert_eq!(statements[1].span().unwrap().start, 11);
+}
+
+#[test]
+fn test_expression_spans() {
+    let prog = "let x = (1 + 2) * f(3, 4)`;";
+    let value = declared_value(&statements(prog)[0]);
+    assert_eq!(spanned_text(prog, &value), "(1 + 2) * f(3, 4)`");
+    match value.item {
+        Expression::Infix { ref left, ref right, .. } => {
+            assert_eq!(spanned_text(prog, &**left), "(1 + 2)");
+            assert_eq!(spanned_text(prog, &**right), "f(3, 4)`");
+            match right.item {
+                Expression::Postfix { ref left, .. } => {
+                    assert_eq!(spanned_text(prog, &**left), "f(3, 4)");
+                },
+                ref other => panic!("expected postfix expression, found {:?}", other),
+            }
+        },
+        ref other => panic!("expected infix expression, found {:?}", other),
+    }
+
+    let prog = "let y = -a as float + if b { 1.0 } else { 2.0 };";
+    let value = declared_value(&statements(prog)[0]);
+    match value.item {
+        Expression::Infix { ref left, ref right, .. } => {
+            assert_eq!(spanned_text(prog, &**left), "-a as float");
+            assert_eq!(spanned_text(prog, &**right), "if b { 1.0 } else { 2.0 }");
+        },
+        ref other => panic!("expected infix expression, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_unspanned_node_locations() {
+    // identifiers, literals and type specifications have no spans of their own; errors about
+    // them are located at the enclosing parameter, field, expression or statement
+    let location = |prog: &str| glue::interpret(prog).unwrap_err().location()
+        .map(|(_, line, column)| (line, column));
+    assert_eq!(location("fn f(a: int, b: Missing) -> int { a }"), Some((1, 14)));
+    assert_eq!(location("struct S {\n    x: Missing\n}"), Some((2, 5)));
+    assert_eq!(location("let f = |x: Missing| -> int { 1 };"), Some((1, 10)));
+    assert_eq!(location("let c: Missing = 1;"), Some((1, 1)));
+    assert_eq!(location("let a = 1;\nlet b = 2 + undefined;"), Some((2, 13)));
+}
+
+#[test]
+fn test_source_location() {
+    let source = Source::new("test.psk", "let a = 1;\nlet b = \"é\" + a;\n");
+    assert_eq!(source.location(0), (1, 1));
+    assert_eq!(source.location(4), (1, 5));
+    assert_eq!(source.location(11), (2, 1));
+    // columns are counted in characters
+    assert_eq!(source.location(24), (2, 13));
+    // offsets past the end are located at the end
+    assert_eq!(source.location(100), (3, 1));
+}
+
+#[test]
+fn test_render() {
+    let prog = "let a = 1;\nlet b = a + \"one\";\n";
+    let value = declared_value(&statements(prog)[1]);
+    let source = Source::new("test.psk", prog);
+    let rendered = source.render("error", "incompatible types for +: int, string",
+        value.span().unwrap());
+    assert_eq!(rendered, "\
+error: incompatible types for +: int, string
+ --> test.psk:2:9
+  |
+2 | let b = a + \"one\";
+  |         ^^^^^^^^^");
+
+    // multi-line spans are underlined to the end of their first line
+    let prog = "\tlet x = if true {\n\t\t1\n\t} else {\n\t\t2\n\t};";
+    let source = Source::new("multi.psk", prog);
+    let rendered = source.render("warning", "unused variable 'x'", Span::new(9, prog.len()));
+    assert_eq!(rendered, "\
+warning: unused variable 'x'
+ --> multi.psk:1:10
+  |
+1 | \tlet x = if true {
+  | \t        ^^^^^^^^^");
+
+    let source = Source::new(
+        "long.psk", format!("{}let c = d;", "\n".repeat(11)));
+    let rendered = source.render("error", "undefined variable 'd'", Span::new(19, 20));
+    assert_eq!(rendered, "\
+error: undefined variable 'd'
+  --> long.psk:12:9
+   |
+12 | let c = d;
+   |         ^");
+}
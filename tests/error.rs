@@ -0,0 +1,109 @@
+extern crate piske;
+extern crate sindra;
+
+use piske::glue;
+use piske::PiskeError;
+
+#[test]
+fn test_parse_error() {
+    let err = glue::interpret("let a = 1;\nlet b = ;").unwrap_err();
+    match err {
+        PiskeError::Parse(ref detail) => {
+            assert!(detail.message.starts_with("expected"));
+            // syntax errors are logged as they are found, so that all of them are reported
+            assert!(detail.logged);
+        },
+        ref other => panic!("expected parse error, found {:?}", other),
+    }
+    assert_eq!(err.kind(), "parse");
+    assert_eq!(err.location(), Some(("<input>", 2, 9)));
+}
+
+#[test]
+fn test_symbol_error() {
+    let err = glue::interpret("let a = 1;\nlet b = c + a;\nlet d = f;").unwrap_err();
+    assert_eq!(err.kind(), "symbol");
+    assert_eq!(err.message(), "symbol 'c' does not exist in scope");
+    assert_eq!(err.location(), Some(("<input>", 2, 9)));
+    // all errors are logged; the error describes the first one
+    assert!(err.is_logged());
+    assert_eq!(err.notes(), &["1 further error(s) reported".to_string()]);
+}
+
+#[test]
+fn test_type_error() {
+    let err = glue::interpret("let a = 1;\nlet b = a + \"one\";").unwrap_err();
+    assert_eq!(err.kind(), "type");
+    assert_eq!(err.location(), Some(("<input>", 2, 9)));
+    assert_eq!(err.to_string(), "\
+error: incompatible types for +: ident:a, lit:\"one\"
+ --> <input>:2:9
+  |
+2 | let b = a + \"one\";
+  |         ^^^^^^^^^");
+    assert!(err.notes().is_empty());
+
+    let err = glue::transpile("let x: int = 1.5;").unwrap_err();
+    assert_eq!(err.kind(), "type");
+}
+
+#[test]
+fn test_error_followed_by_warning() {
+    // the warning for the unreachable arm is logged after the type error
+    let err = glue::interpret("\
+let a = 1 + \"one\";
+let b = match 1 { _ => 0, 1 => 1 };").unwrap_err();
+    assert_eq!(err.kind(), "type");
+    assert_eq!(err.location(), Some(("<input>", 1, 9)));
+}
+
+#[test]
+fn test_runtime_error() {
+    let err = glue::interpret("let a = [1, 2];\nlet b = a[1] + a[2];").unwrap_err();
+    match err {
+        PiskeError::Runtime(ref detail) => {
+            assert_eq!(detail.message, "index 2 out of bounds for array of length 2");
+            assert!(!detail.logged);
+        },
+        ref other => panic!("expected runtime error, found {:?}", other),
+    }
+    // the error is located at the innermost expression which failed
+    assert_eq!(err.location(), Some(("<input>", 2, 16)));
+}
+
+#[test]
+fn test_stdlib_error() {
+    let err = glue::interpret("let x = 0.5;\nprint clamp(x, 1, 0);").unwrap_err();
+    assert_eq!(err.kind(), "stdlib");
+    assert_eq!(err.message(), "clamp: invalid interval: [1, 0]");
+    assert_eq!(err.location(), Some(("<input>", 2, 7)));
+
+    // errors in imported functions are located in the imported file
+    let err = glue::interpret(r#"
+import "tests/import/errors/failing.psk";
+inverted(0.5)
+    "#).unwrap_err();
+    assert_eq!(err.kind(), "stdlib");
+    let (file, line, col) = err.location().unwrap();
+    assert!(file.ends_with("failing.psk"));
+    assert_eq!((line, col), (2, 5));
+}
+
+#[test]
+fn test_io_error() {
+    let err = glue::interpret_file("tests/missing.psk").unwrap_err();
+    assert_eq!(err.kind(), "io");
+    assert!(err.message().starts_with("unable to read file 'tests/missing.psk'"));
+    assert_eq!(err.location(), None);
+    assert!(err.to_string().starts_with("error: unable to read file"));
+}
+
+#[test]
+fn test_error_notes() {
+    let err = PiskeError::runtime("division by zero").with_note("in function 'f'")
+        .with_note("called from function 'g'");
+    assert_eq!(err.to_string(), "\
+error: division by zero
+  = note: in function 'f'
+  = note: called from function 'g'");
+}
@@ -4,12 +4,13 @@ extern crate sindra;
 use piske::parse::program;
 use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
 use piske::value::Value;
+use piske::PiskeError;
 
-fn eval(prog: &str) -> Result<Value, String> {
+fn eval(prog: &str) -> Result<Value, PiskeError> {
     let node = program(prog).unwrap();
     let mut state = State::default();
-    SymbolDefineVisitor::visit(&node, &mut state)?;
-    TypeComputationVisitor::visit(&node, &mut state)?;
+    SymbolDefineVisitor::visit(&node, &mut state).map_err(PiskeError::symbol)?;
+    TypeComputationVisitor::visit(&node, &mut state).map_err(PiskeError::type_error)?;
     EvaluateVisitor::visit(&node, &mut state)
 }
 
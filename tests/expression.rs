@@ -0,0 +1,82 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_fn_call_operands() {
+    let prog = r#"
+fn square(x: int) -> int {
+    x * x
+}
+square(3) + square(4) * 2
+    "#;
+    expect_prog(prog, Value::Int(41));
+
+    expect_prog("let z = 3 + 4i; re(z) * re(z) > 4", Value::Boolean(true));
+    expect_prog("-abs(-2.5) + 1", Value::Float(-1.5));
+    expect_prog("sqrt(16) as int div 3", Value::Int(1));
+    // calls with complex postfix operators
+    expect_prog("let z = 1 + 2i; im(z`) * 2", Value::Float(-4.0));
+    expect_prog("abs(-3)i", Value::Complex(0.0, 3.0));
+}
+
+#[test]
+fn test_ifelse_operands() {
+    expect_prog("let a = true; 1 + if a { 1 } else { 2 }", Value::Int(2));
+    expect_prog("let a = false; 1 + if a { 1 } else { 2 } * 10", Value::Int(21));
+    expect_prog("let x = -3; if x < 0 { -x } else { x } * 2", Value::Int(6));
+    expect_prog("let x = 4; 2 * if x > 2 { 1.5 } else { 0.0 }", Value::Float(3.0));
+    expect_prog("let x = 0; if x > 2 { 1 } else if x > 0 { 2 } else { 3 } == 3",
+        Value::Boolean(true));
+    expect_prog("let x = 1; match x { 1 => 10, _ => 20 } + 5", Value::Int(15));
+}
+
+#[test]
+fn test_block_operands() {
+    expect_prog("{ let a = 2; a * 3 } + { 4 }", Value::Int(10));
+    expect_prog("2 * { let a = 1; a + 1 } ^ 3", Value::Float(16.0));
+    expect_prog("-{ 2 + 3 }", Value::Int(-5));
+    expect_prog("let x = 5; (x - 1) * { x + 1 }", Value::Int(24));
+    expect_prog("let (p, q) = (1, 2); q * { p + 2 }", Value::Int(6));
+}
+
+#[test]
+fn test_grouping() {
+    expect_prog("(1 + 2) * 3", Value::Int(9));
+    expect_prog("2 ^ (1 + 1) ^ 3", Value::Float(256.0));
+    expect_prog("(2 ^ 2) ^ 3", Value::Float(64.0));
+    expect_prog("-(2 + 3) * 2", Value::Int(-10));
+    expect_prog("not (1 < 2) or (3 < 4 and true)", Value::Boolean(true));
+    expect_prog("(1.5 + 0.5) as int * 3", Value::Int(6));
+    expect_prog("let a = 7; a - (a div 2) * 2", Value::Int(1));
+}
+
+#[test]
+fn test_operand_errors() {
+    expect_type_error("1 + if true { 1 }");
+    expect_type_error("2 * { let a = \"two\"; a }");
+    expect_type_error("fn f() -> bool { true } f() + 1");
+}
+
+#[test]
+fn test_operand_transpile() {
+    let prog = r#"
+fn square(x: int) -> int {
+    x * x
+}
+let a = (1 + 2) * 3;
+let b = square(2) + square(3) * 2;
+let n = 3;
+let c = n * n as float / 2;
+let d = 1 + if n > 2 { 10 } else { 20 } * 2;
+let e = { let m = 2; m + 1 } * -(n - 5);
+let z = 3 + 4i;
+let f = re(z) * re(z) + im(z`);
+print a, " ", b, " ", c, " ", d, " ", e, " ", f;
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,123 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_global_counter() {
+    let prog = r#"
+set count = 0;
+fn increment() -> int {
+    set count = count + 1;
+    count
+}
+increment();
+increment();
+increment();
+count
+    "#;
+    expect_prog(prog, Value::Int(3));
+
+    // plain assignment also updates a global variable
+    let prog = r#"
+set total = 0.0;
+fn add(x: float) -> float {
+    total = total + x;
+    total
+}
+add(1.5);
+add(2);
+total
+    "#;
+    expect_prog(prog, Value::Float(3.5));
+}
+
+#[test]
+fn test_global_read() {
+    let prog = r#"
+set scale = 3;
+fn scaled(x: int) -> int {
+    x * scale
+}
+set scale = 4;
+scaled(5)
+    "#;
+    expect_prog(prog, Value::Int(20));
+}
+
+#[test]
+fn test_global_recursion() {
+    // values set by a call persist after it returns, even through recursion
+    let prog = r#"
+set calls = 0;
+fn fib(n: int) -> int {
+    set calls = calls + 1;
+    if n < 2 {
+        n
+    } else {
+        let a = fib(n - 1);
+        let b = fib(n - 2);
+        a + b
+    }
+}
+let f = fib(10);
+f * 1000 + calls
+    "#;
+    expect_prog(prog, Value::Int(55 * 1000 + 177));
+}
+
+#[test]
+fn test_global_shadowing() {
+    // set always refers to the global variable, even when shadowed by a local variable
+    let prog = r#"
+set x = 1;
+fn f() -> int {
+    let x = 10;
+    set x = x + 5;
+    x
+}
+let local = f();
+local * 100 + x
+    "#;
+    expect_prog(prog, Value::Int(1015));
+}
+
+#[test]
+fn test_global_errors() {
+    // globals must be declared at global scope before use
+    expect_type_error("fn f() -> int { set undeclared = 1; 0 }");
+    expect_type_error("fn f() -> int { set count = 1; 0 } set count = 0;");
+    // type of a global variable cannot change
+    expect_type_error("set count = 0; fn f() -> int { set count = \"many\"; 0 }");
+    expect_type_error("set count = 0; set count = true;");
+    // cannot declare a global with the same name as a visible local variable
+    expect_type_error("let x = 0; set x = 1;");
+    // constants cannot be set
+    expect_type_error("set pi = 3.0;");
+}
+
+#[test]
+fn test_global_transpile() {
+    let prog = r#"
+set count = 0;
+set total = 0.0;
+fn record(x: int) -> int {
+    set count = count + 1;
+    let root = sqrt(x);
+    set total = total + root;
+    count
+}
+let record_all = |n: int| -> int {
+    iterate i = [1, n] {
+        record(i * i)
+    }
+};
+let last = record_all(4);
+set count = count + last;
+print count, " ", total;
+    "#;
+    examine_translated_source(prog);
+}
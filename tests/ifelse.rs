@@ -78,3 +78,37 @@ b
     "#;
     expect_prog(prog, Value::Int(100));
 }
+
+#[test]
+fn test_else_if() {
+    let prog = r#"
+fn band(value: float) -> int {
+    if value < 0.25 {
+        0
+    } else if value < 0.5 {
+        1
+    } else if value < 0.75 {
+        2
+    } else {
+        3
+    }
+}
+let bands = [band(0.1), band(0.3), band(0.6), band(0.9)];
+bands[0] * 1000 + bands[1] * 100 + bands[2] * 10 + bands[3]
+    "#;
+
+    expect_prog(prog, Value::Int(123));
+
+    // else-if chains without a final else
+    let prog = r#"
+let a = 5;
+if a < 0 {
+    a = 0;
+} else if a < 10 {
+    a = 10;
+}
+a
+    "#;
+
+    expect_prog(prog, Value::Int(10));
+}
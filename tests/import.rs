@@ -0,0 +1,60 @@
+extern crate piske;
+extern crate sindra;
+
+use piske::glue;
+use piske::value::Value;
+
+#[test]
+fn test_import_file() {
+    // imports are resolved relative to the importing file; shading.psk and main.psk both import
+    // palettes.psk, which is only loaded once
+    let value = glue::interpret_file("tests/import/main.psk");
+    assert_eq!(value, Ok(Value::Array(vec![Value::Float(0.25), Value::Float(1.0),
+        Value::Float(0.5)])));
+}
+
+#[test]
+fn test_import_program() {
+    // imports in programs given as strings are resolved relative to the current directory
+    let prog = r#"
+import "tests/import/lib/palettes.psk";
+fire(0.5)
+    "#;
+    assert_eq!(glue::interpret(prog), Ok(Value::Float(1.0)));
+}
+
+#[test]
+fn test_import_errors() {
+    // functions imported by an imported file are not re-exported: this calls the library
+    // function `clamp(int, int, int)`, not the `clamp(float, float, float)` palettes.psk imports
+    assert_eq!(glue::interpret(r#"import "tests/import/lib/palettes.psk"; clamp(2, 0, 1)"#),
+        Ok(Value::Int(1)));
+    // imported names conflicting with existing definitions
+    assert!(glue::interpret(r#"
+fn fire(t: float) -> float { t }
+import "tests/import/lib/palettes.psk";
+    "#).is_err());
+    // imports must be at the top level
+    assert!(glue::interpret(r#"if true { import "tests/import/lib/palettes.psk"; }"#).is_err());
+
+    let err = glue::interpret(r#"import "tests/import/missing.psk";"#).unwrap_err();
+    assert_eq!(err.kind(), "io");
+    assert!(err.message().starts_with("unable to import 'tests/import/missing.psk'"));
+    assert_eq!(err.location(), Some(("<input>", 1, 1)));
+    let err = glue::interpret(r#"import "tests/import/errors/cycle_a.psk";"#).unwrap_err();
+    assert_eq!(err.kind(), "symbol");
+    assert!(err.message().starts_with("circular import"));
+    let err = glue::interpret(r#"import "tests/import/errors/not_library.psk";"#).unwrap_err();
+    assert_eq!(err.kind(), "symbol");
+    assert!(err.message().ends_with(
+        "may only contain function definitions, struct definitions and imports"));
+    // errors in imported files are located in the imported file
+    let (file, _, _) = err.location().unwrap();
+    assert!(file.ends_with("not_library.psk"));
+}
+
+#[test]
+fn test_import_transpile() {
+    let translated = glue::transpile_file("tests/import/main.psk").unwrap();
+    println!("{}", translated.as_str());
+}
@@ -0,0 +1,245 @@
+extern crate piske;
+extern crate sindra;
+extern crate tempfile;
+
+use std::io::{Read, Seek, SeekFrom};
+
+use piske::parse::program;
+use piske::glue;
+use piske::value::Value;
+use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, LintVisitor};
+use piske::visitor::lint::Lints;
+
+/// Messages of the warnings found by the lint visitor in a program, with the specified lints.
+fn warnings_with(prog: &str, lints: Lints) -> Vec<String> {
+    let mut state = State::default();
+    state.lints = lints;
+    let ast = program(prog).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    assert!(state.take_errors().is_empty());
+    LintVisitor::visit(&ast, &mut state);
+    state.take_warnings().into_iter().map(|warning| warning.message).collect()
+}
+
+fn warnings(prog: &str) -> Vec<String> {
+    warnings_with(prog, Lints::default())
+}
+
+#[test]
+fn test_no_warnings() {
+    let prog = r#"
+    fn square(x: float) -> float {
+        x * x
+    }
+    let total = 0.0;
+    iterate i = [0, 4) {
+        total = total + square(i);
+    }
+    let f = |x: float| -> float { 1.0 };
+    print total, f(2.0);
+    "#;
+    assert_eq!(warnings(prog), Vec::<String>::new());
+}
+
+#[test]
+fn test_unused_variables() {
+    let prog = r#"
+    let a = 1;
+    let b = 2;
+    b = 3;
+    let (c, d) = (4, 5);
+    iterate i = [0, 3) { print c; }
+    a
+    "#;
+    assert_eq!(warnings(prog), vec![
+        "unused variable 'i'",
+        "unused variable 'b'",
+        "unused variable 'd'",
+    ]);
+
+    // function parameters are checked; closure parameters are not
+    let prog = r#"
+    fn first(x: int, y: int) -> int { x }
+    let f = |z: int| -> int { 0 };
+    first(1, 2) + f(3)
+    "#;
+    assert_eq!(warnings(prog), vec!["unused variable 'y'"]);
+}
+
+#[test]
+fn test_unused_functions() {
+    let prog = r#"
+    fn used(x: int) -> int { x + 1 }
+    fn unused(x: int) -> int { x - 1 }
+    fn countdown(n: int) -> int {
+        if n > 0 { countdown(n - 1) } else { 0 }
+    }
+    fn as_value(x: float) -> float { x }
+    let f = as_value;
+    used(1) + f(2.0)
+    "#;
+    // recursive calls are not uses
+    assert_eq!(warnings(prog), vec![
+        "unused function 'unused'",
+        "unused function 'countdown'",
+    ]);
+}
+
+#[test]
+fn test_shadowed_declarations() {
+    let prog = r#"
+    let x = 1;
+    let y = {
+        let x = 2;
+        x * 2
+    };
+    let x = y;
+    iterate y = [0, x) { print y; }
+    "#;
+    assert_eq!(warnings(prog), vec![
+        "declaration of 'x' shadows a previous declaration",
+        "declaration of 'x' shadows a previous declaration",
+        "declaration of 'y' shadows a previous declaration",
+        // the first 'x' is never read
+        "unused variable 'x'",
+    ]);
+
+    // function parameters do not shadow variables declared at top level
+    let prog = "let n = 1;\nfn f(n: int) -> int { n }\nf(n)";
+    assert_eq!(warnings(prog), Vec::<String>::new());
+}
+
+#[test]
+fn test_implicit_promotions() {
+    let prog = "let a = 1.5 * 2;\nlet b: complex = a;\nb";
+    // disabled by default
+    assert_eq!(warnings(prog), Vec::<String>::new());
+    let lints = Lints { implicit_promotions: true, ..Lints::none() };
+    assert_eq!(warnings_with(prog, lints), vec![
+        "implicit promotion from 'int' to 'float'",
+        "implicit promotion from 'float' to 'complex'",
+    ]);
+}
+
+#[test]
+fn test_if_without_else() {
+    let prog = r#"
+    let a = 2;
+    if a > 1 { print a; }
+    let b = if a > 1 { 1 };
+    print b;
+    "#;
+    assert_eq!(warnings(prog), vec![
+        "value of 'if' without 'else' is used; it has no value when the condition is false",
+    ]);
+}
+
+#[test]
+fn test_unused_loop_values() {
+    let prog = r#"
+    let sum = 0;
+    iterate i = [0, 3) {
+        sum = sum + i;
+        sum * 2
+    }
+    let last = iterate i = [0, 3) { i * 2 };
+    let n = 0;
+    iterate while n < 3 {
+        n = n + 1;
+        n
+    }
+    print sum, last;
+    "#;
+    assert_eq!(warnings(prog), vec![
+        "value of loop is never read",
+        "value of loop is never read",
+    ]);
+    // the value of the last statement of a program is its result
+    assert_eq!(warnings("iterate i = [0, 3) { i }"), Vec::<String>::new());
+}
+
+#[test]
+fn test_unreachable_code() {
+    let prog = r#"
+    fn f(x: int) -> int {
+        return x;
+        print x;
+        x + 1
+    }
+    iterate i = [0, 3) {
+        if i > 1 {
+            break i;
+        }
+        continue;
+        print i;
+    }
+    f(1)
+    "#;
+    // reported once per block
+    assert_eq!(warnings(prog), vec![
+        "unreachable statement after 'return'",
+        "unreachable statement after 'continue'",
+    ]);
+}
+
+#[test]
+fn test_toggle_lints() {
+    let prog = "let a = 1;\nfn f() -> int { 1 }\nlet a = 2;\n";
+    assert_eq!(warnings(prog).len(), 4);
+    let lints = Lints { unused_variables: false, ..Lints::default() };
+    assert_eq!(warnings_with(prog, lints), vec![
+        "declaration of 'a' shadows a previous declaration",
+        "unused function 'f'",
+    ]);
+    assert_eq!(warnings_with(prog, Lints::none()), Vec::<String>::new());
+}
+
+#[test]
+fn test_set_lints_by_name() {
+    let mut lints = Lints::default();
+    lints.set("unused-variables", false).unwrap();
+    lints.set("implicit-promotions", true).unwrap();
+    assert_eq!(lints, Lints { unused_variables: false, ..Lints::all() });
+    lints.set("all", false).unwrap();
+    assert_eq!(lints, Lints::none());
+    assert_eq!(lints.set("unused_variables", true).unwrap_err(),
+        "unknown lint: 'unused_variables'");
+    for name in Lints::names() {
+        let mut lints = Lints::none();
+        lints.set(name, true).unwrap();
+        assert!(lints != Lints::none());
+    }
+}
+
+#[test]
+fn test_lints_through_glue() {
+    // lints never fail the program, whichever are enabled
+    let prog = "let a = 1;\nlet a = 2;\na";
+    assert_eq!(glue::interpret_with_lints(prog, Lints::all()).unwrap(), Value::Int(2));
+    assert_eq!(glue::interpret_with_lints(prog, Lints::none()).unwrap(), Value::Int(2));
+    assert!(glue::transpile_with_lints(prog, Lints::all()).is_ok());
+}
+
+#[test]
+fn test_warning_output() {
+    // each warning is written once, to the error output only
+    let mut state = State::default();
+    let mut stdout = tempfile::tempfile().unwrap();
+    let mut stderr = tempfile::tempfile().unwrap();
+    state.io.set_stdout(stdout.try_clone().unwrap());
+    state.io.set_stderr(stderr.try_clone().unwrap());
+    let ast = program("let a = 1;").unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    LintVisitor::visit(&ast, &mut state);
+
+    let mut out = String::new();
+    stdout.seek(SeekFrom::Start(0)).unwrap();
+    stdout.read_to_string(&mut out).unwrap();
+    assert_eq!(out, "");
+    let mut err = String::new();
+    stderr.seek(SeekFrom::Start(0)).unwrap();
+    stderr.read_to_string(&mut err).unwrap();
+    assert_eq!(err.matches("unused variable 'a'").count(), 1);
+}
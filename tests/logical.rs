@@ -0,0 +1,53 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_logical_ops() {
+    expect_prog("true and false", Value::Boolean(false));
+    expect_prog("true and true", Value::Boolean(true));
+    expect_prog("false or true", Value::Boolean(true));
+    expect_prog("false or false", Value::Boolean(false));
+    expect_prog("not true", Value::Boolean(false));
+    expect_prog("!false", Value::Boolean(true));
+}
+
+#[test]
+fn test_logical_precedence() {
+    // and binds tighter than or
+    expect_prog("true or false and false", Value::Boolean(true));
+    // comparisons bind tighter than logical operators
+    expect_prog("let iter = 10; let z = 3.5; z > 4 or iter >= 10", Value::Boolean(true));
+    // not applies to the whole comparison, ! only to its operand
+    expect_prog("let a = 3; not a == 4", Value::Boolean(true));
+    expect_prog("let b = false; !b == true", Value::Boolean(true));
+    expect_prog("not true or true", Value::Boolean(true));
+    expect_prog("not (true or true)", Value::Boolean(false));
+}
+
+#[test]
+fn test_short_circuit() {
+    // indexing out of bounds is a run-time error, so these only succeed if the right operand is
+    // never evaluated
+    expect_prog("let a = [1]; false and a[5] == 1", Value::Boolean(false));
+    expect_prog("let a = [1]; true or a[5] == 1", Value::Boolean(true));
+    expect_prog("let a = [1]; let n = len(a); let i = 5; i < n and a[i] == 1",
+        Value::Boolean(false));
+}
+
+#[test]
+fn test_logical_transpile() {
+    let prog = r#"
+let a = [1];
+let i = 5;
+let z = 3.5;
+if not (i < 1 and a[i] == 1) or z > 4 {
+    print "ok";
+}
+    "#;
+    examine_translated_source(prog);
+}
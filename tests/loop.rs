@@ -1,17 +1,10 @@
 extern crate piske;
+extern crate sindra;
 
-use piske::parse::program;
-use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
-use piske::value::Value;
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, expect_translated_output};
 
-fn expect_prog(prog: &str, val: Value) {
-    let ast = program(prog).unwrap();
-    let mut state = State::default();
-    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
-    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
-    let evaluated = EvaluateVisitor::visit(&ast, &mut state);
-    assert_eq!(evaluated, Ok(val));
-}
+use piske::value::{Value, ValueSet, SetInterval};
 
 #[test]
 fn test_interval_integer() {
@@ -97,3 +90,251 @@ a
     expect_prog(prog, Value::Int(50));
 
 }
+
+#[test]
+fn test_continue() {
+    let prog = r#"
+let sum = 0;
+iterate i = [0, 10) {
+    if i % 2 == 0 {
+        continue;
+    }
+    sum = sum + i;
+}
+sum
+    "#;
+    expect_prog(prog, Value::Int(25));
+
+    // iterations skipped by a continue don't change the value of the loop
+    let prog = r#"
+iterate i = [0, 10) {
+    if i > 6 {
+        continue;
+    }
+    i
+}
+    "#;
+    expect_prog(prog, Value::Int(6));
+
+    let prog = r#"
+let n = 0;
+let sum = 0;
+iterate while n < 10 {
+    n = n + 1;
+    if n % 3 == 0 {
+        continue;
+    }
+    sum = sum + n;
+}
+sum
+    "#;
+    expect_prog(prog, Value::Int(37));
+}
+
+#[test]
+fn test_labeled_break() {
+    // an unlabeled break exits the innermost loop, a labeled one the loop with that label
+    let prog = r#"
+let count = 0;
+let r = 'outer: iterate i = [0, 5) {
+    iterate j = [0, 5) {
+        count = count + 1;
+        if j == 2 {
+            break j;
+        }
+        if i == 3 {
+            break 'outer i * 10;
+        }
+        j
+    }
+};
+[r, count]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Int(30), Value::Int(10)]));
+
+    let prog = r#"
+let pairs = 0;
+'rows: iterate r = [0, 4) {
+    iterate c = [0, 4) {
+        if c > r {
+            continue 'rows;
+        }
+        pairs = pairs + 1;
+    }
+}
+pairs
+    "#;
+    expect_prog(prog, Value::Int(10));
+
+    // labels can be reused by nested loops; the innermost loop with the label is targeted
+    let prog = r#"
+'a: iterate i = [0, 3) {
+    'a: iterate j = [0, 3) {
+        break 'a j + 10;
+    }
+}
+    "#;
+    expect_prog(prog, Value::Int(10));
+}
+
+#[test]
+fn test_return_from_loop() {
+    let prog = r#"
+fn first_over(limit: int) -> int {
+    iterate i = [0, 100) {
+        if i * i > limit {
+            return i;
+        }
+        i
+    }
+}
+first_over(50)
+    "#;
+    expect_prog(prog, Value::Int(8));
+}
+
+#[test]
+fn test_loop_control_errors() {
+    expect_type_error("break 1;");
+    expect_type_error("continue;");
+    expect_type_error("iterate i = [0, 3) { break 'outer i; }");
+    expect_type_error("'outer: iterate i = [0, 3) { i } continue 'outer;");
+    // closures can't exit the loops they are defined in
+    expect_type_error("iterate i = [0, 3) { let f = |x: int| -> int { break x; }; i }");
+}
+
+#[test]
+fn test_loop_control_transpile() {
+    let prog = r#"
+let found = 'search: iterate row = [0, 10) {
+    iterate col = [0, 10) {
+        if row * col == 42 {
+            break 'search row * 100 + col;
+        }
+        if col > row {
+            continue 'search;
+        }
+        col
+    }
+};
+let n = 0;
+let sum = 0;
+let last = iterate while n < 10 {
+    n = n + 1;
+    if n % 3 == 0 {
+        continue;
+    }
+    sum = sum + n;
+    n
+};
+// iterations ended by continue keep the value of the iteration before
+let kept = iterate i = [0, 6) {
+    if i > 3 {
+        continue;
+    }
+    i
+};
+print found, " ", sum, " ", last, " ", kept;
+    "#;
+    expect_translated_output("loop_control", prog);
+}
+
+#[test]
+fn test_interval_step() {
+    let prog = r#"
+let a = 0;
+iterate i = [0 .. 3 .. 10) {
+    a = a + i;
+}
+a
+    "#;
+    expect_prog(prog, Value::Int(3 + 6 + 9));
+
+    // descending intervals, exclusive and inclusive
+    let prog = r#"
+let a = 0;
+iterate i = [10 .. -1 .. 0) {
+    a = a + 1;
+}
+a
+    "#;
+    expect_prog(prog, Value::Int(10));
+
+    let prog = r#"
+let a = 0;
+let last = iterate i = [10 .. -1 .. 0] {
+    a = a + 1;
+    i
+}
+a * 100 + last
+    "#;
+    expect_prog(prog, Value::Int(1100));
+}
+
+#[test]
+fn test_interval_float_step() {
+    // floating-point steps do not accumulate rounding error
+    let prog = r#"
+let count = 0;
+iterate x = [1.0 .. 0.01 .. 2.0) {
+    count = count + 1;
+}
+count
+    "#;
+    expect_prog(prog, Value::Int(100));
+
+    let prog = r#"
+let count = 0;
+let last = iterate x = [1.0 .. 0.1 .. 2.0] {
+    count = count + 1;
+    x
+}
+last
+    "#;
+    expect_prog(prog, Value::Float(2.0));
+
+    // integer steps and bounds are promoted in floating-point intervals
+    let prog = r#"
+let last = iterate x = [0.5 .. 1 .. 3] {
+    x
+}
+last
+    "#;
+    expect_prog(prog, Value::Float(2.5));
+}
+
+#[test]
+fn test_interval_step_errors() {
+    expect_type_error("iterate i = [0 .. 0 .. 10) { i }");
+    expect_type_error("iterate x = [0.0 .. -0.0 .. 1.0) { x }");
+    expect_type_error("iterate i = [0 .. 0.5 .. 10) { i }");
+    expect_type_error("iterate i = [0 .. true .. 10) { i }");
+}
+
+#[test]
+fn test_interval_step_transpile() {
+    let prog = r#"
+let ascending = [0.0; 0];
+iterate x = [1.0 .. 0.25 .. 2.0] {
+    push(ascending, x);
+}
+let descending = [0; 0];
+iterate i = [10 .. -2 .. 0) {
+    push(descending, i);
+}
+print ascending, " ", descending;
+    "#;
+    expect_translated_output("interval_step", prog);
+}
+
+#[test]
+fn test_interval_step_mismatch() {
+    // intervals whose start and step have different types fail to iterate instead of panicking
+    let set = ValueSet::Interval(SetInterval {
+        start: Value::Int(0),
+        end: Value::Int(10),
+        end_inclusive: false,
+        step: Value::Float(0.5),
+    });
+    assert!(set.elements().is_err());
+}
@@ -4,7 +4,7 @@ macro_rules! expr_stmt {
 }
 #[macro_export]
 macro_rules! decl_stmt {
-    ($ident:expr, $expr:expr) => ( Node::new(Statement::Declare($ident, $expr)) )
+    ($ident:expr, $expr:expr) => ( Node::new(Statement::Declare($ident, None, $expr)) )
 }
 #[macro_export]
 macro_rules! assign_stmt {
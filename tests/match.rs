@@ -0,0 +1,91 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, expect_translated_output};
+
+use piske::value::Value;
+
+#[test]
+fn test_match_int() {
+    let prog = r#"
+fn classify(n: int) -> int {
+    match n {
+        0 => 100,
+        [0, 10) => 1,
+        [10, 100) => 2,
+        _ => 3,
+    }
+}
+let classes = [classify(0), classify(5), classify(50), classify(-4)];
+classes[0] * 1000 + classes[1] * 100 + classes[2] * 10 + classes[3]
+    "#;
+    expect_prog(prog, Value::Int(100000 + 100 + 20 + 3));
+}
+
+#[test]
+fn test_match_float() {
+    let prog = r#"
+let t = 0.5;
+match t {
+    [0, 0.5) => "low",
+    [0.5, 1] => "high",
+    _ => "out of range",
+}
+    "#;
+    expect_prog(prog, Value::String("high".to_string()));
+
+    let prog = r#"
+let t = -0.5;
+match t {
+    -0.5 => { let a = 1; a + 1 },
+    _ => 0,
+}
+    "#;
+    expect_prog(prog, Value::Int(2));
+}
+
+#[test]
+fn test_match_arm_promotion() {
+    let prog = r#"
+let n = 3;
+match n {
+    [0, 5] => 1,
+    _ => 2.5,
+}
+    "#;
+    expect_prog(prog, Value::Float(1.0));
+}
+
+#[test]
+fn test_match_exhaustive() {
+    expect_type_error("let n = 1; match n { [0, 10) => 1, [10, 20) => 2 }");
+    // the smallest int value is not covered
+    expect_type_error("let n = 1; match n { [-9223372036854775807, 0) => 1, \
+        [0, 9223372036854775807] => 2 }");
+    expect_type_error("let t = 0.5; match t { [0.0, 1.0] => 1 }");
+    expect_type_error("let n = 1; match n { 1.5 => 1, _ => 2 }");
+    expect_type_error("let n = 1; match n { 1 => 1, _ => true }");
+    expect_type_error("let b = true; match b { _ => 1 }");
+}
+
+#[test]
+fn test_match_transpile() {
+    let prog = r#"
+let palette = [0; 0];
+iterate i = [0, 40) {
+    let t = i / 40.0;
+    let band = match t {
+        [0, 0.25) => 0,
+        [0.25, 0.5) => 1,
+        _ => match i {
+            [20, 30) => 2,
+            _ => 3,
+        },
+    };
+    push(palette, band);
+}
+print palette;
+    "#;
+    expect_translated_output("match", prog);
+}
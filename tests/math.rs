@@ -0,0 +1,146 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use std::f64::consts::{PI, E};
+
+use piske::parse::program;
+use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
+use piske::value::Value;
+use piske::PiskeError;
+
+fn eval_prog(prog: &str) -> Result<Value, PiskeError> {
+    let ast = program(prog).unwrap();
+    let mut state = State::default();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    EvaluateVisitor::visit(&ast, &mut state)
+}
+
+fn expect_prog_approx(prog: &str, expected: f64) {
+    let val = eval_prog(prog);
+    if let Ok(Value::Float(f)) = val {
+        assert!((f - expected).abs() < 1e-9, "expected {}, found {}", expected, f);
+    } else {
+        panic!("Expected float, found {:?}", val);
+    }
+}
+
+#[test]
+fn test_math_functions() {
+    expect_prog_approx("sin(pi / 2)", 1.0);
+    expect_prog_approx("cos(0.0)", 1.0);
+    expect_prog_approx("tan(pi / 4)", 1.0);
+    expect_prog_approx("asin(1.0)", PI / 2.0);
+    expect_prog_approx("acos(1.0)", 0.0);
+    expect_prog_approx("atan(1.0)", PI / 4.0);
+    expect_prog_approx("atan2(1.0, -1.0)", 3.0 * PI / 4.0);
+    expect_prog_approx("sqrt(2)", 2f64.sqrt());
+    expect_prog_approx("exp(1)", E);
+    expect_prog_approx("log(e * e)", 2.0);
+    expect_prog_approx("log10(1000)", 3.0);
+
+    expect_prog("floor(-1.5)", Value::Float(-2.0));
+    expect_prog("ceil(-1.5)", Value::Float(-1.0));
+    expect_prog("round(2.5)", Value::Float(3.0));
+    expect_prog("lerp(2.0, 4.0, 0.25)", Value::Float(2.5));
+}
+
+#[test]
+fn test_min_max_clamp() {
+    expect_prog("min(3, -2)", Value::Int(-2));
+    expect_prog("max(3, -2)", Value::Int(3));
+    // mixed arguments are promoted to float
+    expect_prog("min(1, 2.5)", Value::Float(1.0));
+    expect_prog("max(1, 2.5)", Value::Float(2.5));
+
+    expect_prog("clamp(12, 0, 10)", Value::Int(10));
+    expect_prog("clamp(-0.5, 0, 1)", Value::Float(0.0));
+    expect_prog("clamp(0.25, 0, 1)", Value::Float(0.25));
+    match eval_prog("clamp(0.5, 1, 0)") {
+        Err(PiskeError::Stdlib(detail)) => {
+            assert_eq!(detail.message, "clamp: invalid interval: [1, 0]");
+            // the error is located at the call
+            assert_eq!(detail.span.map(|span| span.start), Some(0));
+        },
+        other => panic!("expected standard library error, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_math_overloads() {
+    // real and complex versions of the same function
+    expect_prog("sqrt(-4 + 0i)", Value::Complex(0.0, 2.0));
+    assert!(eval_prog("sqrt(-4.0)").map(|v| match v {
+        Value::Float(f) => f.is_nan(),
+        _ => false
+    }).unwrap());
+
+    // user-defined functions can replace library overloads
+    let prog = r#"
+fn floor(x: float) -> float { 42.0 }
+floor(1.5)
+    "#;
+    expect_prog(prog, Value::Float(42.0));
+}
+
+#[test]
+fn test_user_definitions_take_precedence() {
+    // programs written before `clamp` was a library function call their own definition, even
+    // where a library overload would match the arguments more closely
+    let prog = r#"
+fn clamp(x: float, lo: float, hi: float) -> float {
+    if x < lo { lo - 10.0 } else if x > hi { hi + 10.0 } else { x }
+}
+clamp(2, 0, 1)
+    "#;
+    expect_prog(prog, Value::Float(11.0));
+
+    // library overloads are still called with arguments no user-defined overload accepts
+    let prog = r#"
+fn min(values: [int]) -> int {
+    let lowest = values[0];
+    iterate i = [1, len(values)) {
+        lowest = min(lowest, values[i]);
+    }
+    lowest
+}
+let listed = min([3, 1, 2]);
+let pair = min(5, 4);
+listed + pair
+    "#;
+    expect_prog(prog, Value::Int(5));
+    examine_translated_source(prog);
+}
+
+#[test]
+fn test_math_constants() {
+    expect_prog("pi", Value::Float(PI));
+    expect_prog("tau", Value::Float(2.0 * PI));
+    expect_prog("e", Value::Float(E));
+    expect_prog("let r = 2; pi * r", Value::Float(2.0 * PI));
+
+    // constants can be shadowed, but not assigned to
+    expect_prog("let e = 3; e", Value::Int(3));
+    expect_type_error("pi = 3.0;");
+    expect_type_error("pi(1)");
+}
+
+#[test]
+fn test_math_transpile() {
+    let prog = r#"
+let a = sin(pi / 2);
+let b = sqrt(2);
+let c = floor(-1.5);
+let d = min(1, 2.5);
+let f = clamp(12, 0, 10);
+let g = atan2(1.0, -1.0);
+let h = lerp(0, tau, 0.5);
+let j = log(exp(2.0));
+let k = sqrt(-4 + 0i);
+print a, " ", b, " ", c, " ", d, " ", f, " ", g, " ", h, " ", j, " ", k, " ", e;
+    "#;
+    examine_translated_source(prog);
+}
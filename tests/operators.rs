@@ -0,0 +1,94 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_translated_output, expect_translated_error};
+
+use piske::value::Value;
+
+#[test]
+fn test_modulo_floor_divide() {
+    expect_prog("7 % 3", Value::Int(1));
+    expect_prog("7 div 2", Value::Int(3));
+    // results round toward negative infinity; the remainder takes the sign of the divisor
+    expect_prog("-7 div 2", Value::Int(-4));
+    expect_prog("-7 % 2", Value::Int(1));
+    expect_prog("7 % -2", Value::Int(-1));
+    expect_prog("7.5 % 2", Value::Float(1.5));
+    expect_prog("-7.5 div 2", Value::Float(-4.0));
+}
+
+#[test]
+fn test_bitwise_ops() {
+    expect_prog("6 & 3", Value::Int(2));
+    expect_prog("6 | 3", Value::Int(7));
+    expect_prog("6 xor 3", Value::Int(5));
+    expect_prog("1 << 4", Value::Int(16));
+    expect_prog("-16 >> 2", Value::Int(-4));
+}
+
+#[test]
+fn test_operator_precedence() {
+    // shifts bind looser than arithmetic
+    expect_prog("1 + 2 << 1", Value::Int(6));
+    // & binds tighter than xor, which binds tighter than |
+    expect_prog("1 | 6 xor 3 & 5", Value::Int(7));
+    // bitwise operators bind tighter than comparisons
+    expect_prog("5 & 3 == 1", Value::Boolean(true));
+    expect_prog("let a = 2; a << 1 < 5", Value::Boolean(true));
+    expect_prog("10 - 7 % 4 * 2", Value::Int(4));
+}
+
+#[test]
+fn test_checkerboard() {
+    let prog = r#"
+let white = 0;
+iterate row = [0, 4) {
+    iterate col = [0, 4) {
+        white = white + (row div 2 + col div 2) % 2;
+        white
+    }
+}
+white
+    "#;
+    expect_prog(prog, Value::Int(8));
+}
+
+#[test]
+fn test_operators_transpile() {
+    let prog = r#"
+let texture = [0; 0];
+iterate x = [0, 8) {
+    iterate y = [0, 8) {
+        push(texture, ((x xor y) & 3 | 1 << 2) + (x - 4) div 3 + (y - 4) % 3);
+    }
+}
+print texture;
+    "#;
+    expect_translated_output("operators", prog);
+}
+
+#[test]
+fn test_operator_errors_transpile() {
+    // invalid operands are reported as errors by both the interpreter and transpiled programs
+    let prog = r#"
+let zero = 0;
+print 7 % zero;
+    "#;
+    expect_translated_error("modulo_by_zero", prog, "attempt to divide integer value by zero");
+    let prog = r#"
+let zero = 0;
+print 7 div zero;
+    "#;
+    expect_translated_error("divide_by_zero", prog, "attempt to divide integer value by zero");
+    let prog = r#"
+let bits = 64;
+print 1 << bits;
+    "#;
+    expect_translated_error("shift_overflow", prog, "invalid shift amount: 64");
+    let prog = r#"
+let bits = -1;
+print 16 >> bits;
+    "#;
+    expect_translated_error("negative_shift", prog, "invalid shift amount: -1");
+}
@@ -0,0 +1,169 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_overload_by_type() {
+    let prog = r#"
+fn sqr_mag(x: float) -> float { x * x }
+fn sqr_mag(z: complex) -> float {
+    let x = re(z);
+    let y = im(z);
+    x * x + y * y
+}
+let a = sqr_mag(-2.5);
+let b = sqr_mag(3 + 4i);
+[a, b]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(6.25), Value::Float(25.0)]));
+
+    // overloads can differ in the number of parameters
+    let prog = r#"
+fn area(r: float) -> float { 3 * r * r }
+fn area(w: float, h: float) -> float { w * h }
+let a = area(2.0);
+let b = area(2.0, 3.5);
+[a, b]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(12.0), Value::Float(7.0)]));
+
+    // redefining a function with the same parameter types replaces it
+    let prog = r#"
+fn f(x: int) -> int { x + 1 }
+fn f(x: float) -> float { x / 2 }
+fn f(x: int) -> int { x + 2 }
+f(1)
+    "#;
+    expect_prog(prog, Value::Int(3));
+}
+
+#[test]
+fn test_overload_promotion() {
+    // an exact match is preferred over one requiring promotion
+    let prog = r#"
+fn describe(x: int) -> string { "int" }
+fn describe(x: float) -> string { "float" }
+let a = describe(1);
+let b = describe(1.0);
+a + " " + b
+    "#;
+    expect_prog(prog, Value::String("int float".to_string()));
+
+    // otherwise, the arguments are promoted to the only matching overload
+    let prog = r#"
+fn half(x: float) -> float { x / 2 }
+fn half(s: string) -> string { s }
+half(3)
+    "#;
+    expect_prog(prog, Value::Float(1.5));
+}
+
+#[test]
+fn test_overload_most_specific() {
+    // an int can be promoted to either float or complex; float is preferred, since it can itself be
+    // promoted to complex
+    let prog = r#"
+fn kind(x: float) -> string { "float" }
+fn kind(z: complex) -> string { "complex" }
+let a = kind(1);
+let b = kind(1i);
+a + " " + b
+    "#;
+    expect_prog(prog, Value::String("float complex".to_string()));
+
+    let prog = r#"
+fn kind(x: float, y: float) -> string { "float" }
+fn kind(x: complex, y: complex) -> string { "complex" }
+let a = kind(1, 2.5);
+let b = kind(1, 2i);
+a + " " + b
+    "#;
+    expect_prog(prog, Value::String("float complex".to_string()));
+}
+
+#[test]
+fn test_overload_structs() {
+    let prog = r#"
+struct Camera { center: complex, size: complex }
+fn shift(z: complex, by: complex) -> complex { z + by }
+fn shift(camera: Camera, by: complex) -> Camera {
+    Camera { center: shift(camera.center, by), size: camera.size }
+}
+let camera = Camera { center: 0 + 0i, size: 2 + 2i };
+let moved = shift(camera, 1 + 1i);
+moved.center
+    "#;
+    expect_prog(prog, Value::Complex(1.0, 1.0));
+}
+
+#[test]
+fn test_overload_stdlib() {
+    expect_prog("abs(-3)", Value::Int(3));
+    expect_prog("abs(-2.5)", Value::Float(2.5));
+    expect_prog("abs(3 + 4i)", Value::Float(5.0));
+
+    // user-defined functions can add overloads to library functions
+    let prog = r#"
+fn abs(values: [float]) -> [float] {
+    let result = [0.0; 0];
+    iterate i = [0, len(values)) {
+        push(result, abs(values[i]));
+    }
+    result
+}
+abs([-1.5, 2])
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(1.5), Value::Float(2.0)]));
+}
+
+#[test]
+fn test_overload_errors() {
+    let overloads = "fn f(x: int) -> int { x } fn f(z: complex) -> complex { z }";
+    // no overload accepts a bool
+    expect_type_error(&format!("{} f(true)", overloads));
+    // a float can only be promoted to complex
+    expect_prog(&format!("{} f(1.5)", overloads), Value::Complex(1.5, 0.0));
+    // neither overload is more specific than the other
+    expect_type_error("fn g(x: int, y: float) -> float { y } fn g(x: float, y: int) -> float { x } \
+        g(1, 1)");
+    // the most specific definition must be at least as specific in every parameter
+    expect_type_error("fn g(x: float, y: float) -> float { y } \
+        fn g(x: complex, y: complex) -> float { 1.0 } fn g(x: int, y: complex) -> float { 2.0 } \
+        g(1, 2.5)");
+    // calls which can't be resolved leave the type of enclosing expressions unknown
+    expect_type_error(&format!("{} f(true) + 1", overloads));
+    // no overload takes two arguments
+    expect_type_error(&format!("{} f(1, 2)", overloads));
+    // overloaded functions can't be used as values
+    expect_type_error(&format!("{} let g = f;", overloads));
+}
+
+#[test]
+fn test_overload_transpile() {
+    let prog = r#"
+fn norm(x: float) -> float { if x < 0 { 0 - x } else { x } }
+fn norm(p: (float, float)) -> float {
+    let (x, y) = p;
+    let nx = norm(x);
+    let ny = norm(y);
+    nx + ny
+}
+fn norm(v: [float]) -> float {
+    let total = 0.0;
+    iterate i = [0, len(v)) {
+        let n = norm(v[i]);
+        total = total + n;
+        total
+    }
+}
+let a = norm(-2.5);
+let b = norm((1.5, -2.0));
+let c = norm([1, -2, 3.5]);
+print a, " ", b, " ", c, " ", abs(-7), " ", abs(-0.5), " ", abs(3 + 4i);
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,81 @@
+extern crate piske;
+extern crate sindra;
+
+use sindra::Node;
+
+use piske::ast::{Statement, Spanned};
+use piske::glue;
+use piske::parse::{program_recovering, MAX_PARSES};
+
+/// Parse a program in recovering mode, returning the remaining statements and the offsets of the
+/// syntax errors.
+fn recover(prog: &str) -> (Vec<Node<Statement>>, Vec<usize>) {
+    let (ast, errors) = program_recovering(prog);
+    let statements = (ast.item.0).item.0.clone();
+    (statements, errors.iter().map(|e| e.offset).collect())
+}
+
+#[test]
+fn test_valid_program() {
+    let (statements, errors) = recover("let a = 1;\nlet b = a + 1;\nprint b;");
+    assert_eq!(statements.len(), 3);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn test_multiple_errors() {
+    let prog = "let a = ;\nlet b = 1;\nlet c = 2 * ;\nprint b;";
+    let (statements, errors) = recover(prog);
+    assert_eq!(errors, vec![8, 33]);
+    // statements containing errors are skipped, without moving the following statements
+    assert_eq!(statements.len(), 2);
+    let starts: Vec<usize> = statements.iter().map(|s| s.span().unwrap().start).collect();
+    assert!(prog[starts[0]..].starts_with("let b = 1;"));
+    assert!(prog[starts[1]..].starts_with("print b;"));
+}
+
+#[test]
+fn test_resync_in_block() {
+    // the parser resynchronizes at the '}' closing the block containing the error
+    let prog = "fn f() -> int {\n    let x = 1 +;\n    2\n}\nlet s = \"; }\";\nlet y = f( ;\nlet z = 3;";
+    let (statements, errors) = recover(prog);
+    assert_eq!(errors, vec![31, 67]);
+    // 'f', 's' and 'z' remain; ';' and '}' in strings are not boundaries
+    assert_eq!(statements.len(), 3);
+
+    // stray closing braces are skipped
+    let (statements, errors) = recover("let a = 1; }\nlet b = a;");
+    assert_eq!(errors, vec![11]);
+    assert_eq!(statements.len(), 2);
+
+    // nothing can be recovered from a block which is never closed
+    let (statements, errors) = recover("fn f() -> int { let a = 1;");
+    assert_eq!(errors, vec![26]);
+    assert!(statements.is_empty());
+}
+
+#[test]
+fn test_recovery_limit() {
+    // each skipped statement costs a parse of the whole program, so recovery gives up eventually
+    let prog = "let a = ;\n".repeat(MAX_PARSES + 5);
+    let (statements, errors) = recover(&prog);
+    assert_eq!(errors.len(), MAX_PARSES);
+    assert_eq!(errors[MAX_PARSES - 1], 10 * (MAX_PARSES - 1) + 8);
+    assert!(statements.is_empty());
+}
+
+#[test]
+fn test_interpret_reports_all_errors() {
+    let err = glue::interpret("let a = ;\nlet b = 1;\nlet c = 2 * ;\nprint b;").unwrap_err();
+    assert_eq!(err.kind(), "parse");
+    assert_eq!(err.location(), Some(("<input>", 1, 9)));
+    assert!(err.is_logged());
+    assert_eq!(err.notes(), &["1 further syntax error(s) reported".to_string()]);
+
+    // the remaining statements are still checked; a syntax error is still reported first
+    let err = glue::transpile("let a = if true { 1 + } else { 2 };\nlet q = undefined;")
+        .unwrap_err();
+    assert_eq!(err.kind(), "parse");
+    assert_eq!(err.location(), Some(("<input>", 1, 23)));
+    assert!(err.notes().is_empty());
+}
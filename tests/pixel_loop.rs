@@ -0,0 +1,122 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog_with_state, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+use piske::visitor::State;
+
+/// Pixel values of the current image, in row-major order.
+fn pixels(state: &State) -> Vec<f64> {
+    let image_data = &state.std_env.image_data;
+    let (rows, cols) = (image_data.dims.rows as usize, image_data.dims.cols as usize);
+    let mut values = vec![];
+    for row in 0..rows {
+        for col in 0..cols {
+            values.push(image_data.values[col * rows + row]);
+        }
+    }
+    values
+}
+
+#[test]
+fn test_pixel_loop() {
+    let prog = r#"
+set_image_dims(2, 3);
+let visited = 0;
+iterate (row, col) over pixels {
+    visited = visited + 1;
+    row * 10 + col
+}
+visited
+    "#;
+    let mut state = State::default();
+    expect_prog_with_state(prog, Value::Int(6), &mut state);
+    assert_eq!(pixels(&state), vec![0.0, 1.0, 2.0, 10.0, 11.0, 12.0]);
+
+    let prog = r#"
+set_image_dims(2, 2);
+let scale = 0.5;
+iterate (y, x) over pixels {
+    let z = project(y, x, 0+0i, 2+2i);
+    let real = re(z);
+    real * scale
+}
+    "#;
+    let mut state = State::default();
+    expect_prog_with_state(prog, Value::Empty, &mut state);
+    assert_eq!(pixels(&state), vec![-0.5, -0.5, 0.0, 0.0]);
+}
+
+#[test]
+fn test_pixel_loop_control() {
+    // continue leaves a pixel unchanged, break stops setting pixels
+    let prog = r#"
+set_image_dims(2, 3);
+'image: iterate (row, col) over pixels {
+    if col == 1 {
+        continue;
+    }
+    iterate over [0, 10) {
+        if row == 1 and col == 2 {
+            break 'image 0.0;
+        }
+    };
+    1.5
+}
+    "#;
+    let mut state = State::default();
+    expect_prog_with_state(prog, Value::Empty, &mut state);
+    assert_eq!(pixels(&state), vec![1.5, 0.0, 1.5, 1.5, 0.0, 0.0]);
+
+    // return from within a pixel loop
+    let prog = r#"
+fn first_set() -> int {
+    iterate (row, col) over pixels {
+        if row == 1 {
+            return col + 100;
+        }
+        2.0
+    };
+    0
+}
+set_image_dims(3, 2);
+first_set()
+    "#;
+    let mut state = State::default();
+    expect_prog_with_state(prog, Value::Int(100), &mut state);
+    assert_eq!(pixels(&state), vec![2.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_pixel_loop_errors() {
+    // pixel values must be numbers
+    expect_type_error(r#"iterate (row, col) over pixels { "white" }"#);
+    expect_type_error("iterate (row, col) over pixels { 1 + 2i }");
+    expect_type_error(r#"iterate (row, col) over pixels { print "pixel"; }"#);
+    // pixel coordinates are integers
+    expect_type_error("iterate (row, col) over pixels { row = 1.5; 0.0 }");
+}
+
+#[test]
+fn test_pixel_loop_transpile() {
+    let prog = r#"
+set_image_dims(4, 4);
+let count = 0;
+'image: iterate (row, col) over pixels {
+    if row == col {
+        continue;
+    }
+    count = count + 1;
+    let c = project(row, col, 0+0i, 2+2i);
+    let magnitude = re(c * c`);
+    if count > 10 {
+        break 'image 0.0;
+    }
+    magnitude
+};
+print count;
+    "#;
+    examine_translated_source(prog);
+}
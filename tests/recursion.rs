@@ -0,0 +1,179 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_prog_with_state, examine_translated_source};
+
+use piske::glue;
+use piske::parse::program;
+use piske::value::Value;
+use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
+use piske::visitor::state::DEFAULT_MAX_CALL_DEPTH;
+
+#[test]
+fn test_recursion() {
+    let prog = r#"
+fn fact(n: int) -> int {
+    if n <= 1 {
+        1
+    } else {
+        let rest = fact(n - 1);
+        n * rest
+    }
+}
+fact(10)
+    "#;
+    expect_prog(prog, Value::Int(3628800));
+
+    // each call has its own copy of 'a' and 'b'
+    let prog = r#"
+fn fib(n: int) -> int {
+    if n < 2 {
+        n
+    } else {
+        let a = fib(n - 1);
+        let b = fib(n - 2);
+        a + b
+    }
+}
+fib(15)
+    "#;
+    expect_prog(prog, Value::Int(610));
+}
+
+#[test]
+fn test_recursive_subdivision() {
+    // loop variables and locals are not overwritten by recursive calls made inside the loop
+    let prog = r#"
+fn count_cells(depth: int) -> int {
+    if depth == 0 {
+        1
+    } else {
+        let total = 0;
+        iterate i = [0, 4) {
+            let cells = count_cells(depth - 1);
+            total = total + cells + i - i;
+            total
+        }
+    }
+}
+count_cells(4)
+    "#;
+    expect_prog(prog, Value::Int(256));
+}
+
+#[test]
+fn test_call_frames() {
+    // each call has its own variables, which closures created during the call capture
+    let prog = r#"
+fn sum_to(n: int) -> int {
+    let add_n = |x: int| -> int { x + n };
+    if n == 0 {
+        add_n(0)
+    } else {
+        let inner = sum_to(n - 1);
+        add_n(inner)
+    }
+}
+sum_to(10)
+    "#;
+    expect_prog(prog, Value::Int(55));
+}
+
+#[test]
+fn test_function_calls_function() {
+    let prog = r#"
+fn square(x: float) -> float {
+    x * x
+}
+fn norm(x: float, y: float) -> float {
+    let xx = square(x);
+    let yy = square(y);
+    xx + yy
+}
+norm(3, 4)
+    "#;
+    expect_prog(prog, Value::Float(25.0));
+}
+
+#[test]
+fn test_max_call_depth() {
+    let prog = r#"
+fn depth(n: int) -> int {
+    if n == 0 {
+        0
+    } else {
+        let d = depth(n - 1);
+        d + 1
+    }
+}
+depth(20)
+    "#;
+    let mut state = State::default();
+    state.max_call_depth = 21;
+    expect_prog_with_state(prog, Value::Int(20), &mut state);
+
+    let mut state = State::default();
+    state.max_call_depth = 20;
+    let ast = program(prog).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    let evaluated = EvaluateVisitor::visit(&ast, &mut state).map_err(|e| e.message().to_string());
+    assert_eq!(evaluated,
+        Err("maximum call depth (20) exceeded when calling function 'depth'".to_string()));
+
+    // unbounded recursion fails cleanly with the default maximum depth, even though the test
+    // thread's stack is smaller than the stack needed for that depth
+    let prog = r#"
+fn forever(n: int) -> int {
+    let next = forever(n + 1);
+    next
+}
+forever(0)
+    "#;
+    assert_eq!(glue::interpret(prog).map_err(|e| e.message().to_string()),
+        Err(format!("maximum call depth ({}) exceeded when calling function 'forever'",
+            DEFAULT_MAX_CALL_DEPTH)));
+}
+
+#[test]
+fn test_deep_recursion() {
+    // recursion close to the default maximum depth, through a body with nested loops and
+    // conditionals (which use the most stack per call)
+    let prog = format!(r#"
+fn walk(depth: int) -> int {{
+    if depth == 0 {{
+        0
+    }} else {{
+        let total = 0;
+        iterate i = [0, 2) {{
+            if i == 0 {{
+                let sub = walk(depth - 1);
+                total = total + sub + 1;
+            }}
+            total
+        }}
+    }}
+}}
+walk({})
+    "#, DEFAULT_MAX_CALL_DEPTH - 1);
+    assert_eq!(glue::interpret(&prog).map_err(|e| e.message().to_string()),
+        Ok(Value::Int(DEFAULT_MAX_CALL_DEPTH as i64 - 1)));
+}
+
+#[test]
+fn test_recursion_transpile() {
+    let prog = r#"
+fn fib(n: int) -> int {
+    if n < 2 {
+        n
+    } else {
+        let a = fib(n - 1);
+        let b = fib(n - 2);
+        a + b
+    }
+}
+print fib(20);
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,111 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+fn ints(values: &[i64]) -> Value {
+    Value::Array(values.iter().map(|&i| Value::Int(i)).collect())
+}
+
+fn floats(values: &[f64]) -> Value {
+    Value::Array(values.iter().map(|&f| Value::Float(f)).collect())
+}
+
+/// Program collecting the elements of an int set into an array, in iteration order.
+fn collect_ints(set: &str) -> String {
+    format!("let a = [0; 0]; iterate x = {} {{ push(a, x); }}; a", set)
+}
+
+#[test]
+fn test_set_literal() {
+    expect_prog(&collect_ints("{1, 4, 9}"), ints(&[1, 4, 9]));
+    // repeated elements are only included once
+    expect_prog(&collect_ints("{3, 1, 3, 2, 1}"), ints(&[3, 1, 2]));
+    expect_prog(&collect_ints("{2 * 3, 1 + 1,}"), ints(&[6, 2]));
+
+    // elements are promoted to float if any of them is a float
+    let prog = "let a = [0.0; 0]; iterate x = {1, 2.5, 1.0} { push(a, x); }; a";
+    expect_prog(prog, floats(&[1.0, 2.5]));
+
+    let prog = r#"
+let total = 0;
+iterate over {1, 2, 3} {
+    total = total + 1;
+}
+total
+    "#;
+    expect_prog(prog, Value::Int(3));
+}
+
+#[test]
+fn test_set_algebra() {
+    expect_prog(&collect_ints("{1, 2, 3} | {3, 4}"), ints(&[1, 2, 3, 4]));
+    expect_prog(&collect_ints("[0, 10) & {5, 12, 0}"), ints(&[0, 5]));
+    expect_prog(&collect_ints("[0, 6) - {1, 3}"), ints(&[0, 2, 4, 5]));
+
+    // difference binds tighter than intersection, which binds tighter than union
+    expect_prog(&collect_ints("{1} | {2, 3} & {3, 4} - {4}"), ints(&[1, 3]));
+    expect_prog(&collect_ints("({1} | {2, 3}) & {1, 2}"), ints(&[1, 2]));
+    expect_prog(&collect_ints("[0, 4) - [1, 3) - {0}"), ints(&[3]));
+
+    // int elements are promoted when combined with a float set
+    let prog = "let a = [0.0; 0]; iterate x = [0, 3) | [0.0 .. 0.5 .. 1.0] { push(a, x); }; a";
+    expect_prog(prog, floats(&[0.0, 1.0, 2.0, 0.5]));
+}
+
+#[test]
+fn test_set_builder() {
+    expect_prog(&collect_ints("{ x | x in [0, 20), x % 3 == 0 }"), ints(&[0, 3, 6, 9, 12, 15, 18]));
+    expect_prog(&collect_ints("{ x | x in {5, 7} }"), ints(&[5, 7]));
+
+    // the condition can refer to other variables, and builders can be combined with other sets
+    let prog = r#"
+let limit = 10;
+let a = [0; 0];
+iterate n = { n | n in [2, 30), n * n < limit } | { n | n in [20, 25), n % 2 == 1 } {
+    push(a, n);
+};
+a
+    "#;
+    expect_prog(prog, ints(&[2, 3, 21, 23]));
+
+    // the builder variable is local to the builder
+    let prog = r#"
+let x = 100;
+let count = 0;
+iterate over { x | x in [0, 5), x > 1 } {
+    count = count + 1;
+}
+count * 1000 + x
+    "#;
+    expect_prog(prog, Value::Int(3100));
+}
+
+#[test]
+fn test_set_errors() {
+    expect_type_error(r#"iterate x = {1, "two"} { x; }"#);
+    expect_type_error("iterate x = {1 + 2i} { x; }");
+    expect_type_error("iterate x = { x | x in [0, 5), x + 1 } { x; }");
+    expect_type_error("iterate x = { y | x in [0, 5) } { x; }");
+    expect_type_error("iterate x = { x | x in {true, false} } { x; }");
+    // element types are checked through the set
+    expect_type_error("iterate x = {1, 2} | {0.5} { let y: int = x; }");
+}
+
+#[test]
+fn test_set_transpile() {
+    let prog = r#"
+let total = 0.0;
+let count = 0;
+let last = iterate x = { x | x in [0, 20), x % 3 == 0 } - {6, 9} | {1.5, 3} & [0.0 .. 0.5 .. 2.0] {
+    total = total + x;
+    count = count + 1;
+    x
+};
+print count, " ", total, " ", last;
+    "#;
+    examine_translated_source(prog);
+}
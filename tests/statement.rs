@@ -26,12 +26,18 @@ fn test_expr_stmt() {
 
 #[test]
 fn test_decl_stmt() {
-    assert_eq!(statement("let a = 4;"), Ok(Node::new(Statement::Declare(ident!(a), int!(4)))));
+    assert_eq!(statement("let a = 4;"),
+        Ok(Node::new(Statement::Declare(ident!(a), None, int!(4)))));
+    assert_eq!(statement("let a: float = 4;"),
+        Ok(Node::new(Statement::Declare(ident!(a),
+            Some(TypeSpec::Named(ident!(float))), int!(4)))));
 }
 
 #[test]
 fn test_assign_stmt() {
     assert_eq!(statement("a = 3"), Ok(Node::new(Statement::Assign(ident!(a), int!(3)))));
+    assert_eq!(statement("a = f(3)"), Ok(Node::new(Statement::Assign(ident!(a),
+        Node::new(Expression::FnCall { name: ident!(f), args: vec![int!(3)] })))));
 }
 
 #[test]
@@ -1,5 +1,6 @@
 extern crate tempfile;
 extern crate piske;
+extern crate sindra;
 
 use piske::value::Value;
 
@@ -0,0 +1,84 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+use piske::parse::program;
+use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor};
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn test_concatenation() {
+    expect_prog(r#""frame" + "_" + "one""#, string("frame_one"));
+    expect_prog(r#"let name = "mandelbrot"; let ext = ".png"; name + ext"#,
+        string("mandelbrot.png"));
+}
+
+#[test]
+fn test_str() {
+    expect_prog("str(42)", string("42"));
+    expect_prog("str(-2.5)", string("-2.5"));
+    expect_prog("str(true)", string("true"));
+    expect_prog("str(1 + 2i)", string("1+2i"));
+    expect_prog(r#"let i = 7; let suffix = str(i); "frame_" + suffix"#, string("frame_7"));
+}
+
+#[test]
+fn test_format() {
+    expect_prog(r#"format("iter_{:04}.png", 12)"#, string("iter_0012.png"));
+    expect_prog(r#"format("{:.3}", 3.14159)"#, string("3.142"));
+    expect_prog(r#"format("{:8.2}|", 2.5)"#, string("    2.50|"));
+    expect_prog(r#"format("{:<5}|{:^5}|{:>5}", 1, "ab", true)"#, string("1    | ab  | true"));
+    expect_prog(r#"format("{:05}", -42)"#, string("-0042"));
+    expect_prog(r#"format("{:.1}", 1.25 + 0.5i)"#, string("1.2+0.5i"));
+    expect_prog(r#"format("{{{}}}", "braces")"#, string("{braces}"));
+    expect_prog(r#"format("no placeholders")"#, string("no placeholders"));
+
+    // the format string does not have to be a literal
+    expect_prog(r#"let fmt = "{}x{}"; format(fmt, 640, 480)"#, string("640x480"));
+}
+
+#[test]
+fn test_string_errors() {
+    expect_type_error(r#""a" - "b""#);
+    expect_type_error(r#""a" + 1"#);
+    expect_type_error("str([1, 2])");
+    expect_type_error("str(1, 2)");
+    expect_type_error("format()");
+    expect_type_error("format(1, 2)");
+    expect_type_error(r#"format("{} {}", 1)"#);
+    expect_type_error(r#"format("{}", 1, 2)"#);
+    expect_type_error(r#"format("{:x}", 1)"#);
+    expect_type_error(r#"format("{}", [1])"#);
+
+    // format strings which are not literals are checked when evaluated
+    let mut state = State::default();
+    let ast = program(r#"let fmt = "{} {}"; format(fmt, 1)"#).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    let evaluated = EvaluateVisitor::visit(&ast, &mut state).map_err(|e| e.message().to_string());
+    assert_eq!(evaluated, Err("too few arguments for format string \"{} {}\"".to_string()));
+}
+
+#[test]
+fn test_string_transpile() {
+    let prog = r#"
+let prefix = "frame";
+iterate i = [8, 10] {
+    let number = str(i);
+    let name = prefix + "_" + number + ".png";
+    let padded = format("{}_{:03}.png", prefix, i);
+    print name, " ", padded;
+}
+let z = 0.5 - 0.25i;
+let label = format("z = {:.2}, |{:^6}|, {:>5.1}", z, "\"q\"", 2.25);
+print label;
+print "z: ", z, " ", str(z);
+    "#;
+    examine_translated_source(prog);
+}
@@ -0,0 +1,71 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_translated_output};
+
+use piske::value::Value;
+
+#[test]
+fn test_struct_member_access() {
+    let prog = r#"
+struct Camera {
+    center: complex,
+    size: complex,
+}
+let cam = Camera { center: -0.5 + 0i, size: 3 + 3i };
+cam.size
+    "#;
+    expect_prog(prog, Value::Complex(3.0, 3.0));
+
+    // fields may be specified in any order, and are promoted to the declared field type
+    let prog = r#"
+struct Camera { center: complex, size: complex }
+let cam = Camera { size: 3, center: 1.5 };
+cam.center
+    "#;
+    expect_prog(prog, Value::Complex(1.5, 0.0));
+}
+
+#[test]
+fn test_struct_nested() {
+    let prog = r#"
+struct Point { x: float, y: float }
+struct Line { start: Point, end: Point }
+let line = Line {
+    start: Point { x: 1.0, y: 2.0 },
+    end: Point { x: 4.0, y: 6.0 },
+};
+line.end.y - line.start.y
+    "#;
+    expect_prog(prog, Value::Float(4.0));
+}
+
+#[test]
+fn test_struct_function() {
+    let prog = r#"
+struct Camera { center: complex, size: complex }
+fn zoom(cam: Camera, factor: float) -> Camera {
+    Camera { center: cam.center, size: cam.size * factor }
+}
+let cam = zoom(Camera { center: -0.5 + 0i, size: 3 + 3i }, 0.5);
+let width = re(cam.size);
+let offset = im(cam.center);
+width + offset
+    "#;
+    expect_prog(prog, Value::Float(1.5));
+}
+
+#[test]
+fn test_struct_transpile() {
+    let prog = r#"
+struct Camera { center: complex, size: complex }
+fn zoom(cam: Camera, factor: float) -> Camera {
+    Camera { center: cam.center, size: cam.size * factor }
+}
+let cam = zoom(Camera { center: -0.5 + 0i, size: 3 + 3i }, 0.5);
+print re(cam.size);
+print cam;
+    "#;
+    expect_translated_output("struct", prog);
+}
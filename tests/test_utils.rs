@@ -2,9 +2,16 @@
 
 extern crate tempfile;
 extern crate piske;
+extern crate sindra;
 
-use std::fs::File;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
 
+use sindra::log::LogPriority;
+
+use piske::glue;
 use piske::value::Value;
 use piske::parse::program;
 use piske::visitor::{State, SymbolDefineVisitor, TypeComputationVisitor, EvaluateVisitor,
@@ -32,6 +39,73 @@ pub fn examine_translated_source(prog: &str) {
     println!("{}", translated.as_str());
 }
 
+/// Transpile a program into a cargo project named `name` (in the `target/transpiled` directory),
+/// build and run it, and check that it prints the same output as the interpreter.
+pub fn expect_translated_output(name: &str, prog: &str) {
+    let (mut state, output) = new_state_with_temp_output();
+    let ast = program(prog).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    EvaluateVisitor::visit(&ast, &mut state).unwrap();
+    let mut expected = String::new();
+    {
+        use std::io::{Read, Seek, SeekFrom};
+        let mut output = &output;
+        output.seek(SeekFrom::Start(0)).unwrap();
+        output.read_to_string(&mut expected).unwrap();
+    }
+
+    let run = run_translated(name, prog);
+    assert!(run.status.success(), "transpiled program failed:\n{}",
+        String::from_utf8_lossy(&run.stderr));
+    assert_eq!(String::from_utf8_lossy(&run.stdout), expected);
+}
+
+/// Check that a program fails with the error `message` both when interpreted and when transpiled
+/// (into a cargo project named `name`) and run.
+pub fn expect_translated_error(name: &str, prog: &str, message: &str) {
+    let mut state = State::default();
+    let ast = program(prog).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    let evaluated = EvaluateVisitor::visit(&ast, &mut state).map_err(|e| e.message().to_string());
+    assert_eq!(evaluated, Err(message.to_string()));
+
+    let run = run_translated(name, prog);
+    assert!(!run.status.success(), "transpiled program unexpectedly succeeded");
+    // build warnings are also written to stderr, before the program's own output
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert_eq!(stderr.lines().last(), Some(format!("ERROR: {}", message).as_str()));
+}
+
+/// Transpile a program into a cargo project named `name` (in the `target/transpiled` directory),
+/// then build and run it.
+fn run_translated(name: &str, prog: &str) -> Output {
+    let translated = glue::transpile(prog).unwrap();
+    let transpiled_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/transpiled");
+    let proj_dir = transpiled_dir.join(name);
+    fs::create_dir_all(proj_dir.join("src")).unwrap();
+    File::create(proj_dir.join("Cargo.toml")).unwrap()
+        .write_all(glue::cargo_manifest(name).as_bytes()).unwrap();
+    File::create(proj_dir.join("src/main.rs")).unwrap()
+        .write_all(translated.as_str().as_bytes()).unwrap();
+
+    // the dependencies of transpiled programs are those of piske itself, so they are already
+    // available offline; they are built once for all transpiled programs, in a shared target
+    // directory
+    Command::new(env!("CARGO")).args(&["run", "--offline", "--quiet"])
+        .env("CARGO_TARGET_DIR", transpiled_dir.join("target"))
+        .current_dir(&proj_dir).output().unwrap()
+}
+
+pub fn expect_type_error(prog: &str) {
+    let mut state = State::default();
+    let ast = program(prog).unwrap();
+    SymbolDefineVisitor::visit(&ast, &mut state).unwrap();
+    TypeComputationVisitor::visit(&ast, &mut state).unwrap();
+    assert_eq!(state.logger.flush(), Some(LogPriority::Error));
+}
+
 pub fn test_output(mut file: &File, expected: &str) {
     use std::io::{Read, Seek, SeekFrom};
 
@@ -1,9 +1,12 @@
 extern crate piske;
 extern crate sindra;
+extern crate psk_std;
 
 mod test_utils;
 use test_utils::examine_translated_source;
 
+use piske::glue;
+
 #[test]
 fn test_examine() {
     let prog = r#"
@@ -38,3 +41,12 @@ write("output.png");
 
     examine_translated_source(prog);
 }
+
+#[test]
+fn test_cargo_manifest() {
+    // built from the repository, transpiled programs use its version of the standard library
+    let manifest = glue::cargo_manifest("mandelbrot");
+    assert!(manifest.contains("name = \"mandelbrot\""));
+    assert!(manifest.contains(&format!("psk_std = {{ path = '{}/psk_std', version = \"{}\" }}",
+        env!("CARGO_MANIFEST_DIR"), psk_std::VERSION)));
+}
@@ -0,0 +1,73 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_type_error, examine_translated_source};
+
+use piske::value::Value;
+
+#[test]
+fn test_tuple_literal() {
+    expect_prog("(1, 2.5)", Value::Tuple(vec![Value::Int(1), Value::Float(2.5)]));
+    expect_prog("let t: (int, bool) = (3, true); t",
+        Value::Tuple(vec![Value::Int(3), Value::Boolean(true)]));
+    // parenthesized expressions are not tuples
+    expect_prog("(1 + 2)", Value::Int(3));
+}
+
+#[test]
+fn test_destructure() {
+    let prog = r#"
+fn escape(c: complex, max_iter: int) -> (complex, int) {
+    let z = 0 + 0i;
+    let iter = 0;
+    let escaped = false;
+    iterate while not escaped and iter < max_iter {
+        z = z * z + c;
+        iter = iter + 1;
+        let r = re(z);
+        escaped = r > 2;
+    }
+    (z, iter)
+}
+let (z, iter) = escape(1 + 0i, 10);
+[re(z), iter as float]
+    "#;
+    expect_prog(prog, Value::Array(vec![Value::Float(5.0), Value::Float(3.0)]));
+
+    // _ ignores an element
+    expect_prog("let (_, b) = (1, 2.5); b", Value::Float(2.5));
+}
+
+#[test]
+fn test_destructure_assign() {
+    expect_prog("let a = 1; let b = 2; (a, b) = (b, a); [a, b]",
+        Value::Array(vec![Value::Int(2), Value::Int(1)]));
+
+    // elements are promoted to the types of the existing variables
+    expect_prog("let x = 1.5; let y = 2; (x, y) = (3, 4); x", Value::Float(3.0));
+}
+
+#[test]
+fn test_tuple_errors() {
+    expect_type_error("let (a, b) = (1, 2, 3);");
+    expect_type_error("let (a, b) = 5;");
+    expect_type_error("let a = 1; let b = 2; (a, b) = (1.5, 2);");
+    expect_type_error("let t: (int, int) = (1, true);");
+}
+
+#[test]
+fn test_tuple_transpile() {
+    let prog = r#"
+fn divmod(a: int, b: int) -> (int, int) {
+    (a div b, a % b)
+}
+let (q, r) = divmod(17, 5);
+let x = 0.5;
+let y = 0;
+(x, y) = (q, r);
+let pair = divmod(-7, 2);
+print q, " ", r, " ", x, " ", pair;
+    "#;
+    examine_translated_source(prog);
+}
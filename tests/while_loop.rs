@@ -0,0 +1,71 @@
+extern crate piske;
+extern crate sindra;
+
+mod test_utils;
+use test_utils::{expect_prog, expect_translated_output};
+
+use piske::value::Value;
+
+#[test]
+fn test_while_loop() {
+    let prog = r#"
+let i = 0;
+let total = 0;
+iterate while i < 5 {
+    i = i + 1;
+    total = total + i;
+    total
+}
+    "#;
+    expect_prog(prog, Value::Int(15));
+
+    // a false condition on entry never runs the body
+    let prog = r#"
+let i = 10;
+iterate while i < 5 {
+    i = i + 1;
+    i
+};
+i
+    "#;
+    expect_prog(prog, Value::Int(10));
+}
+
+#[test]
+fn test_while_loop_break() {
+    // Newton's method for sqrt(2), stopping once the update is small enough
+    let prog = r#"
+let x = 1.0;
+let steps = 0;
+iterate while true {
+    let next = x - (x * x - 2) / (2 * x);
+    steps = steps + 1;
+    if (next - x) * (next - x) < 0.000000000001 {
+        break steps;
+    }
+    x = next;
+    0
+}
+    "#;
+    expect_prog(prog, Value::Int(5));
+}
+
+#[test]
+fn test_while_loop_transpile() {
+    let prog = r#"
+let z = 0 + 0i;
+let c = -0.5 + 0.5i;
+let iter = 0;
+let escaped = iterate while iter < 100 {
+    z = z * z + c;
+    iter = iter + 1;
+    let magnitude = re(z * z`);
+    if magnitude > 4 {
+        break true;
+    }
+    false
+};
+print iter, " ", escaped;
+    "#;
+    expect_translated_output("while_loop", prog);
+}
@@ -1,11 +1,13 @@
 extern crate cargo;
 
 use std::path::{Path, PathBuf};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::fs::File;
 
 extern crate piske;
 
+use piske::visitor::lint::Lints;
+
 fn create_project_dir(proj_dir: &str) -> PathBuf {
     let config = cargo::util::Config::default().unwrap();
     let new_opts = cargo::ops::NewOptions::new(None, true, false, proj_dir, None);
@@ -48,23 +50,21 @@ macro_rules! try_file {
     }}
 }
 
-fn transpile_file(file_name: &str, proj_dir: &str) {
+fn transpile_file(file_name: &str, proj_dir: &str, lints: Lints) {
     let proj_name = Path::new(proj_dir).file_name().unwrap().to_str().unwrap();
     let proj_dir = create_project_dir(proj_dir);
 
-    let transpiled = try_file!(File::open(file_name), mut file, {
-        let mut source = String::new();
-        try_file!(file.read_to_string(&mut source), {
-            match piske::glue::transpile(&source) {
-                Ok(tokens) => tokens,
-                Err(e) => {
-                    writeln!(::std::io::stderr(), "interpreting failed: {}", e).unwrap();
-                    ::std::process::exit(1);
-                }
+    // imports are resolved relative to the file
+    let transpiled = match piske::glue::transpile_file_with_lints(file_name, lints) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            // logged errors have already been reported
+            if !e.is_logged() {
+                writeln!(::std::io::stderr(), "{}", e).unwrap();
             }
-        })
-
-    });
+            ::std::process::exit(1);
+        }
+    };
 
     // write main script
     try_file!(File::create(proj_dir.join("src/main.rs")), mut f, {
@@ -73,27 +73,46 @@ fn transpile_file(file_name: &str, proj_dir: &str) {
 
     // write Cargo.toml
     try_file!(File::create(proj_dir.join("Cargo.toml")), mut f, {
-        let cargo_contents = format!(
-r#"[package]
-name = "{}"
-version = "0.1.0"
-authors = ["Jamie Blondin <jblondin@gmail.com>"]
-
-[dependencies]
-psk_std = "0.1"
-image = "0.18"
-"#, proj_name);
-
+        let cargo_contents = piske::glue::cargo_manifest(proj_name);
         try_file!(f.write_all(cargo_contents.as_bytes()), {});
     });
 
 }
 
+fn usage(program: &str) -> ! {
+    writeln!(::std::io::stderr(), "Usage: {} [-W <lint>] [-A <lint>] <file> <project_path>",
+        program).unwrap();
+    writeln!(::std::io::stderr(),
+        "  -W, --warn <lint>   enable the warnings of lint <lint> (or 'all' lints)").unwrap();
+    writeln!(::std::io::stderr(),
+        "  -A, --allow <lint>  disable the warnings of lint <lint> (or 'all' lints)").unwrap();
+    writeln!(::std::io::stderr(), "Lints: {}", Lints::names().join(", ")).unwrap();
+    ::std::process::exit(1);
+}
+
 fn main() {
     let args: Vec<String> = ::std::env::args().collect();
-    if args.len() != 3 {
-        writeln!(::std::io::stderr(), "Usage: {} <file> <project_path>", args[0]).unwrap();
-        ::std::process::exit(1);
+    let mut lints = Lints::default();
+    let mut paths = vec![];
+    let mut args_iter = args.iter().skip(1);
+    while let Some(arg) = args_iter.next() {
+        let enabled = match arg.as_str() {
+            "-W" | "--warn" => true,
+            "-A" | "--allow" => false,
+            _ if !arg.starts_with('-') => {
+                paths.push(arg.clone());
+                continue;
+            },
+            _ => usage(&args[0])
+        };
+        let name = args_iter.next().unwrap_or_else(|| usage(&args[0]));
+        if let Err(e) = lints.set(name, enabled) {
+            writeln!(::std::io::stderr(), "{}", e).unwrap();
+            usage(&args[0]);
+        }
+    }
+    if paths.len() != 2 {
+        usage(&args[0]);
     }
-    transpile_file(&args[1], &args[2]);
+    transpile_file(&paths[0], &paths[1], lints);
 }